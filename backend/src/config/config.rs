@@ -1,13 +1,18 @@
 // Copyright (c) IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use backend::did_resolution::DEFAULT_DID_CACHE_TTL;
+use backend::submitter::DEFAULT_GAS_POOL_SIZE;
 use dotenvy::dotenv;
 use std::env;
+use std::time::Duration;
 
 #[warn(dead_code)]
 pub struct Config {
     pub api_key: String,
     pub tcp_listner: String,
+    pub gas_pool_size: usize,
+    pub did_cache_ttl: Duration,
 }
 
 impl Config {
@@ -17,6 +22,14 @@ impl Config {
         Self {
             api_key: env::var("API_KEY").expect("API_KEY must be set"),
             tcp_listner: env::var("TCP_LISTNER").expect("TCP_LISTNER must be set"),
+            gas_pool_size: env::var("GAS_POOL_SIZE")
+                .map(|size| size.parse().expect("GAS_POOL_SIZE must be a number"))
+                .unwrap_or(DEFAULT_GAS_POOL_SIZE),
+            did_cache_ttl: env::var("DID_CACHE_TTL_SECS")
+                .map(|secs| {
+                    Duration::from_secs(secs.parse().expect("DID_CACHE_TTL_SECS must be a number"))
+                })
+                .unwrap_or(DEFAULT_DID_CACHE_TTL),
         }
     }
 }
@@ -0,0 +1,104 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+};
+use backend::credentials::{load_credential, revoke_credential, IssuedCredential};
+use backend::verification::{decode_jwt, verify_jwt, AccreditationCheck, ValidationReport};
+use iota_sdk::types::base_types::{IotaAddress, ObjectID};
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use super::{error_response, ErrorResponse, HandlerError};
+use crate::state::AppState;
+
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct VerifyRequest {
+    /// A VC JWT or a VP JWT.
+    jwt: String,
+    /// Federation in which the issuer must still be accredited.
+    #[schema(value_type = Option<String>)]
+    federation_id: Option<ObjectID>,
+    #[schema(value_type = Option<String>)]
+    issuer_address: Option<IotaAddress>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/{id}",
+    tag = "credentials",
+    params(("id" = String, Path, description = "Credential id")),
+    responses(
+        (status = 200, description = "Issued credential", body = IssuedCredential),
+        (status = 404, description = "Unknown credential", body = ErrorResponse),
+    )
+)]
+pub async fn get_credential(
+    Path(id): Path<String>,
+) -> Result<Json<IssuedCredential>, HandlerError> {
+    match load_credential(&id) {
+        Ok(Some(credential)) => Ok(Json(credential)),
+        Ok(None) => Err(error_response(
+            StatusCode::NOT_FOUND,
+            anyhow::anyhow!("Credential {} not found", id),
+        )),
+        Err(err) => Err(error_response(StatusCode::INTERNAL_SERVER_ERROR, err)),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/verify",
+    tag = "credentials",
+    request_body = VerifyRequest,
+    security(()),
+    responses(
+        (status = 200, description = "Validation report, `valid` is false when a check failed", body = ValidationReport),
+        (status = 400, description = "Malformed JWT", body = ErrorResponse),
+        (status = 500, description = "Checks could not be run, e.g. the node is unreachable", body = ErrorResponse),
+    )
+)]
+pub async fn verify_credential(
+    Json(payload): Json<VerifyRequest>,
+) -> Result<Json<ValidationReport>, HandlerError> {
+    let accreditation = payload
+        .federation_id
+        .map(|federation_id| AccreditationCheck {
+            federation_id,
+            issuer_address: payload.issuer_address,
+        });
+
+    let jwt =
+        decode_jwt(&payload.jwt).map_err(|err| error_response(StatusCode::BAD_REQUEST, err))?;
+    verify_jwt(&jwt, accreditation.as_ref())
+        .await
+        .map(Json)
+        .map_err(|err| error_response(StatusCode::INTERNAL_SERVER_ERROR, err))
+}
+
+#[utoipa::path(
+    post,
+    path = "/{id}/revoke",
+    tag = "credentials",
+    params(("id" = String, Path, description = "Credential id")),
+    responses(
+        (status = 200, description = "Revoked credential", body = IssuedCredential),
+        (status = 404, description = "Unknown credential", body = ErrorResponse),
+        (status = 500, description = "Revocation failed", body = ErrorResponse),
+    )
+)]
+pub async fn revoke(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<IssuedCredential>, HandlerError> {
+    println!("Request received revoke: {}", id);
+
+    // Unknown ids are a 404, not a failed revocation
+    get_credential(Path(id.clone())).await?;
+    revoke_credential(&state.submitter, &id)
+        .await
+        .map(Json)
+        .map_err(|err| error_response(StatusCode::INTERNAL_SERVER_ERROR, err))
+}
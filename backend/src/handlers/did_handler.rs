@@ -0,0 +1,150 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::anyhow;
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+};
+use backend::did_lifecycle::{update_did, DidUpdate, DidUpdateResult};
+use backend::did_resolution::{ResolutionError, ResolutionResult};
+use backend::domain_linkage::{verify_document_linkage, ConfigurationSource, DomainLinkageReport};
+use backend::utils::MANUFACTURER_ALIAS;
+use identity_iota::core::Url;
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+
+use super::{error_response, ErrorResponse, HandlerError};
+use crate::state::AppState;
+
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct DidUpdateRequest {
+    #[serde(flatten)]
+    update: DidUpdate,
+    /// Only compute the diff, without submitting the update.
+    #[serde(default)]
+    dry_run: bool,
+}
+
+#[utoipa::path(
+    get,
+    path = "/{did}",
+    tag = "dids",
+    params(("did" = String, Path, description = "DID to resolve")),
+    security(()),
+    responses(
+        (status = 200, description = "DID resolution result", body = ResolutionResult),
+        (status = 400, description = "Invalid DID", body = ResolutionResult),
+        (status = 404, description = "DID not found", body = ResolutionResult),
+        (status = 500, description = "Document could not be rendered", body = ResolutionResult),
+    )
+)]
+pub async fn resolve(
+    State(state): State<AppState>,
+    Path(did): Path<String>,
+) -> (StatusCode, Json<ResolutionResult>) {
+    let result = state.did_resolver.resolve(&did).await;
+    let status = match result.error {
+        None => StatusCode::OK,
+        Some(ResolutionError::InvalidDid) => StatusCode::BAD_REQUEST,
+        Some(ResolutionError::NotFound) => StatusCode::NOT_FOUND,
+        Some(ResolutionError::InternalError) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, Json(result))
+}
+
+#[utoipa::path(
+    post,
+    path = "/{did}/updates",
+    tag = "dids",
+    params(("did" = String, Path, description = "DID controlled by the manufacturer account")),
+    request_body = DidUpdateRequest,
+    responses(
+        (status = 200, description = "Diff of the update, and whether it was submitted", body = DidUpdateResult),
+        (status = 500, description = "Update failed", body = ErrorResponse),
+    )
+)]
+pub async fn update_document(
+    State(state): State<AppState>,
+    Path(did): Path<String>,
+    Json(payload): Json<DidUpdateRequest>,
+) -> Result<Json<DidUpdateResult>, HandlerError> {
+    println!("Request received update_document: {} {:?}", did, payload);
+
+    let result = update_did(MANUFACTURER_ALIAS, &did, payload.update, payload.dry_run)
+        .await
+        .map_err(|err| error_response(StatusCode::INTERNAL_SERVER_ERROR, err))?;
+    if result.submitted {
+        state.did_resolver.invalidate(&did).await;
+    }
+    Ok(Json(result))
+}
+
+/// Where `/domain-linkage` reads the DID Configuration resource from.
+#[derive(Deserialize, Debug, Default, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkageSource {
+    /// `/.well-known/did-configuration.json` of each linked domain.
+    #[default]
+    Domain,
+    /// The credentials this backend stores and serves, without fetching anything.
+    Stored,
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DomainLinkageQuery {
+    /// Domain to check, defaults to every linked domain of the DID.
+    #[param(value_type = Option<String>)]
+    domain: Option<Url>,
+    /// Where the DID Configuration is read from, `domain` by default.
+    #[serde(default)]
+    #[param(inline)]
+    source: LinkageSource,
+}
+
+#[utoipa::path(
+    get,
+    path = "/{did}/domain-linkage",
+    tag = "dids",
+    params(("did" = String, Path, description = "DID to check"), DomainLinkageQuery),
+    security(()),
+    responses(
+        (status = 200, description = "Domain linkage report", body = DomainLinkageReport),
+        (status = 400, description = "Invalid DID", body = ErrorResponse),
+        (status = 404, description = "DID not found", body = ErrorResponse),
+        (status = 502, description = "The DID could not be resolved from the node", body = ErrorResponse),
+        (status = 500, description = "The report could not be built", body = ErrorResponse),
+    )
+)]
+pub async fn domain_linkage(
+    State(state): State<AppState>,
+    Path(did): Path<String>,
+    Query(query): Query<DomainLinkageQuery>,
+) -> Result<Json<DomainLinkageReport>, HandlerError> {
+    let result = state.did_resolver.resolve(&did).await;
+    let Some(document) = result.document else {
+        let status = match result.error {
+            Some(ResolutionError::InvalidDid) => StatusCode::BAD_REQUEST,
+            Some(ResolutionError::NotFound) => StatusCode::NOT_FOUND,
+            _ => StatusCode::BAD_GATEWAY,
+        };
+        let message = result.did_resolution_metadata["errorMessage"]
+            .as_str()
+            .unwrap_or("DID resolution failed")
+            .to_string();
+        return Err(error_response(status, anyhow!(message)));
+    };
+
+    // Only the linked domains of the DID are fetched, never a URL of the client
+    let domains: Vec<Url> = query.domain.into_iter().collect();
+    let source = match query.source {
+        LinkageSource::Domain => ConfigurationSource::Domain,
+        LinkageSource::Stored => ConfigurationSource::Stored,
+    };
+
+    verify_document_linkage(&document, &domains, &source)
+        .await
+        .map(Json)
+        .map_err(|err| error_response(StatusCode::INTERNAL_SERVER_ERROR, err))
+}
@@ -0,0 +1,51 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::state::AppState;
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+};
+use backend::health::{readiness, ReadinessReport};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Serialize, ToSchema)]
+pub struct Liveness {
+    status: &'static str,
+}
+
+/// Answers as long as the server runs, without touching the chain.
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    tag = "health",
+    security(()),
+    responses(
+        (status = 200, description = "The server is up", body = Liveness),
+    )
+)]
+pub async fn healthz() -> Json<Liveness> {
+    Json(Liveness { status: "ok" })
+}
+
+/// Checks the node, packages, federation, keys and gas the API depends on.
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "health",
+    security(()),
+    responses(
+        (status = 200, description = "Every check passed", body = ReadinessReport),
+        (status = 503, description = "At least one check failed", body = ReadinessReport),
+    )
+)]
+pub async fn readyz(State(state): State<AppState>) -> (StatusCode, Json<ReadinessReport>) {
+    let report = readiness(&state.iota_client).await;
+    let status = if report.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report))
+}
@@ -0,0 +1,23 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use axum::http::{header, StatusCode};
+use backend::metrics::gather;
+
+use super::{error_response, ErrorResponse, HandlerError};
+
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "metrics",
+    security(()),
+    responses(
+        (status = 200, description = "Prometheus text exposition format", body = String, content_type = "text/plain; version=0.0.4"),
+        (status = 500, description = "Metrics could not be encoded", body = ErrorResponse),
+    )
+)]
+pub async fn metrics() -> Result<([(header::HeaderName, &'static str); 1], String), HandlerError> {
+    gather()
+        .map(|body| ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body))
+        .map_err(|err| error_response(StatusCode::INTERNAL_SERVER_ERROR, err))
+}
@@ -1,4 +1,35 @@
 // Copyright (c) IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use axum::{extract::Json, http::StatusCode};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+pub mod credential_handler;
+pub mod did_handler;
+pub mod health_handler;
+pub mod metrics_handler;
+pub mod multisig_handler;
 pub mod permission_handler;
+pub mod presentation_handler;
+pub mod product_handler;
+pub mod reward_handler;
+pub mod well_known_handler;
+
+/// Body of every error response.
+#[derive(Serialize, ToSchema)]
+pub struct ErrorResponse {
+    message: String,
+}
+
+pub type HandlerError = (StatusCode, Json<ErrorResponse>);
+
+pub fn error_response(status: StatusCode, err: anyhow::Error) -> HandlerError {
+    eprintln!("Request failed: {:?}", err);
+    (
+        status,
+        Json(ErrorResponse {
+            message: format!("{:#}", err),
+        }),
+    )
+}
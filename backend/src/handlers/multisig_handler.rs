@@ -0,0 +1,113 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use axum::{
+    extract::{Json, Path},
+    http::StatusCode,
+};
+use backend::multisig::{
+    add_signature, execute, list_proposals, load_proposal, propose, FederationOperation, Proposal,
+};
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use super::{error_response, ErrorResponse, HandlerError};
+
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct SignatureRequest {
+    signature: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/proposals",
+    tag = "multisig",
+    request_body = FederationOperation,
+    responses(
+        (status = 200, description = "Proposal awaiting signatures", body = Proposal),
+        (status = 500, description = "Transaction could not be built", body = ErrorResponse),
+    )
+)]
+pub async fn create_proposal(
+    Json(operation): Json<FederationOperation>,
+) -> Result<Json<Proposal>, HandlerError> {
+    println!("Request received create_proposal: {:?}", operation);
+
+    propose(operation)
+        .await
+        .map(Json)
+        .map_err(|err| error_response(StatusCode::INTERNAL_SERVER_ERROR, err))
+}
+
+#[utoipa::path(
+    get,
+    path = "/proposals",
+    tag = "multisig",
+    responses(
+        (status = 200, description = "Every proposal", body = Vec<Proposal>),
+        (status = 500, description = "Proposals could not be read", body = ErrorResponse),
+    )
+)]
+pub async fn get_proposals() -> Result<Json<Vec<Proposal>>, HandlerError> {
+    list_proposals()
+        .map(Json)
+        .map_err(|err| error_response(StatusCode::INTERNAL_SERVER_ERROR, err))
+}
+
+#[utoipa::path(
+    get,
+    path = "/proposals/{id}",
+    tag = "multisig",
+    params(("id" = String, Path, description = "Proposal id, the transaction digest")),
+    responses(
+        (status = 200, description = "Proposal", body = Proposal),
+        (status = 404, description = "Unknown proposal", body = ErrorResponse),
+    )
+)]
+pub async fn get_proposal(Path(id): Path<String>) -> Result<Json<Proposal>, HandlerError> {
+    load_proposal(&id)
+        .map(Json)
+        .map_err(|err| error_response(StatusCode::NOT_FOUND, err))
+}
+
+#[utoipa::path(
+    post,
+    path = "/proposals/{id}/signatures",
+    tag = "multisig",
+    params(("id" = String, Path, description = "Proposal id, the transaction digest")),
+    request_body = SignatureRequest,
+    responses(
+        (status = 200, description = "Proposal with the added signature", body = Proposal),
+        (status = 400, description = "Invalid signature", body = ErrorResponse),
+    )
+)]
+pub async fn sign_proposal(
+    Path(id): Path<String>,
+    Json(payload): Json<SignatureRequest>,
+) -> Result<Json<Proposal>, HandlerError> {
+    println!("Request received sign_proposal: {}", id);
+
+    add_signature(&id, &payload.signature)
+        .await
+        .map(Json)
+        .map_err(|err| error_response(StatusCode::BAD_REQUEST, err))
+}
+
+#[utoipa::path(
+    post,
+    path = "/proposals/{id}/execute",
+    tag = "multisig",
+    params(("id" = String, Path, description = "Proposal id, the transaction digest")),
+    responses(
+        (status = 200, description = "Executed proposal", body = Proposal),
+        (status = 500, description = "Threshold not reached or execution failed", body = ErrorResponse),
+    )
+)]
+pub async fn execute_proposal(Path(id): Path<String>) -> Result<Json<Proposal>, HandlerError> {
+    println!("Request received execute_proposal: {}", id);
+
+    execute(&id)
+        .await
+        .map(Json)
+        .map_err(|err| error_response(StatusCode::INTERNAL_SERVER_ERROR, err))
+}
@@ -1,64 +1,244 @@
 // Copyright (c) IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::services::blockchain::new_user_attestation;
-use axum::{extract::Json, http::StatusCode, response::IntoResponse};
+use crate::state::AppState;
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use backend::credentials::IssuedCredential;
+use backend::role_registry::Role;
+use iota_sdk::types::base_types::ObjectID;
 use serde::{Deserialize, Serialize};
-use std::fmt;
+use utoipa::ToSchema;
 
-#[derive(Deserialize, Debug)]
+use super::{error_response, ErrorResponse, HandlerError};
+
+#[derive(Deserialize, Debug, ToSchema)]
 pub struct RoleRequest {
     user_addr: String,
-    user_role: UserRole,
+    user_role: Role,
     federation_addr: String,
+    /// DID of the user, used as subject of the issued role credential.
+    #[serde(default)]
+    user_did: Option<String>,
+}
+
+impl RoleRequest {
+    fn ids(&self) -> anyhow::Result<(ObjectID, ObjectID)> {
+        Ok((
+            ObjectID::from_hex_literal(&self.federation_addr)?,
+            ObjectID::from_hex_literal(&self.user_addr)?,
+        ))
+    }
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct RoleResponse {
     message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    credential: Option<IssuedCredential>,
+    /// Why the credential of the granted role could not be issued.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    credential_error: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
-enum UserRole {
-    Manufacturer,
-    Repairer,
+#[derive(Serialize, ToSchema)]
+pub struct RevokeRoleResponse {
+    message: String,
+    /// Role credentials of the user revoked along with the role.
+    revoked_credentials: Vec<IssuedCredential>,
 }
 
-impl fmt::Display for UserRole {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let role_str = match self {
-            UserRole::Manufacturer => "Manufacturer",
-            UserRole::Repairer => "Repairer",
-        };
-        write!(f, "{}", role_str)
-    }
+#[derive(Serialize, ToSchema)]
+pub struct UserRoles {
+    #[schema(value_type = String)]
+    federation_id: ObjectID,
+    #[schema(value_type = String)]
+    user_addr: ObjectID,
+    roles: Vec<Role>,
 }
 
-pub async fn set_role(Json(payload): Json<RoleRequest>) -> impl IntoResponse {
-    println!("Request received set_role: {:?}", payload);
+#[derive(Serialize, ToSchema)]
+pub struct RoleValidation {
+    role: Role,
+    valid: bool,
+}
 
-    match new_user_attestation(
-        payload.user_addr.clone(),
-        payload.user_role.to_string(),
-        payload.federation_addr.clone(),
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "roles",
+    request_body = RoleRequest,
+    responses(
+        (status = 200, description = "Role granted, with the credential of a repairer or the reason it could not be issued", body = RoleResponse),
+        (status = 401, description = "Missing or invalid API key"),
+        (status = 422, description = "Malformed body, e.g. an unknown role"),
+        (status = 500, description = "Grant failed", body = RoleResponse),
     )
-    .await
-    {
-        Ok(_) => {
-            let response = RoleResponse {
-                message: format!(
-                    "User {} granted {} permission on federation {}",
-                    payload.user_addr, payload.user_role, payload.federation_addr
-                ),
+)]
+pub async fn set_role(
+    State(state): State<AppState>,
+    Json(payload): Json<RoleRequest>,
+) -> impl IntoResponse {
+    println!("Request received set_role: {:?}", payload);
+
+    let result = async {
+        let (federation_id, user_id) = payload.ids()?;
+        state
+            .roles
+            .grant(federation_id, user_id, payload.user_role)
+            .await?;
+        Ok::<_, anyhow::Error>(federation_id)
+    }
+    .await;
+
+    match result {
+        Ok(federation_id) => {
+            let message = format!(
+                "User {} granted {} permission on federation {}",
+                payload.user_addr, payload.user_role, payload.federation_addr
+            );
+
+            // The role stays granted, so a failed issuance is reported next to it
+            let (credential, credential_error) = match state
+                .credentials
+                .issue(
+                    &payload.user_addr,
+                    payload.user_did.as_deref(),
+                    federation_id,
+                    payload.user_role,
+                )
+                .await
+            {
+                Ok(credential) => (credential, None),
+                Err(err) => {
+                    eprintln!("Error issuing credential: {:?}", err);
+                    (None, Some(format!("{:#}", err)))
+                }
             };
-            (StatusCode::OK, Json(response))
+
+            (
+                StatusCode::OK,
+                Json(RoleResponse {
+                    message,
+                    credential,
+                    credential_error,
+                }),
+            )
         }
         Err(err) => {
             eprintln!("Error setting permission: {:?}", err);
             let error_response = RoleResponse {
                 message: format!("Failed to set permission: {}", err),
+                credential: None,
+                credential_error: None,
             };
             (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
         }
     }
 }
+
+/// Revokes a role and, for repairers, the credentials issued with it.
+#[utoipa::path(
+    post,
+    path = "/revoke",
+    tag = "roles",
+    request_body = RoleRequest,
+    responses(
+        (status = 200, description = "Role and its credentials revoked", body = RevokeRoleResponse),
+        (status = 400, description = "Invalid user or federation address", body = ErrorResponse),
+        (status = 500, description = "The user does not hold the role, or revocation failed", body = ErrorResponse),
+    )
+)]
+pub async fn revoke_role(
+    State(state): State<AppState>,
+    Json(payload): Json<RoleRequest>,
+) -> Result<Json<RevokeRoleResponse>, HandlerError> {
+    println!("Request received revoke_role: {:?}", payload);
+
+    let (federation_id, user_id) = payload
+        .ids()
+        .map_err(|err| error_response(StatusCode::BAD_REQUEST, err))?;
+    state
+        .roles
+        .revoke(federation_id, user_id, payload.user_role)
+        .await
+        .map_err(|err| error_response(StatusCode::INTERNAL_SERVER_ERROR, err))?;
+
+    let message = format!(
+        "User {} lost {} permission on federation {}",
+        payload.user_addr, payload.user_role, payload.federation_addr
+    );
+    let revoked_credentials = state
+        .credentials
+        .revoke(&payload.user_addr, federation_id, payload.user_role)
+        .await
+        .map_err(|err| {
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                err.context(format!("{}, but credential revocation failed", message)),
+            )
+        })?;
+
+    Ok(Json(RevokeRoleResponse {
+        message,
+        revoked_credentials,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{federation_id}/{user_addr}",
+    tag = "roles",
+    params(
+        ("federation_id" = String, Path, description = "Federation object ID"),
+        ("user_addr" = String, Path, description = "User address"),
+    ),
+    responses(
+        (status = 200, description = "Roles held by the user", body = UserRoles),
+        (status = 500, description = "Federation could not be read", body = ErrorResponse),
+    )
+)]
+pub async fn list_roles(
+    State(state): State<AppState>,
+    Path((federation_id, user_addr)): Path<(ObjectID, ObjectID)>,
+) -> Result<Json<UserRoles>, HandlerError> {
+    let roles = state
+        .roles
+        .list(federation_id, user_addr)
+        .await
+        .map_err(|err| error_response(StatusCode::INTERNAL_SERVER_ERROR, err))?;
+    Ok(Json(UserRoles {
+        federation_id,
+        user_addr,
+        roles,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/{federation_id}/{user_addr}/{role}",
+    tag = "roles",
+    params(
+        ("federation_id" = String, Path, description = "Federation object ID"),
+        ("user_addr" = String, Path, description = "User address"),
+        ("role" = Role, Path, description = "Role to check"),
+    ),
+    responses(
+        (status = 200, description = "Whether the user holds the role", body = RoleValidation),
+        (status = 500, description = "Federation could not be read", body = ErrorResponse),
+    )
+)]
+pub async fn validate_role(
+    State(state): State<AppState>,
+    Path((federation_id, user_addr, role)): Path<(ObjectID, ObjectID, Role)>,
+) -> Result<Json<RoleValidation>, HandlerError> {
+    let valid = state
+        .roles
+        .validate(federation_id, user_addr, role)
+        .await
+        .map_err(|err| error_response(StatusCode::INTERNAL_SERVER_ERROR, err))?;
+    Ok(Json(RoleValidation { role, valid }))
+}
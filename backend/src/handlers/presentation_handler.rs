@@ -0,0 +1,79 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use axum::{extract::Json, http::StatusCode};
+use backend::data::federation_id;
+use backend::presentations::{
+    create_challenge, decode_presentation, verify_role_presentation, Challenge,
+    RolePresentationReport,
+};
+use iota_sdk::types::base_types::ObjectID;
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use super::{error_response, ErrorResponse, HandlerError};
+
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct ChallengeRequest {
+    /// Verifier the presentation is meant for, e.g. the origin of the customer app.
+    audience: String,
+}
+
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct PresentationRequest {
+    /// VP JWT answering a challenge.
+    jwt: String,
+    /// Federation the repairer role is checked in, defaults to the one of `init_hierarchies`.
+    #[schema(value_type = Option<String>)]
+    federation_id: Option<ObjectID>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/challenges",
+    tag = "presentations",
+    request_body = ChallengeRequest,
+    security(()),
+    responses(
+        (status = 200, description = "Challenge to sign into the presentation", body = Challenge),
+        (status = 400, description = "Invalid audience", body = ErrorResponse),
+    )
+)]
+pub async fn challenge(
+    Json(payload): Json<ChallengeRequest>,
+) -> Result<Json<Challenge>, HandlerError> {
+    create_challenge(&payload.audience)
+        .await
+        .map(Json)
+        .map_err(|err| error_response(StatusCode::BAD_REQUEST, err))
+}
+
+#[utoipa::path(
+    post,
+    path = "/verify",
+    tag = "presentations",
+    request_body = PresentationRequest,
+    security(()),
+    responses(
+        (status = 200, description = "Role presentation report", body = RolePresentationReport),
+        (status = 400, description = "Malformed presentation", body = ErrorResponse),
+        (status = 500, description = "Presentation could not be verified", body = ErrorResponse),
+    )
+)]
+pub async fn verify_presentation(
+    Json(payload): Json<PresentationRequest>,
+) -> Result<Json<RolePresentationReport>, HandlerError> {
+    let federation_id = match payload.federation_id {
+        Some(federation_id) => federation_id,
+        None => {
+            federation_id().map_err(|err| error_response(StatusCode::INTERNAL_SERVER_ERROR, err))?
+        }
+    };
+
+    let jwt = decode_presentation(&payload.jwt)
+        .map_err(|err| error_response(StatusCode::BAD_REQUEST, err))?;
+    verify_role_presentation(&jwt, federation_id)
+        .await
+        .map(Json)
+        .map_err(|err| error_response(StatusCode::INTERNAL_SERVER_ERROR, err))
+}
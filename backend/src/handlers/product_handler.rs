@@ -0,0 +1,39 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::anyhow;
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+};
+use backend::products::{verify_product, ProductTrust};
+use iota_sdk::types::base_types::ObjectID;
+
+use super::{error_response, ErrorResponse, HandlerError};
+use crate::state::AppState;
+
+#[utoipa::path(
+    get,
+    path = "/{id}/trust",
+    tag = "products",
+    params(("id" = String, Path, description = "Product object ID")),
+    security(()),
+    responses(
+        (status = 200, description = "Trust checks of the product", body = ProductTrust),
+        (status = 404, description = "Product not found", body = ErrorResponse),
+        (status = 500, description = "Product could not be read", body = ErrorResponse),
+    )
+)]
+pub async fn product_trust(
+    State(state): State<AppState>,
+    Path(product_id): Path<ObjectID>,
+) -> Result<Json<ProductTrust>, HandlerError> {
+    match verify_product(&state.iota_client, &state.did_resolver, product_id).await {
+        Ok(Some(trust)) => Ok(Json(trust)),
+        Ok(None) => Err(error_response(
+            StatusCode::NOT_FOUND,
+            anyhow!("Product {} not found", product_id),
+        )),
+        Err(err) => Err(error_response(StatusCode::INTERNAL_SERVER_ERROR, err)),
+    }
+}
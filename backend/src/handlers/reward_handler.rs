@@ -0,0 +1,297 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::anyhow;
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::{header, StatusCode},
+};
+use backend::lcc::{read_dpp_value, top_up_dpp, LccTransaction, VaultBalance};
+use backend::nft_rewards::{
+    authorize_addresses, list_authorized, reward_nfts, AuthorizationResult, AuthorizedAddress,
+    NftRewardConfig, RewardNft,
+};
+use backend::reward_ledger::{
+    parse_time, payouts_csv, query_ledger, sync_ledger, LedgerQuery, RewardPayout,
+};
+use backend::signer::account_signer;
+use backend::treasury::TreasuryConfig;
+use iota_sdk::types::base_types::{IotaAddress, ObjectID};
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+
+use super::{error_response, ErrorResponse, HandlerError};
+use crate::state::AppState;
+
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct TopUpRequest {
+    /// LCC base units to lock for the product.
+    amount: u64,
+    /// LCC coin of the treasury alias to split from, defaults to its largest one.
+    #[serde(default)]
+    #[schema(value_type = Option<String>)]
+    coin_id: Option<ObjectID>,
+}
+
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LedgerParams {
+    #[param(value_type = Option<String>)]
+    recipient: Option<IotaAddress>,
+    #[param(value_type = Option<String>)]
+    product: Option<ObjectID>,
+    /// Unix milliseconds, RFC 3339 timestamp or `YYYY-MM-DD`, inclusive.
+    from: Option<String>,
+    /// Same formats as `from`, exclusive.
+    to: Option<String>,
+}
+
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct AuthorizeRequest {
+    #[schema(value_type = Vec<String>)]
+    addresses: Vec<IotaAddress>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/vault/{product}",
+    tag = "rewards",
+    params(("product" = String, Path, description = "Product object ID")),
+    responses(
+        (status = 200, description = "LCC locked for the product", body = VaultBalance),
+        (status = 404, description = "Product has no vault entry", body = ErrorResponse),
+    )
+)]
+pub async fn get_vault_balance(
+    State(state): State<AppState>,
+    Path(product): Path<ObjectID>,
+) -> Result<Json<VaultBalance>, HandlerError> {
+    match read_dpp_value(&state.iota_client, product).await {
+        Ok(Some(value)) => Ok(Json(VaultBalance { product, value })),
+        Ok(None) => Err(error_response(
+            StatusCode::NOT_FOUND,
+            anyhow!("Product {} has no reward vault entry", product),
+        )),
+        Err(err) => Err(error_response(StatusCode::INTERNAL_SERVER_ERROR, err)),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/vault/{product}/top-up",
+    tag = "rewards",
+    params(("product" = String, Path, description = "Product object ID")),
+    request_body = TopUpRequest,
+    responses(
+        (status = 200, description = "Top-up transaction", body = LccTransaction),
+        (status = 409, description = "Product already has a vault entry", body = ErrorResponse),
+        (status = 500, description = "Top-up failed", body = ErrorResponse),
+    )
+)]
+pub async fn top_up(
+    State(state): State<AppState>,
+    Path(product): Path<ObjectID>,
+    Json(payload): Json<TopUpRequest>,
+) -> Result<Json<LccTransaction>, HandlerError> {
+    println!("Request received top_up: {} {:?}", product, payload);
+
+    // The vault only accepts one top-up per product
+    match read_dpp_value(&state.iota_client, product).await {
+        Ok(None) => {}
+        Ok(Some(_)) => {
+            return Err(error_response(
+                StatusCode::CONFLICT,
+                anyhow!("Product {} already has a reward vault entry", product),
+            ))
+        }
+        Err(err) => return Err(error_response(StatusCode::INTERNAL_SERVER_ERROR, err)),
+    }
+
+    let signer = async { account_signer(&TreasuryConfig::from_env()?.alias).await }
+        .await
+        .map_err(|err| error_response(StatusCode::INTERNAL_SERVER_ERROR, err))?;
+    state
+        .submitter
+        .submit(&signer, |gas_coin| {
+            top_up_dpp(
+                &state.iota_client,
+                &signer,
+                product,
+                payload.amount,
+                payload.coin_id,
+                Some(gas_coin.0),
+            )
+        })
+        .await
+        .map(Json)
+        .map_err(|err| error_response(StatusCode::INTERNAL_SERVER_ERROR, err))
+}
+
+#[utoipa::path(
+    post,
+    path = "/whitelist",
+    tag = "rewards",
+    request_body = AuthorizeRequest,
+    responses(
+        (status = 200, description = "Authorized and skipped addresses", body = AuthorizationResult),
+        (status = 400, description = "No addresses", body = ErrorResponse),
+        (status = 500, description = "Authorization failed", body = ErrorResponse),
+    )
+)]
+pub async fn authorize(
+    State(state): State<AppState>,
+    Json(payload): Json<AuthorizeRequest>,
+) -> Result<Json<AuthorizationResult>, HandlerError> {
+    println!("Request received authorize: {:?}", payload);
+
+    if payload.addresses.is_empty() {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            anyhow!("No addresses to authorize"),
+        ));
+    }
+    authorize_batch(&state, &payload.addresses).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/whitelist/{address}",
+    tag = "rewards",
+    params(("address" = String, Path, description = "Address to authorize")),
+    responses(
+        (status = 200, description = "Authorized or skipped address", body = AuthorizationResult),
+        (status = 500, description = "Authorization failed", body = ErrorResponse),
+    )
+)]
+pub async fn authorize_one(
+    State(state): State<AppState>,
+    Path(address): Path<IotaAddress>,
+) -> Result<Json<AuthorizationResult>, HandlerError> {
+    println!("Request received authorize_one: {}", address);
+
+    authorize_batch(&state, &[address]).await
+}
+
+async fn authorize_batch(
+    state: &AppState,
+    addresses: &[IotaAddress],
+) -> Result<Json<AuthorizationResult>, HandlerError> {
+    let config = NftRewardConfig::from_env()
+        .map_err(|err| error_response(StatusCode::INTERNAL_SERVER_ERROR, err))?;
+    let signer = account_signer(&config.admin_alias)
+        .await
+        .map_err(|err| error_response(StatusCode::INTERNAL_SERVER_ERROR, err))?;
+    state
+        .submitter
+        .submit(&signer, |gas_coin| {
+            authorize_addresses(&state.iota_client, &signer, addresses, Some(gas_coin.0))
+        })
+        .await
+        .map(Json)
+        .map_err(|err| error_response(StatusCode::INTERNAL_SERVER_ERROR, err))
+}
+
+#[utoipa::path(
+    get,
+    path = "/whitelist",
+    tag = "rewards",
+    responses(
+        (status = 200, description = "Authorized addresses", body = Vec<AuthorizedAddress>),
+        (status = 500, description = "Whitelist could not be read", body = ErrorResponse),
+    )
+)]
+pub async fn get_whitelist(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<AuthorizedAddress>>, HandlerError> {
+    list_authorized(&state.iota_client)
+        .await
+        .map(Json)
+        .map_err(|err| error_response(StatusCode::INTERNAL_SERVER_ERROR, err))
+}
+
+#[utoipa::path(
+    get,
+    path = "/nfts/{address}",
+    tag = "rewards",
+    params(("address" = String, Path, description = "Owner of the NFTs")),
+    responses(
+        (status = 200, description = "Reward NFTs owned by the address", body = Vec<RewardNft>),
+        (status = 500, description = "NFTs could not be read", body = ErrorResponse),
+    )
+)]
+pub async fn get_reward_nfts(
+    State(state): State<AppState>,
+    Path(address): Path<IotaAddress>,
+) -> Result<Json<Vec<RewardNft>>, HandlerError> {
+    reward_nfts(&state.iota_client, address)
+        .await
+        .map(Json)
+        .map_err(|err| error_response(StatusCode::INTERNAL_SERVER_ERROR, err))
+}
+
+#[utoipa::path(
+    get,
+    path = "/ledger",
+    tag = "rewards",
+    params(LedgerParams),
+    responses(
+        (status = 200, description = "Matching payouts, oldest first", body = Vec<RewardPayout>),
+        (status = 400, description = "Invalid time", body = ErrorResponse),
+        (status = 500, description = "Ledger could not be synced", body = ErrorResponse),
+    )
+)]
+pub async fn get_ledger(
+    State(state): State<AppState>,
+    Query(params): Query<LedgerParams>,
+) -> Result<Json<Vec<RewardPayout>>, HandlerError> {
+    ledger(&state, params).await.map(Json)
+}
+
+#[utoipa::path(
+    get,
+    path = "/ledger/export",
+    tag = "rewards",
+    params(LedgerParams),
+    responses(
+        (status = 200, description = "Matching payouts as CSV", body = String, content_type = "text/csv"),
+        (status = 400, description = "Invalid time", body = ErrorResponse),
+        (status = 500, description = "Ledger could not be synced", body = ErrorResponse),
+    )
+)]
+pub async fn export_ledger(
+    State(state): State<AppState>,
+    Query(params): Query<LedgerParams>,
+) -> Result<([(header::HeaderName, &'static str); 2], String), HandlerError> {
+    let payouts = ledger(&state, params).await?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"reward-ledger.csv\"",
+            ),
+        ],
+        payouts_csv(&payouts),
+    ))
+}
+
+/// Syncs the ledger with the chain and returns the payouts matching `params`.
+async fn ledger(state: &AppState, params: LedgerParams) -> Result<Vec<RewardPayout>, HandlerError> {
+    let time = |value: Option<String>| {
+        value
+            .map(|value| parse_time(&value))
+            .transpose()
+            .map_err(|err| error_response(StatusCode::BAD_REQUEST, err))
+    };
+    let query = LedgerQuery {
+        recipient: params.recipient,
+        product: params.product,
+        from: time(params.from)?,
+        to: time(params.to)?,
+    };
+
+    sync_ledger(&state.iota_client)
+        .await
+        .map_err(|err| error_response(StatusCode::INTERNAL_SERVER_ERROR, err))?;
+    query_ledger(&query).map_err(|err| error_response(StatusCode::INTERNAL_SERVER_ERROR, err))
+}
@@ -0,0 +1,41 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use axum::{
+    extract::Json,
+    http::{header, HeaderMap, StatusCode},
+};
+use backend::domain_linkage::configuration_for_host;
+use identity_iota::credential::DomainLinkageConfiguration;
+
+use super::{error_response, ErrorResponse, HandlerError};
+
+#[utoipa::path(
+    get,
+    path = "/did-configuration.json",
+    tag = "well-known",
+    security(()),
+    responses(
+        (status = 200, description = "DID Configuration resource of the requested host", body = Object),
+        (status = 404, description = "No configuration for the host", body = ErrorResponse),
+    )
+)]
+pub async fn did_configuration(
+    headers: HeaderMap,
+) -> Result<Json<DomainLinkageConfiguration>, HandlerError> {
+    // Behind a reverse proxy the public host is in X-Forwarded-Host
+    let host = headers
+        .get("x-forwarded-host")
+        .or_else(|| headers.get(header::HOST))
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    match configuration_for_host(host) {
+        Ok(Some(configuration)) => Ok(Json(configuration)),
+        Ok(None) => Err(error_response(
+            StatusCode::NOT_FOUND,
+            anyhow::anyhow!("No DID configuration for {}", host),
+        )),
+        Err(err) => Err(error_response(StatusCode::INTERNAL_SERVER_ERROR, err)),
+    }
+}
@@ -0,0 +1,387 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context};
+use identity_iota::core::{Timestamp, Url};
+use identity_iota::credential::{
+    Credential, CredentialBuilder, RevocationBitmap, RevocationBitmapStatus, Status, Subject,
+};
+use identity_iota::iota::{IotaDID, IotaDocument};
+use iota_sdk::types::base_types::ObjectID;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::Mutex;
+use utoipa::ToSchema;
+
+use crate::data::{data_path, read_data, write_data};
+use crate::identity::{create_credential, get_client, get_read_only_client, publish_did_update};
+use crate::keystore::MANUFACTURER_ALIAS;
+use crate::role_registry::Role;
+use crate::signer::account_signer;
+use crate::submitter::TxSubmitter;
+
+pub const AUTHORIZED_REPAIRER_CREDENTIAL: &str = "AuthorizedRepairer";
+/// Fragment of the `RevocationBitmap2022` service of the manufacturer DID document.
+pub const REVOCATION_SERVICE_FRAGMENT: &str = "revocation";
+
+const CREDENTIALS_DIR: &str = "credentials";
+const REVOCATION_FILE: &str = "revocation.json";
+
+/// Serialises index allocation and updates of the revocation bitmap.
+static REVOCATION_LOCK: Mutex<()> = Mutex::const_new(());
+
+#[derive(Serialize, Deserialize, Default)]
+struct RevocationIndex {
+    next_index: u32,
+}
+
+/// A credential issued by the manufacturer DID, as stored by the backend.
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct IssuedCredential {
+    pub id: String,
+    pub credential_type: String,
+    pub issuer: String,
+    /// Address of the account the credential was issued to.
+    pub subject: String,
+    #[schema(value_type = String)]
+    pub federation_id: ObjectID,
+    pub issued_at: u64,
+    /// The signed credential in JWT form.
+    pub jwt: String,
+    /// Index of the credential in the revocation bitmap of the issuer.
+    #[serde(default)]
+    pub revocation_index: Option<u32>,
+    #[serde(default)]
+    pub revoked: bool,
+}
+
+impl IssuedCredential {
+    fn save(&self) -> anyhow::Result<()> {
+        write_data(&credential_file(&self.id), self)
+    }
+}
+
+fn credential_file(id: &str) -> String {
+    format!("{}/{}.json", CREDENTIALS_DIR, id)
+}
+
+pub fn load_credential(id: &str) -> anyhow::Result<Option<IssuedCredential>> {
+    // Ids are generated UUIDs, anything else cannot be a stored credential
+    if uuid::Uuid::parse_str(id).is_err() {
+        return Ok(None);
+    }
+    read_data(&credential_file(id))
+}
+
+/// Resolves the manufacturer DID document set in `MANUFACTURER_DID`.
+pub async fn manufacturer_document() -> anyhow::Result<IotaDocument> {
+    let did_str = std::env::var("MANUFACTURER_DID").context("MANUFACTURER_DID must be set")?;
+    let did = IotaDID::from_str(&did_str).context("Invalid MANUFACTURER_DID")?;
+    get_read_only_client()
+        .await?
+        .resolve_did(&did)
+        .await
+        .with_context(|| format!("Failed to resolve {}", did))
+}
+
+/// Fragment of the verification method signing credentials.
+///
+/// Defaults to the first method of the document, `MANUFACTURER_VM_FRAGMENT`
+/// selects another one.
+pub fn issuer_vm_fragment(document: &IotaDocument) -> anyhow::Result<String> {
+    if let Ok(fragment) = std::env::var("MANUFACTURER_VM_FRAGMENT") {
+        return Ok(fragment);
+    }
+    document
+        .methods(None)
+        .first()
+        .and_then(|method| method.id().fragment())
+        .map(str::to_string)
+        .context("Issuer DID document has no verification method")
+}
+
+/// Issues an `AuthorizedRepairer` credential for `user_addr` in `federation_id`,
+/// signed by the manufacturer DID.
+pub async fn issue_repairer_credential(
+    submitter: &TxSubmitter,
+    user_addr: &str,
+    user_did: Option<&str>,
+    federation_id: ObjectID,
+) -> anyhow::Result<IssuedCredential> {
+    let issuer_document = ensure_revocation_service(submitter).await?;
+    let fragment = issuer_vm_fragment(&issuer_document)?;
+    let revocation_index = allocate_revocation_index().await?;
+    let status: Status = RevocationBitmapStatus::new(
+        issuer_document
+            .id()
+            .to_url()
+            .join(format!("#{}", REVOCATION_SERVICE_FRAGMENT))?,
+        revocation_index,
+    )
+    .into();
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let mut subject = json!({
+        "address": user_addr,
+        "role": "repairer",
+        "federation": federation_id.to_string(),
+    });
+    if let Some(user_did) = user_did {
+        subject["id"] = json!(user_did);
+    }
+
+    let credential: Credential = CredentialBuilder::default()
+        .id(Url::parse(format!("urn:uuid:{}", id))?)
+        .issuer(Url::parse(issuer_document.id().as_str())?)
+        .type_(AUTHORIZED_REPAIRER_CREDENTIAL)
+        .subject(Subject::from_json_value(subject)?)
+        .status(status)
+        .issuance_date(Timestamp::now_utc())
+        .build()?;
+
+    let jwt = create_credential(credential, issuer_document.clone(), &fragment).await?;
+
+    let issued = IssuedCredential {
+        id,
+        credential_type: AUTHORIZED_REPAIRER_CREDENTIAL.to_string(),
+        issuer: issuer_document.id().to_string(),
+        subject: user_addr.to_string(),
+        federation_id,
+        issued_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        jwt: jwt.as_str().to_string(),
+        revocation_index: Some(revocation_index),
+        revoked: false,
+    };
+    issued.save()?;
+
+    println!(
+        "✅ {} credential {} issued",
+        issued.credential_type, issued.id
+    );
+    Ok(issued)
+}
+
+/// Adds the revocation service to the manufacturer DID document if it is missing.
+async fn ensure_revocation_service(submitter: &TxSubmitter) -> anyhow::Result<IotaDocument> {
+    let document = manufacturer_document().await?;
+    let service_url = document
+        .id()
+        .to_url()
+        .join(format!("#{}", REVOCATION_SERVICE_FRAGMENT))?;
+    if document.resolve_service(&service_url).is_some() {
+        return Ok(document);
+    }
+
+    let _guard = REVOCATION_LOCK.lock().await;
+    // Another request may have published the service meanwhile
+    let mut document = manufacturer_document().await?;
+    if document.resolve_service(&service_url).is_some() {
+        return Ok(document);
+    }
+    let service = RevocationBitmap::new().to_service(service_url)?;
+    document.insert_service(service)?;
+    publish_manufacturer_document(submitter, document.clone()).await?;
+    println!("✅ Revocation service added to {}", document.id());
+    Ok(document)
+}
+
+/// Publishes an update of the manufacturer DID document, paid with a gas coin
+/// of the manufacturer lane so it never races the other manufacturer
+/// transactions for a coin.
+async fn publish_manufacturer_document(
+    submitter: &TxSubmitter,
+    document: IotaDocument,
+) -> anyhow::Result<()> {
+    let identity_client = get_client(MANUFACTURER_ALIAS).await?;
+    let signer = account_signer(MANUFACTURER_ALIAS).await?;
+    submitter
+        .submit(&signer, |gas_coin| {
+            publish_did_update(&identity_client, document.clone(), gas_coin)
+        })
+        .await
+        .context("Failed to publish the manufacturer DID document")
+}
+
+async fn allocate_revocation_index() -> anyhow::Result<u32> {
+    let _guard = REVOCATION_LOCK.lock().await;
+    let mut revocation: RevocationIndex = read_data(REVOCATION_FILE)?.unwrap_or_default();
+    let index = revocation.next_index;
+    revocation.next_index += 1;
+    write_data(REVOCATION_FILE, &revocation)?;
+    Ok(index)
+}
+
+/// Revokes a credential by setting its index in the revocation bitmap of the
+/// manufacturer DID document on chain.
+pub async fn revoke_credential(
+    submitter: &TxSubmitter,
+    id: &str,
+) -> anyhow::Result<IssuedCredential> {
+    let mut credential =
+        load_credential(id)?.with_context(|| format!("Credential {} not found", id))?;
+    if credential.revoked {
+        return Ok(credential);
+    }
+    let Some(index) = credential.revocation_index else {
+        bail!("Credential {} was issued without a revocation status", id);
+    };
+
+    {
+        let _guard = REVOCATION_LOCK.lock().await;
+        let mut document = manufacturer_document().await?;
+        document.revoke_credentials(REVOCATION_SERVICE_FRAGMENT, &[index])?;
+        publish_manufacturer_document(submitter, document).await?;
+    }
+
+    credential.revoked = true;
+    credential.save()?;
+    println!("🚫 Credential {} revoked (index {})", credential.id, index);
+    Ok(credential)
+}
+
+/// Credentials issued to `subject` in `federation_id`.
+pub fn subject_credentials(
+    subject: &str,
+    federation_id: ObjectID,
+) -> anyhow::Result<Vec<IssuedCredential>> {
+    let dir = data_path(CREDENTIALS_DIR);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut credentials = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        let credential: IssuedCredential =
+            serde_json::from_str(&std::fs::read_to_string(&path)?)
+                .with_context(|| format!("Malformed credential {}", path.display()))?;
+        if credential.subject == subject && credential.federation_id == federation_id {
+            credentials.push(credential);
+        }
+    }
+    Ok(credentials)
+}
+
+/// Revokes every credential issued to `subject` in `federation_id`, to be
+/// called when the role of the subject is revoked.
+pub async fn revoke_subject_credentials(
+    submitter: &TxSubmitter,
+    subject: &str,
+    federation_id: ObjectID,
+) -> anyhow::Result<Vec<IssuedCredential>> {
+    let mut revoked = Vec::new();
+    for credential in subject_credentials(subject, federation_id)? {
+        if !credential.revoked && credential.revocation_index.is_some() {
+            revoked.push(revoke_credential(submitter, &credential.id).await?);
+        }
+    }
+    Ok(revoked)
+}
+
+/// Credentials that come with a role, issued when the role is granted and
+/// revoked along with it.
+#[async_trait::async_trait]
+pub trait RoleCredentials: Send + Sync {
+    /// Issues the credential of `role` to `user_addr`, if the role has one.
+    async fn issue(
+        &self,
+        user_addr: &str,
+        user_did: Option<&str>,
+        federation_id: ObjectID,
+        role: Role,
+    ) -> anyhow::Result<Option<IssuedCredential>>;
+
+    /// Revokes the credentials issued to `user_addr` with `role`.
+    async fn revoke(
+        &self,
+        user_addr: &str,
+        federation_id: ObjectID,
+        role: Role,
+    ) -> anyhow::Result<Vec<IssuedCredential>>;
+}
+
+/// Role credentials matching the `ROLE_REGISTRY` env var: roles kept in
+/// memory come without credentials, since issuing one updates the
+/// manufacturer DID document on chain.
+pub fn role_credentials(submitter: Arc<TxSubmitter>) -> Arc<dyn RoleCredentials> {
+    match std::env::var("ROLE_REGISTRY").as_deref() {
+        Ok("memory") => Arc::new(NoRoleCredentials),
+        _ => Arc::new(ManufacturerRoleCredentials::new(submitter)),
+    }
+}
+
+/// Repairer credentials issued by the manufacturer DID.
+pub struct ManufacturerRoleCredentials {
+    submitter: Arc<TxSubmitter>,
+}
+
+impl ManufacturerRoleCredentials {
+    pub fn new(submitter: Arc<TxSubmitter>) -> Self {
+        Self { submitter }
+    }
+}
+
+#[async_trait::async_trait]
+impl RoleCredentials for ManufacturerRoleCredentials {
+    async fn issue(
+        &self,
+        user_addr: &str,
+        user_did: Option<&str>,
+        federation_id: ObjectID,
+        role: Role,
+    ) -> anyhow::Result<Option<IssuedCredential>> {
+        match role {
+            Role::Repairer => Ok(Some(
+                issue_repairer_credential(&self.submitter, user_addr, user_did, federation_id)
+                    .await?,
+            )),
+            Role::Manufacturer => Ok(None),
+        }
+    }
+
+    async fn revoke(
+        &self,
+        user_addr: &str,
+        federation_id: ObjectID,
+        role: Role,
+    ) -> anyhow::Result<Vec<IssuedCredential>> {
+        match role {
+            Role::Repairer => {
+                revoke_subject_credentials(&self.submitter, user_addr, federation_id).await
+            }
+            Role::Manufacturer => Ok(Vec::new()),
+        }
+    }
+}
+
+/// Roles without credentials.
+pub struct NoRoleCredentials;
+
+#[async_trait::async_trait]
+impl RoleCredentials for NoRoleCredentials {
+    async fn issue(
+        &self,
+        _user_addr: &str,
+        _user_did: Option<&str>,
+        _federation_id: ObjectID,
+        _role: Role,
+    ) -> anyhow::Result<Option<IssuedCredential>> {
+        Ok(None)
+    }
+
+    async fn revoke(
+        &self,
+        _user_addr: &str,
+        _federation_id: ObjectID,
+        _role: Role,
+    ) -> anyhow::Result<Vec<IssuedCredential>> {
+        Ok(Vec::new())
+    }
+}
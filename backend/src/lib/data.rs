@@ -0,0 +1,119 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use iota_sdk::types::base_types::ObjectID;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+pub const DATA_DIR: &str = "../data";
+pub const ITH_FILE: &str = "ith.json";
+
+/// Folder of the backend's JSON files, `DATA_DIR` or `../data`.
+pub fn data_dir() -> PathBuf {
+    std::env::var("DATA_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| Path::new(DATA_DIR).to_path_buf())
+}
+
+pub fn data_path(file: &str) -> PathBuf {
+    data_dir().join(file)
+}
+
+/// Reads a JSON file from the data folder, `None` if it does not exist yet.
+pub fn read_data<T: DeserializeOwned>(file: &str) -> anyhow::Result<Option<T>> {
+    let path = data_path(file);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Error reading {}", path.display()))?;
+    let value = serde_json::from_str(&content)
+        .with_context(|| format!("Malformed JSON in {}", path.display()))?;
+    Ok(Some(value))
+}
+
+/// Writes `value` as JSON to the data folder, replacing the file atomically.
+pub fn write_data<T: Serialize>(file: &str, value: &T) -> anyhow::Result<()> {
+    let path = data_path(file);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, serde_json::to_string_pretty(value)?)?;
+    fs::rename(&tmp_path, &path).with_context(|| format!("Error writing {}", path.display()))
+}
+
+/// Appends `record` as a JSON line to a file of the data folder, which is never rewritten.
+pub fn append_record<T: Serialize>(file: &str, record: &T) -> anyhow::Result<()> {
+    let path = data_path(file);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut line = serde_json::to_string(record)?;
+    line.push('\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .with_context(|| format!("Error appending to {}", path.display()))
+}
+
+/// Reads the JSON lines written by `append_record`, empty if the file does not exist yet.
+pub fn read_records<T: DeserializeOwned>(file: &str) -> anyhow::Result<Vec<T>> {
+    let path = data_path(file);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Error reading {}", path.display()))?;
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line)
+                .with_context(|| format!("Malformed JSON in {} line {}", path.display(), index + 1))
+        })
+        .collect()
+}
+
+/// Takes an exclusive lock on `<file>.lock` in the data folder, held until the
+/// returned file is dropped.
+///
+/// The lock is taken by the operating system, so it serialises every process
+/// sharing the data folder, e.g. the server and the CLI scripts, and is
+/// released even when the holder crashes.
+pub async fn lock_data(file: &str) -> anyhow::Result<File> {
+    let path = data_path(&format!("{}.lock", file));
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let lock = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .with_context(|| format!("Error opening {}", path.display()))?;
+    tokio::task::spawn_blocking(move || {
+        lock.lock()
+            .with_context(|| format!("Error locking {}", path.display()))?;
+        Ok(lock)
+    })
+    .await?
+}
+
+/// Federation created by `init_hierarchies`.
+pub fn federation_id() -> anyhow::Result<ObjectID> {
+    let ith: Value =
+        read_data(ITH_FILE)?.context("Federation not initialised, run init_hierarchies first")?;
+    let federation_id = ith["federation_id"]
+        .as_str()
+        .context("federation_id missing from ith.json")?;
+    Ok(ObjectID::from_hex_literal(federation_id)?)
+}
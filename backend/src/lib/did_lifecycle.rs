@@ -0,0 +1,203 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::str::FromStr;
+
+use anyhow::{bail, Context};
+use identity_iota::core::{Object, Url};
+use identity_iota::did::DIDUrl;
+use identity_iota::document::Service;
+use identity_iota::iota::{IotaDID, IotaDocument};
+use identity_iota::storage::JwkDocumentExt;
+use identity_iota::verification::MethodScope;
+use serde::{Deserialize, Serialize};
+use similar::TextDiff;
+use utoipa::ToSchema;
+
+use crate::domain_linkage::refresh_linkages;
+use crate::identity::{
+    delete_method_key, get_client, method_key_storage, MethodKey, TEST_GAS_BUDGET,
+};
+
+/// Change to an existing DID document.
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DidUpdate {
+    AddMethod {
+        #[schema(value_type = String)]
+        key_type: MethodKey,
+        /// Generated from the key when omitted.
+        fragment: Option<String>,
+    },
+    RemoveMethod {
+        fragment: String,
+    },
+    AddService {
+        fragment: String,
+        service_type: String,
+        #[schema(value_type = String)]
+        endpoint: Url,
+    },
+    RemoveService {
+        fragment: String,
+    },
+    Deactivate,
+}
+
+/// Result of a DID document update.
+#[derive(Serialize, Debug, ToSchema)]
+pub struct DidUpdateResult {
+    pub did: String,
+    /// Unified diff between the current and the updated document.
+    pub diff: String,
+    pub submitted: bool,
+    pub document: serde_json::Value,
+}
+
+/// Applies `update` to the document of `did` and publishes it with the account `alias`,
+/// which must control the identity.
+///
+/// The diff of the document is printed before submission. With `dry_run` the
+/// update is only computed; generated keys are removed again in that case, as
+/// well as when publishing fails. Keys of removed methods are deleted once the
+/// update is on chain.
+pub async fn update_did(
+    alias: &str,
+    did: &str,
+    update: DidUpdate,
+    dry_run: bool,
+) -> anyhow::Result<DidUpdateResult> {
+    let did = IotaDID::from_str(did).with_context(|| format!("Invalid DID {}", did))?;
+    let identity_client = get_client(alias).await?;
+    let keytool_storage = method_key_storage()?;
+
+    let current = identity_client.resolve_did(&did).await?;
+    if current.metadata.deactivated == Some(true) {
+        bail!("{} is deactivated", did);
+    }
+    let mut updated = current.clone();
+    let mut generated_method = None;
+    let mut removed_method = None;
+
+    match &update {
+        DidUpdate::AddMethod { key_type, fragment } => {
+            let fragment = updated
+                .generate_method(
+                    &keytool_storage,
+                    key_type.key_type(),
+                    key_type.algorithm(),
+                    fragment.as_deref(),
+                    MethodScope::VerificationMethod,
+                )
+                .await?;
+            let method_id = fragment_url(&did, &fragment)?;
+            generated_method = updated.resolve_method(&method_id, None).cloned();
+        }
+        DidUpdate::RemoveMethod { fragment } => {
+            let method_id = fragment_url(&did, fragment)?;
+            if updated.resolve_method(&method_id, None).is_none() {
+                bail!("{} has no verification method #{}", did, fragment);
+            }
+            if updated.methods(None).len() == 1 {
+                bail!("Refusing to remove the last verification method of {}", did);
+            }
+            removed_method = updated.remove_method(&method_id);
+        }
+        DidUpdate::AddService {
+            fragment,
+            service_type,
+            endpoint,
+        } => {
+            let service = Service::builder(Object::new())
+                .id(fragment_url(&did, fragment)?)
+                .type_(service_type.as_str())
+                .service_endpoint(endpoint.clone())
+                .build()?;
+            updated.insert_service(service)?;
+        }
+        DidUpdate::RemoveService { fragment } => {
+            updated
+                .remove_service(&fragment_url(&did, fragment)?)
+                .with_context(|| format!("{} has no service #{}", did, fragment))?;
+        }
+        DidUpdate::Deactivate => {
+            updated.metadata.deactivated = Some(true);
+        }
+    }
+
+    let diff = document_diff(&current, &updated)?;
+    println!("📝 Changes to {}:\n{}", did, diff);
+
+    if dry_run {
+        let document = serde_json::to_value(&updated)?;
+        if let Some(method) = &generated_method {
+            delete_method_key(&keytool_storage, method).await?;
+        }
+        return Ok(DidUpdateResult {
+            did: did.to_string(),
+            diff,
+            submitted: false,
+            document,
+        });
+    }
+
+    let published = match update {
+        DidUpdate::Deactivate => identity_client
+            .deactivate_did_output(&did, TEST_GAS_BUDGET)
+            .await
+            .map(|_| updated)
+            .map_err(anyhow::Error::from),
+        _ => identity_client
+            .publish_did_document_update(updated, TEST_GAS_BUDGET)
+            .await
+            .map_err(anyhow::Error::from),
+    };
+    let document = match published {
+        Ok(document) => document,
+        Err(err) => {
+            // The generated key was never published, it would only be left behind
+            if let Some(method) = &generated_method {
+                delete_method_key(&keytool_storage, method).await?;
+            }
+            return Err(err);
+        }
+    };
+    if let Some(method) = &removed_method {
+        delete_method_key(&keytool_storage, method).await?;
+    }
+    println!("✅ {} updated", did);
+
+    // The served domain linkage credentials may have been signed by a method
+    // that is gone now
+    if std::env::var("MANUFACTURER_DID").is_ok_and(|manufacturer| manufacturer == did.to_string()) {
+        if let Err(err) = refresh_linkages().await {
+            eprintln!(
+                "⚠️ Failed to refresh the domain linkage credentials of {}, the renewal task will retry: {:#}",
+                did, err
+            );
+        }
+    }
+
+    Ok(DidUpdateResult {
+        did: did.to_string(),
+        diff,
+        submitted: true,
+        document: serde_json::to_value(&document)?,
+    })
+}
+
+fn fragment_url(did: &IotaDID, fragment: &str) -> anyhow::Result<DIDUrl> {
+    Ok(did
+        .clone()
+        .join(format!("#{}", fragment.trim_start_matches('#')))?)
+}
+
+/// Unified diff of the pretty printed documents.
+pub fn document_diff(current: &IotaDocument, updated: &IotaDocument) -> anyhow::Result<String> {
+    let current = serde_json::to_string_pretty(current)?;
+    let updated = serde_json::to_string_pretty(updated)?;
+    Ok(TextDiff::from_lines(&current, &updated)
+        .unified_diff()
+        .header("current", "updated")
+        .to_string())
+}
@@ -0,0 +1,173 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use identity_iota::core::Timestamp;
+use identity_iota::did::CoreDID;
+use identity_iota::iota::IotaDocument;
+use identity_iota::resolver::Resolver;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::sync::RwLock;
+use utoipa::ToSchema;
+
+use crate::metrics::time_chain_call;
+use crate::verification::resolver;
+
+pub const DEFAULT_DID_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// Why a DID could not be resolved, as defined by DID Resolution.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ResolutionError {
+    InvalidDid,
+    NotFound,
+    InternalError,
+}
+
+/// A W3C DID resolution result.
+#[derive(Serialize, Clone, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolutionResult {
+    #[serde(rename = "@context")]
+    pub context: &'static str,
+    pub did_document: Option<Value>,
+    pub did_resolution_metadata: Value,
+    pub did_document_metadata: Value,
+    #[serde(skip)]
+    pub error: Option<ResolutionError>,
+    #[serde(skip)]
+    pub document: Option<IotaDocument>,
+}
+
+impl ResolutionResult {
+    fn resolved(document: IotaDocument) -> anyhow::Result<Self> {
+        // IotaDocument serialises as { "doc": <DID document>, "meta": <document metadata> }
+        let mut value = serde_json::to_value(&document)?;
+        Ok(Self {
+            context: "https://w3id.org/did-resolution/v1",
+            did_document: Some(value["doc"].take()),
+            did_resolution_metadata: json!({
+                "contentType": "application/did+ld+json",
+                "retrieved": Timestamp::now_utc().to_rfc3339(),
+            }),
+            did_document_metadata: value["meta"].take(),
+            error: None,
+            document: Some(document),
+        })
+    }
+
+    fn failed(error: ResolutionError, message: String) -> Self {
+        Self {
+            context: "https://w3id.org/did-resolution/v1",
+            did_document: None,
+            did_resolution_metadata: json!({ "error": error, "errorMessage": message }),
+            did_document_metadata: json!({}),
+            error: Some(error),
+            document: None,
+        }
+    }
+}
+
+struct CachedResult {
+    resolved_at: Instant,
+    result: ResolutionResult,
+}
+
+/// DID resolver keeping resolved documents for `ttl`.
+pub struct CachedResolver {
+    resolver: Resolver<IotaDocument>,
+    ttl: Duration,
+    cache: RwLock<HashMap<String, CachedResult>>,
+}
+
+impl CachedResolver {
+    pub async fn new(ttl: Duration) -> anyhow::Result<Self> {
+        Ok(Self {
+            resolver: resolver().await?,
+            ttl,
+            cache: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Resolves `did`, from the cache when it was resolved less than `ttl` ago.
+    ///
+    /// Failed resolutions are not cached.
+    pub async fn resolve(&self, did: &str) -> ResolutionResult {
+        if let Some(cached) = self.cache.read().await.get(did) {
+            if cached.resolved_at.elapsed() < self.ttl {
+                let mut result = cached.result.clone();
+                result.did_resolution_metadata["cached"] = json!(true);
+                return result;
+            }
+        }
+
+        let Ok(parsed) = CoreDID::from_str(did) else {
+            return ResolutionResult::failed(
+                ResolutionError::InvalidDid,
+                format!("{} is not a valid DID", did),
+            );
+        };
+        let result = match time_chain_call("resolve_did", self.resolver.resolve(&parsed)).await {
+            Ok(document) => ResolutionResult::resolved(document).unwrap_or_else(|err| {
+                ResolutionResult::failed(ResolutionError::InternalError, err.to_string())
+            }),
+            Err(err) => {
+                let message = error_chain(&err);
+                let error = if is_not_found(&message) {
+                    ResolutionError::NotFound
+                } else {
+                    ResolutionError::InternalError
+                };
+                ResolutionResult::failed(error, message)
+            }
+        };
+
+        if result.error.is_none() {
+            let mut cache = self.cache.write().await;
+            cache.retain(|_, cached| cached.resolved_at.elapsed() < self.ttl);
+            cache.insert(
+                did.to_string(),
+                CachedResult {
+                    resolved_at: Instant::now(),
+                    result: result.clone(),
+                },
+            );
+        }
+        result
+    }
+
+    /// Drops the cached document of `did`, e.g. after updating it.
+    pub async fn invalidate(&self, did: &str) {
+        self.cache.write().await.remove(did);
+    }
+}
+
+/// `err` followed by its sources, the identity client wraps node errors.
+fn error_chain(err: &dyn std::error::Error) -> String {
+    let mut message = err.to_string();
+    let mut source = err.source();
+    while let Some(err) = source {
+        message.push_str(": ");
+        message.push_str(&err.to_string());
+        source = err.source();
+    }
+    message
+}
+
+/// Whether a resolution failed because the identity object does not exist,
+/// as opposed to the node or the document being unusable.
+fn is_not_found(message: &str) -> bool {
+    [
+        "could not find",
+        "notExists",
+        "NotExists",
+        "does not exist",
+        "not found",
+    ]
+    .iter()
+    .any(|pattern| message.contains(pattern))
+}
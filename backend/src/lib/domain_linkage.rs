@@ -0,0 +1,425 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration as StdDuration;
+
+use anyhow::{anyhow, bail, Context};
+use identity_iota::core::FromJson;
+use identity_iota::core::{Duration, Timestamp, Url};
+use identity_iota::credential::{
+    DomainLinkageConfiguration, DomainLinkageCredentialBuilder, Jwt,
+    JwtCredentialValidationOptions, JwtDomainLinkageValidator, LinkedDomainService,
+};
+use identity_iota::did::DIDUrl;
+use identity_iota::iota::IotaDocument;
+use identity_iota::storage::{JwkDocumentExt, JwsSignatureOptions};
+use identity_iota::verification::jws::Decoder;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::credentials::{issuer_vm_fragment, manufacturer_document};
+use crate::data::{read_data, write_data};
+use crate::identity::method_key_storage;
+use crate::verification::{resolve_document, resolver, Check, CheckStatus, MethodJwsVerifier};
+
+pub const DID_CONFIGURATION_FILE: &str = "did-configuration.json";
+/// Validity of a Domain Linkage Credential.
+pub const LINKAGE_VALIDITY_DAYS: i64 = 365;
+/// Credentials expiring within this many days are renewed.
+const RENEWAL_MARGIN_DAYS: i64 = 30;
+const RENEWAL_CHECK_INTERVAL: StdDuration = StdDuration::from_secs(12 * 60 * 60);
+
+/// Signed Domain Linkage Credential of one origin.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LinkedOrigin {
+    pub origin: Url,
+    pub did: String,
+    pub jwt: String,
+    pub issued_at: Timestamp,
+    pub expires_at: Timestamp,
+}
+
+/// Domains linked to the manufacturer DID.
+///
+/// Read from the comma separated `LINKED_DOMAINS`, defaulting to `NEXT_PUBLIC_DAPP_URL`.
+pub fn linked_domains() -> anyhow::Result<Vec<Url>> {
+    let domains = std::env::var("LINKED_DOMAINS")
+        .or_else(|_| std::env::var("NEXT_PUBLIC_DAPP_URL"))
+        .context("LINKED_DOMAINS or NEXT_PUBLIC_DAPP_URL must be set")?;
+    domains
+        .split(',')
+        .map(str::trim)
+        .filter(|domain| !domain.is_empty())
+        .map(|domain| Url::parse(domain).with_context(|| format!("Invalid domain {}", domain)))
+        .collect()
+}
+
+/// Signs a Domain Linkage Credential for `origin` with the method `fragment` of `document`.
+pub async fn sign_domain_linkage(
+    document: &IotaDocument,
+    fragment: &str,
+    origin: &Url,
+) -> anyhow::Result<LinkedOrigin> {
+    let issued_at = Timestamp::now_utc();
+    let expires_at = issued_at
+        .checked_add(Duration::days(LINKAGE_VALIDITY_DAYS))
+        .ok_or_else(|| anyhow!("Timestamp overflow"))?;
+
+    let domain_linkage_credential = DomainLinkageCredentialBuilder::new()
+        .issuer(document.id().clone().into())
+        .origin(origin.clone())
+        .issuance_date(issued_at)
+        .expiration_date(expires_at)
+        .build()?;
+
+    let jwt: Jwt = document
+        .create_credential_jwt(
+            &domain_linkage_credential,
+            &method_key_storage()?,
+            fragment,
+            &JwsSignatureOptions::default(),
+            None,
+        )
+        .await?;
+
+    Ok(LinkedOrigin {
+        origin: origin.clone(),
+        did: document.id().to_string(),
+        jwt: jwt.as_str().to_string(),
+        issued_at,
+        expires_at,
+    })
+}
+
+pub fn load_linked_origins() -> anyhow::Result<Vec<LinkedOrigin>> {
+    Ok(read_data(DID_CONFIGURATION_FILE)?.unwrap_or_default())
+}
+
+/// Stores `linked`, replacing the credential previously issued for the same origin.
+pub fn save_linked_origin(linked: LinkedOrigin) -> anyhow::Result<()> {
+    let mut origins = load_linked_origins()?;
+    origins.retain(|existing| existing.origin.origin() != linked.origin.origin());
+    origins.push(linked);
+    write_data(DID_CONFIGURATION_FILE, &origins)
+}
+
+/// Signs and stores a Domain Linkage Credential for every linked domain.
+pub async fn issue_domain_linkages(
+    document: &IotaDocument,
+    fragment: &str,
+) -> anyhow::Result<Vec<LinkedOrigin>> {
+    let mut issued = Vec::new();
+    for domain in linked_domains()? {
+        let linked = sign_domain_linkage(document, fragment, &domain).await?;
+        save_linked_origin(linked.clone())?;
+        println!(
+            "🔗 Domain linkage for {} valid until {}",
+            domain, linked.expires_at
+        );
+        issued.push(linked);
+    }
+    Ok(issued)
+}
+
+/// DID Configuration resource to serve on `host`, `None` if no linked origin matches.
+pub fn configuration_for_host(host: &str) -> anyhow::Result<Option<DomainLinkageConfiguration>> {
+    let jwts: Vec<Jwt> = load_linked_origins()?
+        .into_iter()
+        .filter(|linked| origin_host(&linked.origin).eq_ignore_ascii_case(host))
+        .map(|linked| Jwt::from(linked.jwt))
+        .collect();
+
+    if jwts.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(DomainLinkageConfiguration::new(jwts)))
+}
+
+/// Host of `origin` as sent in a `Host` header, with the port unless it is the default one.
+fn origin_host(origin: &Url) -> String {
+    match (origin.host_str(), origin.port()) {
+        (Some(host), Some(port)) => format!("{}:{}", host, port),
+        (Some(host), None) => host.to_string(),
+        _ => String::new(),
+    }
+}
+
+/// Fails when stored credentials were signed by another DID than
+/// `MANUFACTURER_DID`, e.g. after `init_dids` created a new DID.
+pub fn check_linked_did() -> anyhow::Result<()> {
+    let stored = load_linked_origins()?;
+    if stored.is_empty() {
+        return Ok(());
+    }
+    let did = std::env::var("MANUFACTURER_DID").context("MANUFACTURER_DID must be set")?;
+    if let Some(linked) = stored.iter().find(|linked| linked.did != did) {
+        bail!(
+            "Domain linkage of {} was signed by {} but MANUFACTURER_DID is {}, set it to the DID created by init_dids",
+            linked.origin,
+            linked.did,
+            did
+        );
+    }
+    Ok(())
+}
+
+/// Whether the `kid` of a stored credential still names a verification
+/// method of `document`. Once the method is removed or rotated away, the
+/// credential no longer validates even before it expires.
+fn signed_by_current_method(document: &IotaDocument, linked: &LinkedOrigin) -> bool {
+    Decoder::new()
+        .decode_compact_serialization(linked.jwt.as_bytes(), None)
+        .ok()
+        .and_then(|token| {
+            token
+                .protected_header()
+                .and_then(|header| header.kid())
+                .map(str::to_string)
+        })
+        .and_then(|kid| DIDUrl::parse(kid).ok())
+        .is_some_and(|kid| document.resolve_method(&kid, None).is_some())
+}
+
+/// Rebuilds the served credentials from `LINKED_DOMAINS`: drops the ones of
+/// removed domains, signs one for domains that have none yet and renews the
+/// ones expiring within the renewal margin or signed by a method that is no
+/// longer in the manufacturer DID document.
+///
+/// Called periodically by the server, and after every update of the
+/// manufacturer DID document that may remove the signing method.
+pub async fn refresh_linkages() -> anyhow::Result<usize> {
+    check_linked_did()?;
+    let threshold = Timestamp::now_utc()
+        .checked_add(Duration::days(RENEWAL_MARGIN_DAYS))
+        .ok_or_else(|| anyhow!("Timestamp overflow"))?;
+    let domains = linked_domains()?;
+    let stored = load_linked_origins()?;
+
+    let served: Vec<LinkedOrigin> = stored
+        .iter()
+        .filter(|linked| {
+            domains
+                .iter()
+                .any(|domain| domain.origin() == linked.origin.origin())
+        })
+        .cloned()
+        .collect();
+    if served.len() != stored.len() {
+        write_data(DID_CONFIGURATION_FILE, &served)?;
+        println!(
+            "🗑️ Dropped the domain linkage of {} origin(s) not in LINKED_DOMAINS",
+            stored.len() - served.len()
+        );
+    }
+
+    if domains.is_empty() {
+        return Ok(0);
+    }
+
+    let document = manufacturer_document().await?;
+    let pending: Vec<Url> = domains
+        .into_iter()
+        .filter(|domain| {
+            !served.iter().any(|linked| {
+                linked.origin.origin() == domain.origin()
+                    && linked.expires_at > threshold
+                    && signed_by_current_method(&document, linked)
+            })
+        })
+        .collect();
+    if pending.is_empty() {
+        return Ok(0);
+    }
+
+    let fragment = issuer_vm_fragment(&document)?;
+    for origin in &pending {
+        let renewed = sign_domain_linkage(&document, &fragment, origin).await?;
+        println!(
+            "🔄 Domain linkage for {} signed until {}",
+            origin, renewed.expires_at
+        );
+        save_linked_origin(renewed)?;
+    }
+    Ok(pending.len())
+}
+
+/// Periodically refreshes the Domain Linkage Credentials, for the lifetime of the server.
+pub async fn run_linkage_renewal() {
+    let mut interval = tokio::time::interval(RENEWAL_CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        if let Err(err) = refresh_linkages().await {
+            eprintln!("❌ Failed to renew domain linkage credentials: {:#}", err);
+        }
+    }
+}
+
+/// Where the DID Configuration resource of a domain is read from.
+pub enum ConfigurationSource {
+    /// `/.well-known/did-configuration.json` of the domain itself.
+    Domain,
+    /// Any URL, e.g. a local backend serving the resource over plain HTTP.
+    /// Only for operators, the API never fetches URLs supplied by clients.
+    Url(String),
+    File(String),
+    /// The credentials the backend stores and serves itself, read without
+    /// any network request.
+    Stored,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct DomainReport {
+    pub domain: String,
+    pub valid: bool,
+    pub checks: Vec<Check>,
+}
+
+/// Pass/fail report of the domain linkage of a DID.
+#[derive(Serialize, Debug, ToSchema)]
+pub struct DomainLinkageReport {
+    pub did: String,
+    pub valid: bool,
+    /// Domains listed in the LinkedDomainService of the DID document.
+    pub linked_domains: Vec<String>,
+    pub domains: Vec<DomainReport>,
+}
+
+/// Verifies the linkage between `did` and each of `domains`, or every domain
+/// of its LinkedDomainService when `domains` is empty.
+pub async fn verify_domain_linkage(
+    did: &str,
+    domains: &[Url],
+    source: &ConfigurationSource,
+) -> anyhow::Result<DomainLinkageReport> {
+    let resolver = resolver().await?;
+    let did_document = resolve_document(&resolver, did).await?;
+    verify_document_linkage(&did_document, domains, source).await
+}
+
+/// Verifies the domain linkage of an already resolved DID document, see
+/// [`verify_domain_linkage`].
+pub async fn verify_document_linkage(
+    did_document: &IotaDocument,
+    domains: &[Url],
+    source: &ConfigurationSource,
+) -> anyhow::Result<DomainLinkageReport> {
+    let did = did_document.id().to_string();
+    let linked_domains: Vec<Url> = did_document
+        .service()
+        .iter()
+        .cloned()
+        .filter_map(|service| LinkedDomainService::try_from(service).ok())
+        .flat_map(|service| service.domains().to_vec())
+        .collect();
+    let domains = if domains.is_empty() {
+        linked_domains.clone()
+    } else {
+        domains.to_vec()
+    };
+
+    let mut reports = Vec::new();
+    for domain in &domains {
+        let mut checks = Vec::new();
+
+        // The origin of the domain has to be listed in the DID document, other
+        // domains are never fetched
+        if linked_domains
+            .iter()
+            .any(|linked| linked.origin() == domain.origin())
+        {
+            checks.push(Check::new("domain_match", CheckStatus::Passed, None));
+        } else {
+            checks.push(Check::new(
+                "domain_match",
+                CheckStatus::Failed,
+                Some(format!("{} is not a linked domain of {}", domain, did)),
+            ));
+            checks.push(Check::new("configuration", CheckStatus::Skipped, None));
+            checks.push(Check::new("linkage", CheckStatus::Skipped, None));
+            reports.push(DomainReport {
+                domain: domain.to_string(),
+                valid: false,
+                checks,
+            });
+            continue;
+        }
+
+        match load_configuration(domain, source).await {
+            Ok(configuration) => {
+                checks.push(Check::new("configuration", CheckStatus::Passed, None));
+                let result = JwtDomainLinkageValidator::with_signature_verifier(MethodJwsVerifier)
+                    .validate_linkage(
+                        did_document,
+                        &configuration,
+                        domain,
+                        &JwtCredentialValidationOptions::default(),
+                    );
+                checks.push(match result {
+                    Ok(()) => Check::new("linkage", CheckStatus::Passed, None),
+                    Err(err) => {
+                        Check::new("linkage", CheckStatus::Failed, Some(format!("{:?}", err)))
+                    }
+                });
+            }
+            Err(err) => {
+                checks.push(Check::new(
+                    "configuration",
+                    CheckStatus::Failed,
+                    Some(format!("{:#}", err)),
+                ));
+                checks.push(Check::new("linkage", CheckStatus::Skipped, None));
+            }
+        }
+
+        reports.push(DomainReport {
+            domain: domain.to_string(),
+            valid: checks
+                .iter()
+                .all(|check| check.status == CheckStatus::Passed),
+            checks,
+        });
+    }
+
+    Ok(DomainLinkageReport {
+        did: did_document.id().to_string(),
+        valid: !reports.is_empty() && reports.iter().all(|report| report.valid),
+        linked_domains: linked_domains.iter().map(Url::to_string).collect(),
+        domains: reports,
+    })
+}
+
+async fn load_configuration(
+    domain: &Url,
+    source: &ConfigurationSource,
+) -> anyhow::Result<DomainLinkageConfiguration> {
+    match source {
+        ConfigurationSource::Domain => {
+            Ok(DomainLinkageConfiguration::fetch_configuration(domain.clone()).await?)
+        }
+        ConfigurationSource::Url(url) => {
+            let content = reqwest::get(url)
+                .await?
+                .error_for_status()
+                .with_context(|| format!("Failed to fetch {}", url))?
+                .text()
+                .await?;
+            Ok(DomainLinkageConfiguration::from_json(&content)?)
+        }
+        ConfigurationSource::File(path) => {
+            let content =
+                std::fs::read_to_string(path).with_context(|| format!("Error reading {}", path))?;
+            Ok(DomainLinkageConfiguration::from_json(&content)?)
+        }
+        ConfigurationSource::Stored => {
+            let jwts: Vec<Jwt> = load_linked_origins()?
+                .into_iter()
+                .filter(|linked| linked.origin.origin() == domain.origin())
+                .map(|linked| Jwt::from(linked.jwt))
+                .collect();
+            if jwts.is_empty() {
+                bail!("No stored Domain Linkage Credential for {}", domain);
+            }
+            Ok(DomainLinkageConfiguration::new(jwts))
+        }
+    }
+}
@@ -0,0 +1,238 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context};
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::{aead::Aead, Key, KeyInit, XChaCha20Poly1305, XNonce};
+use fastcrypto::encoding::{Base64, Encoding};
+use iota_sdk::types::{
+    base_types::IotaAddress,
+    crypto::{get_key_pair, IotaKeyPair, PublicKey, Signature},
+    transaction::TransactionData,
+};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use shared_crypto::intent::{Intent, IntentMessage};
+
+use crate::signer::KeyBackend;
+
+const KEYSTORE_VERSION: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+/// On-disk representation of an encrypted keystore.
+///
+/// The key list is serialised as JSON and sealed with XChaCha20-Poly1305,
+/// using a key derived from the passphrase with Argon2id.
+#[derive(Serialize, Deserialize)]
+struct EncryptedKeystoreFile {
+    version: u8,
+    kdf: KdfParams,
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct KdfParams {
+    algorithm: String,
+    salt: String,
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredKey {
+    alias: String,
+    private_key: String,
+}
+
+/// Passphrase-protected keystore holding one key pair per alias.
+pub struct EncryptedKeystore {
+    path: PathBuf,
+    passphrase: String,
+    keys: BTreeMap<String, IotaKeyPair>,
+}
+
+impl EncryptedKeystore {
+    /// Opens the keystore at `path`, or starts an empty one if the file does not exist yet.
+    pub fn open(path: impl AsRef<Path>, passphrase: &str) -> anyhow::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut keys = BTreeMap::new();
+
+        if path.exists() {
+            let content = std::fs::read_to_string(&path)
+                .with_context(|| format!("Error reading keystore {}", path.display()))?;
+            let file: EncryptedKeystoreFile =
+                serde_json::from_str(&content).context("Malformed encrypted keystore")?;
+            if file.version != KEYSTORE_VERSION {
+                bail!("Unsupported encrypted keystore version {}", file.version);
+            }
+
+            let key = derive_key(passphrase, &file.kdf)?;
+            let nonce = decode(&file.nonce)?;
+            if nonce.len() != NONCE_LEN {
+                bail!("Corrupted keystore: nonce of {} bytes", nonce.len());
+            }
+            let ciphertext = decode(&file.ciphertext)?;
+            let plaintext = XChaCha20Poly1305::new(Key::from_slice(&key))
+                .decrypt(XNonce::from_slice(&nonce), ciphertext.as_ref())
+                .map_err(|_| anyhow!("Wrong passphrase or corrupted keystore"))?;
+
+            let stored: Vec<StoredKey> = serde_json::from_slice(&plaintext)?;
+            for StoredKey { alias, private_key } in stored {
+                let keypair = IotaKeyPair::decode(&private_key)
+                    .map_err(|e| anyhow!("Invalid key for alias '{}': {}", alias, e))?;
+                keys.insert(alias, keypair);
+            }
+        }
+
+        Ok(Self {
+            path,
+            passphrase: passphrase.to_string(),
+            keys,
+        })
+    }
+
+    /// Re-encrypts the keystore with a fresh salt and nonce and writes it to disk.
+    ///
+    /// The file is written next to the keystore, readable by the owner only,
+    /// and renamed over it, so a crash never leaves a partly written keystore.
+    pub fn save(&self) -> anyhow::Result<()> {
+        let stored = self
+            .keys
+            .iter()
+            .map(|(alias, keypair)| {
+                Ok(StoredKey {
+                    alias: alias.clone(),
+                    private_key: keypair.encode().map_err(|e| anyhow!("{}", e))?,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let plaintext = serde_json::to_vec(&stored)?;
+
+        let mut salt = [0u8; SALT_LEN];
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut salt);
+        rand::thread_rng().fill_bytes(&mut nonce);
+
+        let kdf = KdfParams {
+            algorithm: "argon2id".to_string(),
+            salt: Base64::encode(salt),
+            m_cost: Params::DEFAULT_M_COST,
+            t_cost: Params::DEFAULT_T_COST,
+            p_cost: Params::DEFAULT_P_COST,
+        };
+        let key = derive_key(&self.passphrase, &kdf)?;
+        let ciphertext = XChaCha20Poly1305::new(Key::from_slice(&key))
+            .encrypt(XNonce::from_slice(&nonce), plaintext.as_ref())
+            .map_err(|_| anyhow!("Error encrypting keystore"))?;
+
+        let file = EncryptedKeystoreFile {
+            version: KEYSTORE_VERSION,
+            kdf,
+            nonce: Base64::encode(nonce),
+            ciphertext: Base64::encode(ciphertext),
+        };
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut tmp_path = self.path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_path);
+
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        let mut tmp_file = options
+            .open(&tmp_path)
+            .with_context(|| format!("Error writing keystore {}", tmp_path.display()))?;
+        tmp_file.write_all(serde_json::to_string_pretty(&file)?.as_bytes())?;
+        tmp_file.sync_all()?;
+        std::fs::rename(&tmp_path, &self.path)
+            .with_context(|| format!("Error writing keystore {}", self.path.display()))
+    }
+
+    /// Adds `keypair` under `alias`. Fails if the alias is already taken.
+    pub fn add_key(&mut self, alias: &str, keypair: IotaKeyPair) -> anyhow::Result<IotaAddress> {
+        if self.keys.contains_key(alias) {
+            bail!("Alias '{}' already exists", alias);
+        }
+        let address = IotaAddress::from(&keypair.public());
+        self.keys.insert(alias.to_string(), keypair);
+        Ok(address)
+    }
+
+    /// Generates a new ED25519 key pair under `alias`.
+    pub fn generate_key(&mut self, alias: &str) -> anyhow::Result<IotaAddress> {
+        let (_, keypair) = get_key_pair();
+        self.add_key(alias, IotaKeyPair::Ed25519(keypair))
+    }
+
+    /// Moves the key stored under `alias` to `new_alias`.
+    pub fn rename_alias(&mut self, alias: &str, new_alias: &str) -> anyhow::Result<()> {
+        if self.keys.contains_key(new_alias) {
+            bail!("Alias '{}' already exists", new_alias);
+        }
+        let keypair = self
+            .keys
+            .remove(alias)
+            .with_context(|| format!("No key found for alias {}", alias))?;
+        self.keys.insert(new_alias.to_string(), keypair);
+        Ok(())
+    }
+
+    pub fn address(&self, alias: &str) -> anyhow::Result<IotaAddress> {
+        Ok(IotaAddress::from(&self.key(alias)?.public()))
+    }
+
+    pub fn aliases(&self) -> impl Iterator<Item = &String> {
+        self.keys.keys()
+    }
+
+    fn key(&self, alias: &str) -> anyhow::Result<&IotaKeyPair> {
+        self.keys
+            .get(alias)
+            .with_context(|| format!("No key found for alias {}", alias))
+    }
+}
+
+#[async_trait::async_trait]
+impl KeyBackend for EncryptedKeystore {
+    async fn public_key(&self, alias: &str) -> anyhow::Result<PublicKey> {
+        Ok(self.key(alias)?.public())
+    }
+
+    async fn sign(&self, alias: &str, data: &TransactionData) -> anyhow::Result<Signature> {
+        let keypair = self.key(alias)?;
+        Ok(Signature::new_secure(
+            &IntentMessage::new(Intent::iota_transaction(), data),
+            keypair,
+        ))
+    }
+}
+
+fn derive_key(passphrase: &str, kdf: &KdfParams) -> anyhow::Result<[u8; 32]> {
+    if kdf.algorithm != "argon2id" {
+        bail!("Unsupported key derivation function {}", kdf.algorithm);
+    }
+    let params = Params::new(kdf.m_cost, kdf.t_cost, kdf.p_cost, Some(32))
+        .map_err(|e| anyhow!("Invalid argon2 parameters: {}", e))?;
+    let mut key = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), &decode(&kdf.salt)?, &mut key)
+        .map_err(|e| anyhow!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+fn decode(value: &str) -> anyhow::Result<Vec<u8>> {
+    Base64::decode(value).map_err(|e| anyhow!("Invalid base64 in keystore: {}", e))
+}
@@ -0,0 +1,63 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::str::FromStr;
+
+use anyhow::Context;
+use iota_sdk::{
+    rpc_types::{EventFilter, IotaEvent},
+    types::{base_types::ObjectID, event::EventID},
+    IotaClient,
+};
+use move_core_types::language_storage::StructTag;
+
+use crate::metrics::time_chain_call;
+
+const EVENT_PAGE_SIZE: usize = 50;
+
+/// Package of the `audit_trails` contract, `IOTA_CUSTOM_NOTARIZATION_PKG_ID`.
+pub fn package_id() -> anyhow::Result<ObjectID> {
+    let package_id = std::env::var("IOTA_CUSTOM_NOTARIZATION_PKG_ID")
+        .context("IOTA_CUSTOM_NOTARIZATION_PKG_ID must be set")?;
+    ObjectID::from_hex_literal(&package_id).context("Invalid IOTA_CUSTOM_NOTARIZATION_PKG_ID")
+}
+
+/// Every event of the contract with the type `<module>::<name>`, oldest first.
+pub async fn contract_events(
+    client: &IotaClient,
+    event_type: &str,
+) -> anyhow::Result<Vec<IotaEvent>> {
+    Ok(contract_events_since(client, event_type, None).await?.0)
+}
+
+/// Events of the contract with the type `<module>::<name>` after `cursor`,
+/// oldest first, and the cursor to continue from.
+pub async fn contract_events_since(
+    client: &IotaClient,
+    event_type: &str,
+    mut cursor: Option<EventID>,
+) -> anyhow::Result<(Vec<IotaEvent>, Option<EventID>)> {
+    let filter = EventFilter::MoveEventType(StructTag::from_str(&format!(
+        "{}::{}",
+        package_id()?,
+        event_type
+    ))?);
+
+    let mut events = Vec::new();
+    loop {
+        let page = time_chain_call(
+            "query_events",
+            client
+                .event_api()
+                .query_events(filter.clone(), cursor, Some(EVENT_PAGE_SIZE), false),
+        )
+        .await?;
+        events.extend(page.data);
+        if page.next_cursor.is_some() {
+            cursor = page.next_cursor;
+        }
+        if !page.has_next_page {
+            return Ok((events, cursor));
+        }
+    }
+}
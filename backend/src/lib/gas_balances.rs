@@ -0,0 +1,61 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+use anyhow::Context;
+use iota_sdk::IotaClient;
+
+use crate::keystore::{GAS_STATION_ALIAS, MANUFACTURER_ALIAS, ROOT_AUTH_ALIAS};
+use crate::metrics::{time_chain_call, GAS_BALANCE};
+use crate::signer::signer_backend;
+
+const DEFAULT_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// Accounts whose balance is exported at `/metrics`.
+const ALIASES: [&str; 3] = [ROOT_AUTH_ALIAS, MANUFACTURER_ALIAS, GAS_STATION_ALIAS];
+
+/// Exports the IOTA balance of the backend accounts every
+/// `GAS_BALANCE_CHECK_INTERVAL_SECS`, for the lifetime of the server.
+///
+/// Balances that cannot be read, e.g. of an alias without key in the signer
+/// backend, are logged and keep their last value.
+pub async fn run_gas_balance_watcher(client: IotaClient) {
+    let interval = match std::env::var("GAS_BALANCE_CHECK_INTERVAL_SECS") {
+        Ok(value) => match value.parse() {
+            Ok(secs) => Duration::from_secs(secs),
+            Err(_) => {
+                eprintln!(
+                    "⚠️ Gas balance watcher disabled: Invalid GAS_BALANCE_CHECK_INTERVAL_SECS"
+                );
+                return;
+            }
+        },
+        Err(_) => DEFAULT_CHECK_INTERVAL,
+    };
+
+    let mut interval = tokio::time::interval(interval);
+    loop {
+        interval.tick().await;
+        for alias in ALIASES {
+            match gas_balance(&client, alias).await {
+                Ok(balance) => GAS_BALANCE
+                    .with_label_values(&[alias])
+                    .set(i64::try_from(balance).unwrap_or(i64::MAX)),
+                Err(err) => eprintln!("⚠️ Cannot read gas balance of {}: {:#}", alias, err),
+            }
+        }
+    }
+}
+
+async fn gas_balance(client: &IotaClient, alias: &str) -> anyhow::Result<u128> {
+    let address = signer_backend()?
+        .address(alias)
+        .await
+        .with_context(|| format!("No key for {}", alias))?;
+    Ok(time_chain_call(
+        "get_balance",
+        client.coin_read_api().get_balance(address, None),
+    )
+    .await?
+    .total_balance)
+}
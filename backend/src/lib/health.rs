@@ -0,0 +1,213 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::future::Future;
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use anyhow::{bail, Context};
+use iota_sdk::{
+    rpc_types::IotaObjectDataOptions,
+    types::base_types::{ObjectID, ObjectType},
+    IotaClient,
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::data::federation_id;
+use crate::keystore::{MANUFACTURER_ALIAS, ROOT_AUTH_ALIAS};
+use crate::metrics::time_chain_call;
+use crate::products::move_fields;
+use crate::signer::signer_backend;
+use crate::verification::{Check, CheckStatus};
+
+/// Default balance, in nanos, a signer needs to be considered ready.
+pub const DEFAULT_MIN_GAS_BALANCE: u64 = 1_000_000_000;
+/// Longest all readiness checks together may take, so that probes get an answer.
+const READINESS_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Packages the backend calls, by check name and env var.
+const PACKAGES: [(&str, &str); 3] = [
+    ("hierarchies_package", "IOTA_HIERARCHIES_PKG_ID"),
+    ("identity_package", "IOTA_IDENTITY_PKG_ID"),
+    ("notarization_package", "IOTA_CUSTOM_NOTARIZATION_PKG_ID"),
+];
+
+/// Accounts the server signs with, by alias and names of their key and gas
+/// checks. The gas station signs in its own service.
+const SIGNERS: [(&str, &str, &str); 2] = [
+    (ROOT_AUTH_ALIAS, "root_auth_key", "root_auth_gas"),
+    (MANUFACTURER_ALIAS, "manufacturer_key", "manufacturer_gas"),
+];
+
+/// Outcome of every readiness check, ready when none failed.
+#[derive(Serialize, Debug, ToSchema)]
+pub struct ReadinessReport {
+    pub ready: bool,
+    pub checks: Vec<Check>,
+}
+
+/// Checks the node, the published packages, the federation and the signer
+/// accounts the API depends on.
+///
+/// The checks run concurrently. Those still running when
+/// `READINESS_TIMEOUT` is over fail as timed out. Signers need
+/// `READY_MIN_GAS_BALANCE` nanos (default: 1 IOTA).
+pub async fn readiness(client: &IotaClient) -> ReadinessReport {
+    let deadline = Instant::now() + READINESS_TIMEOUT;
+    let min_balance = std::env::var("READY_MIN_GAS_BALANCE")
+        .ok()
+        .and_then(|balance| balance.parse().ok())
+        .unwrap_or(DEFAULT_MIN_GAS_BALANCE);
+
+    let [hierarchies, identity, notarization] = PACKAGES;
+    let [root_auth, manufacturer] = SIGNERS;
+    let (
+        fullnode_check,
+        hierarchies_check,
+        identity_check,
+        notarization_check,
+        federation_check,
+        root_auth_checks,
+        manufacturer_checks,
+    ) = tokio::join!(
+        run("fullnode", deadline, fullnode(client)),
+        run(hierarchies.0, deadline, package(client, hierarchies.1)),
+        run(identity.0, deadline, package(client, identity.1)),
+        run(notarization.0, deadline, package(client, notarization.1)),
+        run("federation", deadline, federation(client)),
+        signer(client, root_auth, deadline, min_balance),
+        signer(client, manufacturer, deadline, min_balance),
+    );
+
+    let mut checks = vec![
+        fullnode_check,
+        hierarchies_check,
+        identity_check,
+        notarization_check,
+        federation_check,
+    ];
+    checks.extend(root_auth_checks);
+    checks.extend(manufacturer_checks);
+
+    ReadinessReport {
+        ready: checks
+            .iter()
+            .all(|check| check.status != CheckStatus::Failed),
+        checks,
+    }
+}
+
+/// Key and gas checks of a signer, by alias and check names.
+async fn signer(
+    client: &IotaClient,
+    (alias, key_check, gas_check): (&'static str, &'static str, &'static str),
+    deadline: Instant,
+    min_balance: u64,
+) -> [Check; 2] {
+    let address = async { signer_backend()?.address(alias).await };
+    let address = match tokio::time::timeout_at(deadline, address).await {
+        Ok(Ok(address)) => address,
+        Ok(Err(err)) => {
+            return [
+                Check::new(
+                    key_check,
+                    CheckStatus::Failed,
+                    Some(format!("No key for {}: {:#}", alias, err)),
+                ),
+                Check::new(gas_check, CheckStatus::Skipped, None),
+            ]
+        }
+        Err(_) => {
+            return [
+                Check::new(
+                    key_check,
+                    CheckStatus::Failed,
+                    Some("Timed out".to_string()),
+                ),
+                Check::new(gas_check, CheckStatus::Skipped, None),
+            ]
+        }
+    };
+
+    let balance = async {
+        let balance = time_chain_call(
+            "get_balance",
+            client.coin_read_api().get_balance(address, None),
+        )
+        .await?
+        .total_balance;
+        if balance < u128::from(min_balance) {
+            bail!(
+                "{} holds {} nanos, {} needed",
+                address,
+                balance,
+                min_balance
+            );
+        }
+        Ok(format!("{} nanos", balance))
+    };
+    [
+        Check::new(
+            key_check,
+            CheckStatus::Passed,
+            Some(format!("{} is {}", alias, address)),
+        ),
+        run(gas_check, deadline, balance).await,
+    ]
+}
+
+/// Runs `check` until `deadline`, its `Ok` value is the detail of the passed check.
+async fn run(
+    name: &'static str,
+    deadline: Instant,
+    check: impl Future<Output = anyhow::Result<String>>,
+) -> Check {
+    match tokio::time::timeout_at(deadline, check).await {
+        Ok(Ok(detail)) => Check::new(name, CheckStatus::Passed, Some(detail)),
+        Ok(Err(err)) => Check::new(name, CheckStatus::Failed, Some(format!("{:#}", err))),
+        Err(_) => Check::new(name, CheckStatus::Failed, Some("Timed out".to_string())),
+    }
+}
+
+async fn fullnode(client: &IotaClient) -> anyhow::Result<String> {
+    let chain = time_chain_call(
+        "get_chain_identifier",
+        client.read_api().get_chain_identifier(),
+    )
+    .await?;
+    Ok(format!("version {}, chain {}", client.api_version(), chain))
+}
+
+async fn package(client: &IotaClient, var: &str) -> anyhow::Result<String> {
+    let package_id = std::env::var(var).with_context(|| format!("{} is not set", var))?;
+    let package_id =
+        ObjectID::from_hex_literal(&package_id).with_context(|| format!("Invalid {}", var))?;
+    let data = time_chain_call(
+        "get_object",
+        client
+            .read_api()
+            .get_object_with_options(package_id, IotaObjectDataOptions::new().with_type()),
+    )
+    .await?
+    .data
+    .with_context(|| format!("{} {} not found", var, package_id))?;
+    if !matches!(data.type_, Some(ObjectType::Package)) {
+        bail!("{} {} is not a package", var, package_id);
+    }
+    Ok(package_id.to_string())
+}
+
+async fn federation(client: &IotaClient) -> anyhow::Result<String> {
+    let federation_id = federation_id()?;
+    let response = time_chain_call(
+        "get_object",
+        client
+            .read_api()
+            .get_object_with_options(federation_id, IotaObjectDataOptions::new().with_content()),
+    )
+    .await?;
+    move_fields(&response).with_context(|| format!("Federation {} not readable", federation_id))?;
+    Ok(federation_id.to_string())
+}
@@ -1,78 +1,212 @@
 // Copyright (c) IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use std::fmt;
 use std::path::PathBuf;
+use std::str::FromStr;
 
-use anyhow::Context;
+use anyhow::{anyhow, bail, Context};
 use identity_iota::credential::Credential;
 use identity_iota::credential::Jwt;
 use identity_iota::iota::IotaDocument;
-use identity_iota::iota_interaction::KeytoolSigner;
 use identity_iota::iota_interaction::KeytoolStorage as Keytool;
 use identity_iota::storage::JwkDocumentExt;
 use identity_iota::storage::JwsSignatureOptions;
 use identity_iota::storage::KeytoolStorage;
+use identity_iota::storage::{JwkStorage, KeyIdStorage, MethodDigest};
 use identity_iota::verification::jws::JwsAlgorithm;
 use identity_iota::verification::MethodScope;
+use identity_iota::verification::VerificationMethod;
 
 use identity_iota::{
-    core::{Object, OrderedSet, Url},
+    core::{Object, OrderedSet},
     credential::LinkedDomainService,
     did::{DIDUrl, DID},
 };
 
 use identity_iota::iota::rebased::client::IdentityClient;
 use identity_iota::iota::rebased::client::IdentityClientReadOnly;
+use identity_iota::iota::rebased::migration::Identity;
+use identity_iota::iota::IotaDID;
 use identity_iota::storage::KeyType;
-use iota_sdk::types::base_types::IotaAddress;
-use iota_sdk::IotaClientBuilder;
-use iota_sdk::IOTA_TESTNET_URL;
+use iota_sdk::types::base_types::ObjectRef;
 use rand::distributions::DistString;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::domain_linkage::linked_domains;
+use crate::network::iota_client;
+use crate::signer::{account_signer, AccountSigner, SignerConfig};
+use crate::verification::MethodJwsVerifier;
+
 pub const TEST_GAS_BUDGET: u64 = 50_000_000;
 
+/// Key type and JWS algorithm of a verification method.
+///
+/// Written as `ed25519`, `p256`, `secp256k1` or `<key type>:<algorithm>`,
+/// e.g. `secp256r1:ES256`, for any other key type the keytool supports. The
+/// algorithm has to be one of `MethodJwsVerifier::ALGORITHMS`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub struct MethodKey {
+    key_type: String,
+    algorithm: JwsAlgorithm,
+}
+
+impl MethodKey {
+    pub fn ed25519() -> Self {
+        Self {
+            key_type: "ed25519".to_string(),
+            algorithm: JwsAlgorithm::EdDSA,
+        }
+    }
+
+    pub fn p256() -> Self {
+        Self {
+            key_type: "secp256r1".to_string(),
+            algorithm: JwsAlgorithm::ES256,
+        }
+    }
+
+    pub fn secp256k1() -> Self {
+        Self {
+            key_type: "secp256k1".to_string(),
+            algorithm: JwsAlgorithm::ES256K,
+        }
+    }
+
+    /// Key of new verification methods, read from `DID_KEY_TYPE` and defaulting to P-256.
+    pub fn from_env() -> anyhow::Result<Self> {
+        match std::env::var("DID_KEY_TYPE") {
+            Ok(value) if !value.trim().is_empty() => value.parse(),
+            _ => Ok(Self::p256()),
+        }
+    }
+
+    pub fn key_type(&self) -> KeyType {
+        KeyType::new(&self.key_type)
+    }
+
+    pub fn algorithm(&self) -> JwsAlgorithm {
+        self.algorithm
+    }
+}
+
+impl Default for MethodKey {
+    fn default() -> Self {
+        Self::p256()
+    }
+}
+
+impl FromStr for MethodKey {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Self> {
+        let value = value.trim();
+        if let Some((key_type, algorithm)) = value.split_once(':') {
+            if key_type.is_empty() {
+                bail!("Key type missing in '{}'", value);
+            }
+            let algorithm = JwsAlgorithm::from_str(&algorithm.to_uppercase())
+                .map_err(|_| anyhow!("Unknown JWS algorithm '{}'", algorithm))?;
+            if !MethodJwsVerifier::ALGORITHMS.contains(&algorithm) {
+                bail!(
+                    "Unsupported JWS algorithm '{}', use EdDSA, ES256 or ES256K",
+                    algorithm.name()
+                );
+            }
+            return Ok(Self {
+                key_type: key_type.to_lowercase(),
+                algorithm,
+            });
+        }
+        match value.to_lowercase().as_str() {
+            "ed25519" => Ok(Self::ed25519()),
+            "p256" | "p-256" | "secp256r1" => Ok(Self::p256()),
+            "secp256k1" => Ok(Self::secp256k1()),
+            other => bail!(
+                "Unsupported key type '{}', use ed25519, p256, secp256k1 or <key type>:<algorithm>",
+                other
+            ),
+        }
+    }
+}
+
+impl fmt::Display for MethodKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.key_type, self.algorithm.name())
+    }
+}
+
+impl TryFrom<String> for MethodKey {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> anyhow::Result<Self> {
+        value.parse()
+    }
+}
+
+impl From<MethodKey> for String {
+    fn from(key: MethodKey) -> Self {
+        key.to_string()
+    }
+}
+
+/// Storage of DID verification method keys.
+///
+/// The keys live in the IOTA keytool, which keeps them unencrypted, so they
+/// are refused unless the accounts are in the plain keystore as well.
+pub fn method_key_storage() -> anyhow::Result<KeytoolStorage> {
+    if !matches!(SignerConfig::from_env()?, SignerConfig::File) {
+        bail!("DID method keys are stored unencrypted in the IOTA keytool, use SIGNER_BACKEND=file for DID keys");
+    }
+    Ok(KeytoolStorage::from(Keytool::default()))
+}
+
+/// Deletes the key of a verification method from `storage`.
+///
+/// Only for methods already removed from the published document, which
+/// would otherwise point at a key that no longer exists. A key deleted
+/// before is skipped.
+pub async fn delete_method_key(
+    storage: &KeytoolStorage,
+    method: &VerificationMethod,
+) -> anyhow::Result<()> {
+    let digest = MethodDigest::new(method)?;
+    let Ok(key_id) = storage.key_id_storage().get_key_id(&digest).await else {
+        return Ok(());
+    };
+    storage.key_storage().delete(&key_id).await?;
+    storage.key_id_storage().delete_key_id(&digest).await?;
+    Ok(())
+}
+
+/// Creates and publishes a DID document whose verification method uses `key`.
 pub async fn create_did_document(
     alias: &str,
     is_domain_linkage: bool,
+    key: &MethodKey,
 ) -> anyhow::Result<(IotaDocument, String)> {
-    let keytool = Keytool::default();
-    let iota_account = keytool
-        .get_key_by_alias(alias)
-        .expect("Error get_key_by_alias")
-        .unwrap_or_else(|| {
-            eprintln!("No key found for alias {alias}");
-            std::process::exit(1);
-        });
-
-    let address = IotaAddress::from(&iota_account);
-
-    let identity_client = {
-        let read_only_client = get_read_only_client().await?;
-        let signer = keytool.signer().with_address(address).build()?;
-        IdentityClient::new(read_only_client, signer).await?
-    };
+    let keytool_storage = method_key_storage()?;
+    let identity_client = get_client(alias).await?;
 
-    let keytool_storage = KeytoolStorage::from(keytool);
     let mut did_document = IotaDocument::new(identity_client.network());
 
     let _vm_fragment = did_document
         .generate_method(
             &keytool_storage,
-            KeyType::new("secp256r1"),
-            JwsAlgorithm::ES256,
+            key.key_type(),
+            key.algorithm(),
             None,
             MethodScope::VerificationMethod,
         )
         .await?;
 
     if is_domain_linkage {
-        let domain_str =
-            std::env::var("NEXT_PUBLIC_DAPP_URL").expect("Missing env var NEXT_PUBLIC_DAPP_URL");
-        let domain_url: Url = Url::parse(&domain_str)?;
-
         let mut domains = OrderedSet::new();
-        domains.append(domain_url.clone());
+        for domain_url in linked_domains()? {
+            domains.append(domain_url);
+        }
 
         let service_url: DIDUrl = did_document.id().clone().join("#domain-linkage")?;
         let linked_domain_service = LinkedDomainService::new(service_url, domains, Object::new())?;
@@ -88,13 +222,47 @@ pub async fn create_did_document(
     Ok((did_document, _vm_fragment))
 }
 
+/// Publishes `document` as the new version of its identity, paid with
+/// `gas_coin`.
+///
+/// Unlike `IdentityClient::publish_did_document_update`, which picks any coin
+/// of the signer, the gas payment is explicit so the update can run within a
+/// `TxSubmitter` lane.
+pub async fn publish_did_update(
+    identity_client: &IdentityClient<AccountSigner>,
+    document: IotaDocument,
+    gas_coin: ObjectRef,
+) -> anyhow::Result<()> {
+    let did: &IotaDID = document.id();
+    let Identity::FullFledged(mut identity) = identity_client
+        .get_identity(did.to_object_id())
+        .await
+        .with_context(|| format!("Failed to get the identity of {}", did))?
+    else {
+        bail!("{} is not a full-fledged identity", did);
+    };
+    let controller_token = identity
+        .get_controller_token(identity_client)
+        .await?
+        .with_context(|| format!("{} controls no {}", identity_client.sender_address(), did))?;
+
+    identity
+        .update_did_document(document, &controller_token)
+        .finish(identity_client)
+        .await?
+        .with_gas_budget(TEST_GAS_BUDGET)
+        .with_gas_payment(vec![gas_coin])
+        .build_and_execute(identity_client)
+        .await?;
+    Ok(())
+}
+
 pub async fn create_credential(
     credential: Credential,
     issuer_document: IotaDocument,
     issuer_vm_fragment: &str,
 ) -> anyhow::Result<Jwt> {
-    let keytool = Keytool::default();
-    let keytool_storage = KeytoolStorage::from(keytool);
+    let keytool_storage = method_key_storage()?;
 
     let credential_jwt: Jwt = issuer_document
         .create_credential_jwt(
@@ -109,22 +277,17 @@ pub async fn create_credential(
     Ok(credential_jwt)
 }
 
-pub async fn get_client(alias: &str) -> Result<IdentityClient<KeytoolSigner>, anyhow::Error> {
-    let keytool = Keytool::default();
-    let iota_account = keytool
-        .get_key_by_alias(alias)
-        .expect("Error get_key_by_alias")
-        .unwrap_or_else(|| {
-            eprintln!("No key found for alias {alias}");
-            std::process::exit(1);
-        });
+pub async fn get_client(alias: &str) -> Result<IdentityClient<AccountSigner>, anyhow::Error> {
+    get_client_with_signer(account_signer(alias).await?).await
+}
 
-    let address = IotaAddress::from(&iota_account);
-    println!("Alias address: {}", address);
+pub async fn get_client_with_signer(
+    signer: AccountSigner,
+) -> Result<IdentityClient<AccountSigner>, anyhow::Error> {
+    println!("Alias address: {}", signer.address());
 
     let identity_client = {
         let read_only_client = get_read_only_client().await?;
-        let signer = keytool.signer().with_address(address).build()?;
         IdentityClient::new(read_only_client, signer).await?
     };
 
@@ -140,12 +303,7 @@ pub fn random_stronghold_path() -> PathBuf {
 }
 
 pub async fn get_read_only_client() -> anyhow::Result<IdentityClientReadOnly> {
-    let api_endpoint =
-        std::env::var("API_ENDPOINT").unwrap_or_else(|_| IOTA_TESTNET_URL.to_string());
-    let iota_client = IotaClientBuilder::default()
-        .build(&api_endpoint)
-        .await
-        .map_err(|err| anyhow::anyhow!(format!("failed to connect to network; {}", err)))?;
+    let iota_client = iota_client().await?;
     let package_id = std::env::var("IOTA_IDENTITY_PKG_ID")
         .map_err(|e| {
             anyhow::anyhow!(
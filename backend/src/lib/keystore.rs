@@ -1,153 +1,287 @@
 // Copyright (c) IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::{bail, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use hierarchies::client::{HierarchiesClient, HierarchiesClientReadOnly};
 use iota::client_commands;
 use iota_config::{
     iota_config_dir, Config, PersistedConfig, IOTA_CLIENT_CONFIG, IOTA_KEYSTORE_FILENAME,
 };
-use iota_interaction::IotaKeySignature;
 use iota_keys::keystore::{AccountKeystore, FileBasedKeystore};
 use iota_sdk::{
     iota_client_config::{IotaClientConfig, IotaEnv},
     types::{
-        base_types::IotaAddress, crypto::SignatureScheme::ED25519, transaction::TransactionData,
+        base_types::IotaAddress,
+        crypto::{IotaKeyPair, PublicKey, Signature, SignatureScheme::ED25519},
+        transaction::TransactionData,
     },
     wallet_context::WalletContext,
-    IotaClient, IotaClientBuilder,
+    IotaClient,
 };
 
-use secret_storage::{SignatureScheme as SignerSignatureScheme, Signer as SignerTrait};
+use crate::encrypted_keystore::EncryptedKeystore;
+use crate::network::{faucet_url, iota_client};
+use crate::signer::{
+    account_signer, AccountSigner, KeyBackend, SignerConfig, DEFAULT_ENCRYPTED_KEYSTORE_PATH,
+};
 use shared_crypto::intent::Intent;
-use std::process::Command;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 pub const ROOT_AUTH_ALIAS: &str = "root-auth";
 pub const MANUFACTURER_ALIAS: &str = "manu-fact";
 pub const GAS_STATION_ALIAS: &str = "gas-stat";
 
-const GAS_LOCAL_NETWORK: &str = "https://faucet.testnet.iota.cafe/gas";
-
 pub async fn get_hierarchies_client(
     account_alias: String,
-) -> anyhow::Result<HierarchiesClient<KeystoreClient>> {
+) -> anyhow::Result<HierarchiesClient<AccountSigner>> {
+    let read_only_client = get_hierarchies_read_only_client().await?;
+
+    let signer = account_signer(&account_alias).await?;
+    println!("ith client address loaded: {:?}", signer.address());
+
+    Ok(HierarchiesClient::new(read_only_client, signer).await?)
+}
+
+pub async fn get_hierarchies_read_only_client() -> anyhow::Result<HierarchiesClientReadOnly> {
     let package_id = std::env::var("IOTA_HIERARCHIES_PKG_ID")
         .context("IOTA_HIERARCHIES_PKG_ID is not set in the environment variables")?
         .parse()?;
 
-    let client = IotaClientBuilder::default().build_testnet().await?;
-    println!("IOTA testnet version is: {}", client.api_version());
-
-    let keystore_client = KeystoreClient::get_keystore();
-    let user_address = keystore_client.get_address(account_alias.clone())?;
-    println!("ith client address loaded: {:?}", user_address);
+    let client = iota_client().await?;
+    println!("IOTA node version is: {}", client.api_version());
 
-    let read_only_client =
-        HierarchiesClientReadOnly::new_with_pkg_id(client.clone(), package_id).await?;
-    Ok(HierarchiesClient::new(read_only_client, keystore_client).await?)
+    Ok(HierarchiesClientReadOnly::new_with_pkg_id(client, package_id).await?)
 }
 
 pub async fn faucet(address: IotaAddress) -> anyhow::Result<()> {
-    client_commands::request_tokens_from_faucet(address, GAS_LOCAL_NETWORK.to_string())
+    client_commands::request_tokens_from_faucet(address, faucet_url())
         .await
         .context("Failed to request tokens from faucet")?;
     tokio::time::sleep(std::time::Duration::from_millis(100)).await;
     Ok(())
 }
 
+/// Local keystore of the backend accounts.
+///
+/// Keys live either in the plain IOTA CLI keystore or, with
+/// `SIGNER_BACKEND=encrypted`, in a passphrase-encrypted keystore that is
+/// unlocked once when the client is opened.
 #[derive(Clone)]
-pub struct KeystoreClient(pub Arc<FileBasedKeystore>);
+pub enum KeystoreClient {
+    File(Arc<FileBasedKeystore>),
+    Encrypted(Arc<EncryptedKeystore>),
+}
 
 impl KeystoreClient {
     pub fn new(alias: String) -> Self {
-        let keystore_path = iota_config_dir()
-            .expect("Failed to get config directory")
-            .join(IOTA_KEYSTORE_FILENAME);
-        let mut file_based_keystore =
-            FileBasedKeystore::new(&keystore_path).expect("Error creating Filestore");
-        match file_based_keystore.generate_and_add_new_key(ED25519, Some(alias.clone()), None, None)
-        {
-            Ok(_) => println!("Key generated successfully."),
-            Err(e) if e.to_string().contains("Alias") => {
-                println!(
-                    "Warning: Alias '{}' already exists. Using existing key.",
-                    alias
-                );
+        match keystore_passphrase().expect("Error selecting keystore") {
+            Some(passphrase) => {
+                let mut encrypted_keystore =
+                    EncryptedKeystore::open(encrypted_keystore_path(), &passphrase)
+                        .expect("Error unlocking encrypted keystore");
+                match encrypted_keystore.generate_key(&alias) {
+                    Ok(_) => println!("Key generated successfully."),
+                    Err(e) if e.to_string().contains("Alias") => {
+                        println!(
+                            "Warning: Alias '{}' already exists. Using existing key.",
+                            alias
+                        );
+                    }
+                    Err(e) => panic!("Unexpected error generating key: {:?}", e),
+                }
+                encrypted_keystore.save().expect("Error save keystore");
+                KeystoreClient::Encrypted(Arc::new(encrypted_keystore))
+            }
+            None => {
+                let mut file_based_keystore = FileBasedKeystore::new(&file_keystore_path())
+                    .expect("Error creating Filestore");
+                match file_based_keystore.generate_and_add_new_key(
+                    ED25519,
+                    Some(alias.clone()),
+                    None,
+                    None,
+                ) {
+                    Ok(_) => println!("Key generated successfully."),
+                    Err(e) if e.to_string().contains("Alias") => {
+                        println!(
+                            "Warning: Alias '{}' already exists. Using existing key.",
+                            alias
+                        );
+                    }
+                    Err(e) => panic!("Unexpected error generating key: {:?}", e),
+                }
+                file_based_keystore.save().expect("Error save keystore");
+                KeystoreClient::File(Arc::new(file_based_keystore))
             }
-            Err(e) => panic!("Unexpected error generating key: {:?}", e),
         }
-        file_based_keystore.save().expect("Error save keystore");
-        KeystoreClient(Arc::new(file_based_keystore))
     }
 
+    /// Imports a bech32 encoded private key under `alias`.
+    ///
+    /// The key is decoded in-process, so it never shows up on a command line.
+    /// Importing the same key twice is a no-op.
     pub fn import(private_key: &str, alias: &str) -> Result<()> {
-        let status = Command::new("iota")
-            .args([
-                "keytool",
-                "import",
-                private_key,
-                "ed25519",
-                "--alias",
-                alias,
-            ])
-            .status()
-            .with_context(|| "Error iota keytool import")?;
-
-        if !status.success() {
-            bail!("iota keytool import exit code no-zero");
+        let keypair = IotaKeyPair::decode(private_key.trim())
+            .map_err(|e| anyhow!("Invalid private key for alias '{}': {}", alias, e))?;
+        let address = IotaAddress::from(&keypair.public());
+
+        match keystore_passphrase()? {
+            Some(passphrase) => {
+                let mut encrypted_keystore =
+                    EncryptedKeystore::open(encrypted_keystore_path(), &passphrase)?;
+                match encrypted_keystore.address(alias) {
+                    Ok(existing) if existing == address => {}
+                    Ok(_) => bail!("Alias '{}' already holds a different key", alias),
+                    Err(_) => {
+                        encrypted_keystore.add_key(alias, keypair)?;
+                        encrypted_keystore.save()?;
+                    }
+                }
+            }
+            None => {
+                let mut file_based_keystore = FileBasedKeystore::new(&file_keystore_path())?;
+                match file_based_keystore.get_address_by_alias(alias.to_string()) {
+                    Ok(existing) if *existing == address => {}
+                    Ok(_) => bail!("Alias '{}' already holds a different key", alias),
+                    Err(_) => {
+                        file_based_keystore.add_key(Some(alias.to_string()), keypair)?;
+                        file_based_keystore.save()?;
+                    }
+                }
+            }
         }
 
         println!("Account imported correctly '{}'", alias);
         Ok(())
     }
 
+    /// Moves the current key of `alias` to `retired_alias` and generates a new key under `alias`.
+    ///
+    /// Returns the old and the new address.
+    pub fn rotate_key(alias: &str, retired_alias: &str) -> Result<(IotaAddress, IotaAddress)> {
+        match keystore_passphrase()? {
+            Some(passphrase) => {
+                let mut encrypted_keystore =
+                    EncryptedKeystore::open(encrypted_keystore_path(), &passphrase)?;
+                let old_address = encrypted_keystore.address(alias)?;
+                encrypted_keystore.rename_alias(alias, retired_alias)?;
+                let new_address = encrypted_keystore.generate_key(alias)?;
+                encrypted_keystore.save()?;
+                Ok((old_address, new_address))
+            }
+            None => {
+                let mut file_based_keystore = FileBasedKeystore::new(&file_keystore_path())?;
+                let old_address = *file_based_keystore.get_address_by_alias(alias.to_string())?;
+                file_based_keystore.update_alias(alias, Some(retired_alias))?;
+                let new_address = file_based_keystore
+                    .generate_and_add_new_key(ED25519, Some(alias.to_string()), None, None)?
+                    .0;
+                file_based_keystore.save()?;
+                Ok((old_address, new_address))
+            }
+        }
+    }
+
+    /// Swaps the aliases of two keys, used to undo [`KeystoreClient::rotate_key`].
+    pub fn swap_aliases(alias: &str, other_alias: &str) -> Result<()> {
+        let parking_alias = format!("{}-swap", alias);
+        match keystore_passphrase()? {
+            Some(passphrase) => {
+                let mut encrypted_keystore =
+                    EncryptedKeystore::open(encrypted_keystore_path(), &passphrase)?;
+                encrypted_keystore.rename_alias(alias, &parking_alias)?;
+                encrypted_keystore.rename_alias(other_alias, alias)?;
+                encrypted_keystore.rename_alias(&parking_alias, other_alias)?;
+                encrypted_keystore.save()
+            }
+            None => {
+                let mut file_based_keystore = FileBasedKeystore::new(&file_keystore_path())?;
+                file_based_keystore.update_alias(alias, Some(&parking_alias))?;
+                file_based_keystore.update_alias(other_alias, Some(alias))?;
+                file_based_keystore.update_alias(&parking_alias, Some(other_alias))?;
+                file_based_keystore.save()?;
+                Ok(())
+            }
+        }
+    }
+
     pub fn get_keystore() -> Self {
-        let keystore_path = iota_config_dir()
-            .expect("Failed to get config directory")
-            .join(IOTA_KEYSTORE_FILENAME);
-        let file_based_keystore =
-            FileBasedKeystore::new(&keystore_path).expect("Error retrieving Filestore");
-        KeystoreClient(Arc::new(file_based_keystore))
+        Self::open().expect("Error retrieving keystore")
+    }
+
+    /// Opens the keystore selected by `SIGNER_BACKEND`.
+    pub fn open() -> Result<Self> {
+        match keystore_passphrase()? {
+            Some(passphrase) => Self::unlock(encrypted_keystore_path(), &passphrase),
+            None => Ok(KeystoreClient::File(Arc::new(FileBasedKeystore::new(
+                &file_keystore_path(),
+            )?))),
+        }
+    }
+
+    /// Decrypts the keystore at `path` with `passphrase`.
+    pub fn unlock(path: impl AsRef<Path>, passphrase: &str) -> Result<Self> {
+        let encrypted_keystore = EncryptedKeystore::open(path, passphrase)?;
+        println!(
+            "🔓 Keystore unlocked ({} accounts)",
+            encrypted_keystore.aliases().count()
+        );
+        Ok(KeystoreClient::Encrypted(Arc::new(encrypted_keystore)))
     }
 
     pub fn get_address(&self, alias: String) -> anyhow::Result<IotaAddress> {
-        self.0.get_address_by_alias(alias).map(|addr| *addr)
+        match self {
+            KeystoreClient::File(keystore) => {
+                keystore.get_address_by_alias(alias).map(|addr| *addr)
+            }
+            KeystoreClient::Encrypted(keystore) => keystore.address(&alias),
+        }
     }
 }
 
 #[async_trait::async_trait]
-impl SignerTrait<IotaKeySignature> for KeystoreClient {
-    type KeyId = ();
-    async fn sign(
-        &self,
-        data: &TransactionData,
-    ) -> secret_storage::Result<<IotaKeySignature as SignerSignatureScheme>::Signature> {
-        let address = self
-            .0
-            .get_address_by_alias(ROOT_AUTH_ALIAS.to_owned())
-            .unwrap();
-        let signature = self
-            .0
-            .sign_secure(address, data, Intent::iota_transaction())
-            .unwrap();
-        Ok(signature)
+impl KeyBackend for KeystoreClient {
+    async fn public_key(&self, alias: &str) -> anyhow::Result<PublicKey> {
+        match self {
+            KeystoreClient::File(keystore) => {
+                let address = self.get_address(alias.to_string())?;
+                Ok(keystore.get_key(&address)?.public())
+            }
+            KeystoreClient::Encrypted(keystore) => keystore.public_key(alias).await,
+        }
     }
 
-    async fn public_key(
-        &self,
-    ) -> secret_storage::Result<<IotaKeySignature as secret_storage::SignatureScheme>::PublicKey>
-    {
-        let address = self
-            .0
-            .get_address_by_alias(ROOT_AUTH_ALIAS.to_owned())
-            .unwrap();
-        let key = self.0.get_key(address).unwrap();
-        Ok(key.public())
+    async fn sign(&self, alias: &str, data: &TransactionData) -> anyhow::Result<Signature> {
+        match self {
+            KeystoreClient::File(keystore) => {
+                let address = self.get_address(alias.to_string())?;
+                keystore
+                    .sign_secure(&address, data, Intent::iota_transaction())
+                    .map_err(Into::into)
+            }
+            KeystoreClient::Encrypted(keystore) => keystore.sign(alias, data).await,
+        }
     }
+}
+
+fn file_keystore_path() -> PathBuf {
+    iota_config_dir()
+        .expect("Failed to get config directory")
+        .join(IOTA_KEYSTORE_FILENAME)
+}
+
+fn encrypted_keystore_path() -> String {
+    std::env::var("ENCRYPTED_KEYSTORE_PATH")
+        .unwrap_or_else(|_| DEFAULT_ENCRYPTED_KEYSTORE_PATH.to_string())
+}
 
-    fn key_id(&self) -> Self::KeyId {
-        ()
+/// Passphrase of the encrypted keystore, `None` for the IOTA CLI keystore.
+fn keystore_passphrase() -> Result<Option<String>> {
+    match SignerConfig::from_env()? {
+        SignerConfig::File => Ok(None),
+        SignerConfig::Encrypted { passphrase, .. } => Ok(Some(passphrase)),
+        _ => bail!("Local keys need SIGNER_BACKEND 'file' or 'encrypted'"),
     }
 }
 
@@ -171,8 +305,8 @@ pub async fn setup_for_write(
 pub async fn setup_for_read(
     alias: Option<String>,
 ) -> Result<(IotaClient, IotaAddress), anyhow::Error> {
-    let client = IotaClientBuilder::default().build_testnet().await?;
-    println!("IOTA testnet version is: {}", client.api_version());
+    let client = iota_client().await?;
+    println!("IOTA node version is: {}", client.api_version());
     let wallet = retrieve_wallet(alias)?;
     assert!(wallet.get_addresses().len() >= 2);
     let active_address = wallet.active_address()?;
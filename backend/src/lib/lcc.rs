@@ -0,0 +1,441 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::str::FromStr;
+
+use anyhow::{bail, Context};
+use iota_json_rpc_types::{
+    IotaExecutionStatus, IotaTransactionBlockEffectsAPI, IotaTransactionBlockResponse,
+};
+use iota_sdk::{
+    json::IotaJsonValue,
+    rpc_types::{IotaObjectDataOptions, ObjectChange},
+    types::{
+        base_types::{IotaAddress, ObjectID},
+        object::Owner,
+        programmable_transaction_builder::ProgrammableTransactionBuilder,
+        transaction::{Argument, ObjectArg, TransactionKind},
+    },
+    IotaClient,
+};
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::TypeTag;
+use serde::Serialize;
+use serde_json::json;
+use utoipa::ToSchema;
+
+use crate::events::package_id;
+use crate::identity::TEST_GAS_BUDGET;
+use crate::metrics::time_chain_call;
+use crate::signer::AccountSigner;
+use crate::transaction::sign_and_execute_transaction;
+
+pub const LCC_MODULE: &str = "LCC";
+pub const REWARD_MODULE: &str = "lcc_reward";
+/// LCC has 9 decimals, one LCC is 10^9 base units.
+pub const LCC_DECIMALS: u8 = 9;
+/// Base units `lcc_reward::send_lcc_reward` pays per logged entry.
+pub const REWARD_VALUE: u64 = 1_000_000_000;
+
+/// Objects of the published `audit_trails` package used by the LCC bindings.
+///
+/// Read from `IOTA_CUSTOM_NOTARIZATION_PKG_ID`, `LCC_TREASURY_CAP_ID` and `LCC_VAULT_ID`.
+#[derive(Clone, Debug)]
+pub struct LccConfig {
+    pub package_id: ObjectID,
+    pub treasury_cap_id: Option<ObjectID>,
+    pub vault_id: Option<ObjectID>,
+}
+
+impl LccConfig {
+    pub fn from_env() -> anyhow::Result<Self> {
+        let object_id = |name: &str| -> anyhow::Result<Option<ObjectID>> {
+            std::env::var(name)
+                .ok()
+                .filter(|value| !value.is_empty())
+                .map(|value| {
+                    ObjectID::from_hex_literal(&value).with_context(|| format!("Invalid {}", name))
+                })
+                .transpose()
+        };
+        Ok(Self {
+            package_id: package_id()?,
+            treasury_cap_id: object_id("LCC_TREASURY_CAP_ID")?,
+            vault_id: object_id("LCC_VAULT_ID")?,
+        })
+    }
+
+    /// Type of the LCC currency, `<package>::LCC::LCC`.
+    pub fn lcc_type(&self) -> String {
+        format!("{}::{}::LCC", self.package_id, LCC_MODULE)
+    }
+
+    /// Whether `tag` is the LCC currency type.
+    pub fn is_lcc_type(&self, tag: &TypeTag) -> bool {
+        matches!(tag, TypeTag::Struct(lcc) if ObjectID::from(lcc.address) == self.package_id
+            && lcc.module.as_str() == LCC_MODULE
+            && lcc.name.as_str() == "LCC")
+    }
+
+    pub(crate) fn treasury_cap_id(&self) -> anyhow::Result<ObjectID> {
+        self.treasury_cap_id
+            .context("LCC_TREASURY_CAP_ID must be set")
+    }
+
+    fn vault_id(&self) -> anyhow::Result<ObjectID> {
+        self.vault_id.context("LCC_VAULT_ID must be set")
+    }
+}
+
+/// An executed LCC transaction.
+#[derive(Serialize, Debug, ToSchema)]
+pub struct LccTransaction {
+    pub digest: String,
+    /// LCC coin created by the transaction, e.g. the minted coin.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>)]
+    pub coin_id: Option<ObjectID>,
+}
+
+/// LCC locked in the reward vault for a product.
+#[derive(Serialize, Debug, ToSchema)]
+pub struct VaultBalance {
+    #[schema(value_type = String)]
+    pub product: ObjectID,
+    /// Locked value in base units.
+    pub value: u64,
+}
+
+/// Mints `amount` base units of LCC to the signer, who must own the treasury cap.
+///
+/// Use `treasury::treasury_mint`, which enforces the custody and mint limits.
+pub(crate) async fn mint(
+    client: &IotaClient,
+    signer: &AccountSigner,
+    amount: u64,
+    gas: Option<ObjectID>,
+) -> anyhow::Result<LccTransaction> {
+    let config = LccConfig::from_env()?;
+    let response = execute_move_call(
+        client,
+        signer,
+        &config,
+        LCC_MODULE,
+        "mint",
+        vec![
+            IotaJsonValue::from_object_id(config.treasury_cap_id()?),
+            IotaJsonValue::new(json!(amount.to_string()))?,
+        ],
+        gas,
+    )
+    .await?;
+    println!("✅ Minted {} LCC base units: {}", amount, response.digest);
+    Ok(LccTransaction {
+        digest: response.digest.to_string(),
+        coin_id: created_coin(&config, &response),
+    })
+}
+
+/// Burns the LCC coin `coin_id`, which the signer must own together with the treasury cap.
+pub(crate) async fn burn(
+    client: &IotaClient,
+    signer: &AccountSigner,
+    coin_id: ObjectID,
+    gas: Option<ObjectID>,
+) -> anyhow::Result<LccTransaction> {
+    let config = LccConfig::from_env()?;
+    let response = execute_move_call(
+        client,
+        signer,
+        &config,
+        LCC_MODULE,
+        "burn",
+        vec![
+            IotaJsonValue::from_object_id(config.treasury_cap_id()?),
+            IotaJsonValue::from_object_id(coin_id),
+        ],
+        gas,
+    )
+    .await?;
+    println!("🔥 Burned LCC coin {}: {}", coin_id, response.digest);
+    Ok(LccTransaction {
+        digest: response.digest.to_string(),
+        coin_id: None,
+    })
+}
+
+/// Total LCC supply in base units, read with `LCC::total_supply`.
+pub async fn total_supply(client: &IotaClient) -> anyhow::Result<u64> {
+    let config = LccConfig::from_env()?;
+    let (cap, owner) = object_arg(client, config.treasury_cap_id()?, false).await?;
+    inspect_u64(
+        client,
+        &config,
+        owner,
+        LCC_MODULE,
+        "total_supply",
+        vec![cap],
+    )
+    .await
+}
+
+/// Locks `amount` base units of LCC in the reward vault for `product`.
+///
+/// The LCC are split from `coin`, or from the largest LCC coin of the signer
+/// when omitted. `lcc_reward::top_up_dpp` inserts a new vault entry, so a
+/// product can only be topped up once.
+pub async fn top_up_dpp(
+    client: &IotaClient,
+    signer: &AccountSigner,
+    product: ObjectID,
+    amount: u64,
+    coin: Option<ObjectID>,
+    gas: Option<ObjectID>,
+) -> anyhow::Result<LccTransaction> {
+    let config = LccConfig::from_env()?;
+    if read_dpp_value(client, product).await?.is_some() {
+        bail!("Product {} already has a reward vault entry", product);
+    }
+    let coin = match coin {
+        Some(coin) => coin,
+        None => lcc_coin(client, signer.address(), amount)
+            .await?
+            .with_context(|| {
+                format!(
+                    "No LCC coin of {} holds {} base units",
+                    signer.address(),
+                    amount
+                )
+            })?,
+    };
+
+    let response = execute_move_call(
+        client,
+        signer,
+        &config,
+        REWARD_MODULE,
+        "top_up_dpp",
+        vec![
+            IotaJsonValue::from_object_id(config.vault_id()?),
+            IotaJsonValue::from_object_id(coin),
+            IotaJsonValue::new(json!(amount.to_string()))?,
+            IotaJsonValue::new(json!(IotaAddress::from(product).to_string()))?,
+        ],
+        gas,
+    )
+    .await?;
+    println!(
+        "✅ Locked {} LCC base units for product {}: {}",
+        amount, product, response.digest
+    );
+    Ok(LccTransaction {
+        digest: response.digest.to_string(),
+        coin_id: None,
+    })
+}
+
+/// LCC locked in the vault for `product`, read with `lcc_reward::read_dpp_value`.
+///
+/// `None` when the vault has no entry for the product.
+pub async fn read_dpp_value(client: &IotaClient, product: ObjectID) -> anyhow::Result<Option<u64>> {
+    let config = LccConfig::from_env()?;
+    let (vault, _) = object_arg(client, config.vault_id()?, true).await?;
+    let mut ptb = ProgrammableTransactionBuilder::new();
+    let vault = ptb.obj(vault)?;
+    let product_arg = ptb.pure(IotaAddress::from(product))?;
+
+    match inspect(
+        client,
+        &config,
+        ptb,
+        IotaAddress::ZERO,
+        REWARD_MODULE,
+        "read_dpp_value",
+        vec![vault, product_arg],
+    )
+    .await
+    {
+        Ok(bytes) => Ok(Some(bcs::from_bytes(&bytes)?)),
+        // VecMap::get_mut aborts for a missing key
+        Err(err) if is_missing_key(&err) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Calls a function of the package with the signer and fails if it aborts.
+async fn execute_move_call(
+    client: &IotaClient,
+    signer: &AccountSigner,
+    config: &LccConfig,
+    module: &str,
+    function: &str,
+    args: Vec<IotaJsonValue>,
+    gas: Option<ObjectID>,
+) -> anyhow::Result<IotaTransactionBlockResponse> {
+    let tx_data = client
+        .transaction_builder()
+        .move_call(
+            signer.address(),
+            config.package_id,
+            module,
+            function,
+            vec![],
+            args,
+            gas,
+            TEST_GAS_BUDGET,
+            None,
+        )
+        .await?;
+    let response = sign_and_execute_transaction(client, signer, tx_data).await?;
+
+    if let Some(effects) = &response.effects {
+        if let IotaExecutionStatus::Failure { error } = effects.status() {
+            bail!("{}::{} failed on chain: {}", module, function, error);
+        }
+    }
+    Ok(response)
+}
+
+/// Transaction argument for `object_id`, and the owner of the object if it has one.
+pub(crate) async fn object_arg(
+    client: &IotaClient,
+    object_id: ObjectID,
+    mutable: bool,
+) -> anyhow::Result<(ObjectArg, IotaAddress)> {
+    let data = time_chain_call(
+        "get_object",
+        client
+            .read_api()
+            .get_object_with_options(object_id, IotaObjectDataOptions::new().with_owner()),
+    )
+    .await?
+    .data
+    .with_context(|| format!("Object {} not found", object_id))?;
+
+    match data.owner {
+        Some(Owner::Shared {
+            initial_shared_version,
+        }) => Ok((
+            ObjectArg::SharedObject {
+                id: object_id,
+                initial_shared_version,
+                mutable,
+            },
+            IotaAddress::ZERO,
+        )),
+        Some(Owner::AddressOwner(owner)) => {
+            Ok((ObjectArg::ImmOrOwnedObject(data.object_ref()), owner))
+        }
+        _ => Ok((
+            ObjectArg::ImmOrOwnedObject(data.object_ref()),
+            IotaAddress::ZERO,
+        )),
+    }
+}
+
+async fn inspect_u64(
+    client: &IotaClient,
+    config: &LccConfig,
+    sender: IotaAddress,
+    module: &str,
+    function: &str,
+    objects: Vec<ObjectArg>,
+) -> anyhow::Result<u64> {
+    let mut ptb = ProgrammableTransactionBuilder::new();
+    let args = objects
+        .into_iter()
+        .map(|object| ptb.obj(object))
+        .collect::<Result<Vec<_>, _>>()?;
+    let bytes = inspect(client, config, ptb, sender, module, function, args).await?;
+    Ok(bcs::from_bytes(&bytes)?)
+}
+
+/// Runs a read-only call with dev inspect and returns its first return value.
+async fn inspect(
+    client: &IotaClient,
+    config: &LccConfig,
+    mut ptb: ProgrammableTransactionBuilder,
+    sender: IotaAddress,
+    module: &str,
+    function: &str,
+    args: Vec<Argument>,
+) -> anyhow::Result<Vec<u8>> {
+    ptb.programmable_move_call(
+        config.package_id,
+        Identifier::from_str(module)?,
+        Identifier::from_str(function)?,
+        vec![],
+        args,
+    );
+    let results = time_chain_call(
+        "dev_inspect_transaction_block",
+        client.read_api().dev_inspect_transaction_block(
+            sender,
+            TransactionKind::programmable(ptb.finish()),
+            None,
+            None,
+            None,
+        ),
+    )
+    .await?;
+    if let Some(error) = results.error {
+        bail!("{}::{} failed: {}", module, function, error);
+    }
+    results
+        .results
+        .and_then(|results| results.into_iter().next())
+        .and_then(|result| result.return_values.into_iter().next())
+        .map(|(bytes, _)| bytes)
+        .with_context(|| format!("{}::{} returned no value", module, function))
+}
+
+fn is_missing_key(err: &anyhow::Error) -> bool {
+    let message = format!("{:#}", err);
+    message.contains("MoveAbort") && message.contains("vec_map")
+}
+
+/// The LCC coin of `owner` with the largest balance, `None` if no coin holds
+/// at least `amount`.
+pub async fn lcc_coin(
+    client: &IotaClient,
+    owner: IotaAddress,
+    amount: u64,
+) -> anyhow::Result<Option<ObjectID>> {
+    let config = LccConfig::from_env()?;
+    let coin = time_chain_call(
+        "get_coins",
+        client
+            .coin_read_api()
+            .get_coins(owner, Some(config.lcc_type()), None, None),
+    )
+    .await?
+    .data
+    .into_iter()
+    .max_by_key(|coin| coin.balance);
+    Ok(coin
+        .filter(|coin| coin.balance >= amount)
+        .map(|coin| coin.coin_object_id))
+}
+
+fn created_coin(config: &LccConfig, response: &IotaTransactionBlockResponse) -> Option<ObjectID> {
+    response
+        .object_changes
+        .as_ref()?
+        .iter()
+        .find_map(|change| match change {
+            ObjectChange::Created {
+                object_id,
+                object_type,
+                ..
+            } if object_type.module.as_str() == "coin"
+                && object_type.name.as_str() == "Coin"
+                && object_type
+                    .type_params
+                    .first()
+                    .is_some_and(|tag| config.is_lcc_type(tag)) =>
+            {
+                Some(*object_id)
+            }
+            _ => None,
+        })
+}
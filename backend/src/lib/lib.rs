@@ -1,9 +1,33 @@
 // Copyright (c) IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod credentials;
+pub mod data;
+pub mod did_lifecycle;
+pub mod did_resolution;
+pub mod domain_linkage;
+pub mod encrypted_keystore;
+pub mod events;
+pub mod gas_balances;
+pub mod health;
 pub mod identity;
 pub mod keystore;
+pub mod lcc;
+pub mod metrics;
+pub mod multisig;
+pub mod network;
+pub mod nft_rewards;
+pub mod presentations;
+pub mod products;
+pub mod reward_ledger;
+pub mod reward_pool;
+pub mod role_registry;
+pub mod rotation;
+pub mod signer;
+pub mod submitter;
 pub mod transaction;
+pub mod treasury;
+pub mod verification;
 
 // Re-export for backward compatibility
 pub mod identity_utils {
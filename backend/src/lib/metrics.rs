@@ -0,0 +1,230 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::future::Future;
+use std::sync::LazyLock;
+use std::time::Instant;
+
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+
+/// Registry of the metrics served at `/metrics`.
+pub static REGISTRY: LazyLock<Registry> = LazyLock::new(Registry::new);
+
+/// LCC base units locked in the reward vault, per product.
+pub static REWARD_POOL_BALANCE: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register(
+        IntGaugeVec::new(
+            Opts::new(
+                "dpp_reward_pool_balance",
+                "LCC base units locked in the reward vault per product",
+            ),
+            &["product"],
+        )
+        .expect("valid metric"),
+    )
+});
+
+/// 1 while a product has fewer rewards left than the configured minimum.
+pub static REWARD_POOL_LOW: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register(
+        IntGaugeVec::new(
+            Opts::new(
+                "dpp_reward_pool_low",
+                "1 while the reward pool of a product is below the minimum",
+            ),
+            &["product"],
+        )
+        .expect("valid metric"),
+    )
+});
+
+/// Automatic top-ups of the reward vault by result.
+pub static REWARD_POOL_TOP_UPS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new(
+                "dpp_reward_pool_top_ups_total",
+                "Automatic reward pool top-ups by result",
+            ),
+            &["result"],
+        )
+        .expect("valid metric"),
+    )
+});
+
+/// HTTP requests served, by method, matched route and status.
+pub static HTTP_REQUESTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new(
+                "dpp_http_requests_total",
+                "HTTP requests by route and status",
+            ),
+            &["method", "route", "status"],
+        )
+        .expect("valid metric"),
+    )
+});
+
+/// Time to answer HTTP requests, by method, matched route and status.
+pub static HTTP_REQUEST_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register(
+        HistogramVec::new(
+            HistogramOpts::new(
+                "dpp_http_request_duration_seconds",
+                "HTTP request latency by route and status",
+            ),
+            &["method", "route", "status"],
+        )
+        .expect("valid metric"),
+    )
+});
+
+/// Latency of the JSON-RPC calls of the backend to the node, by call and result.
+pub static CHAIN_CALL_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register(
+        HistogramVec::new(
+            HistogramOpts::new(
+                "dpp_chain_call_duration_seconds",
+                "Latency of calls to the IOTA node by call and result",
+            ),
+            &["call", "result"],
+        )
+        .expect("valid metric"),
+    )
+});
+
+/// Transactions submitted by the server, by signer alias, result and Move
+/// abort code.
+pub static TRANSACTIONS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new(
+                "dpp_transactions_total",
+                "Submitted transactions by signer, result and Move abort code",
+            ),
+            &["alias", "result", "abort_code"],
+        )
+        .expect("valid metric"),
+    )
+});
+
+/// Nanos of gas spent by the transactions of each signer alias.
+pub static GAS_SPENT: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new(
+                "dpp_gas_spent_nanos_total",
+                "Gas spent in nanos by signer alias",
+            ),
+            &["alias"],
+        )
+        .expect("valid metric"),
+    )
+});
+
+/// IOTA balance in nanos of the backend accounts.
+pub static GAS_BALANCE: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register(
+        IntGaugeVec::new(
+            Opts::new(
+                "dpp_gas_balance_nanos",
+                "IOTA balance in nanos of the backend accounts",
+            ),
+            &["alias"],
+        )
+        .expect("valid metric"),
+    )
+});
+
+/// Awaits `future`, a call to the node, and records its latency as `call`.
+pub async fn time_chain_call<T, E>(
+    call: &'static str,
+    future: impl Future<Output = Result<T, E>>,
+) -> Result<T, E> {
+    let start = Instant::now();
+    let result = future.await;
+    CHAIN_CALL_DURATION
+        .with_label_values(&[call, if result.is_ok() { "ok" } else { "error" }])
+        .observe(start.elapsed().as_secs_f64());
+    result
+}
+
+tokio::task_local! {
+    /// Set while the `TxSubmitter` runs a submission.
+    static SUBMISSION: ();
+}
+
+/// Runs `future` as a submission, whose transactions are recorded once for
+/// all its attempts by the `TxSubmitter`.
+pub async fn submission<F: Future>(future: F) -> F::Output {
+    SUBMISSION.scope((), future).await
+}
+
+/// Whether the current task runs a submission of the `TxSubmitter`.
+pub fn in_submission() -> bool {
+    SUBMISSION.try_with(|_| ()).is_ok()
+}
+
+/// Counts a transaction of `alias`, failed ones by Move abort code.
+pub fn record_transaction(alias: &str, error: Option<&str>) {
+    let (outcome, abort_code) = match error {
+        None => ("success", None),
+        Some(error) => ("failure", move_abort_code(error)),
+    };
+    let abort_code = abort_code.unwrap_or_else(|| "none".to_string());
+    TRANSACTIONS
+        .with_label_values(&[alias, outcome, &abort_code])
+        .inc();
+}
+
+/// Adds `nanos` to the gas spent by `alias`.
+pub fn record_gas(alias: &str, nanos: u64) {
+    GAS_SPENT.with_label_values(&[alias]).inc_by(nanos);
+}
+
+/// `module::code` of the Move abort in an error message, as rendered by the
+/// node: `MoveAbort(MoveLocation { module: ModuleId { address: .., name:
+/// Identifier("app") }, .. }, 3) in command 0`.
+fn move_abort_code(message: &str) -> Option<String> {
+    let abort = &message[message.find("MoveAbort(")? + "MoveAbort(".len()..];
+    let mut depth = 0;
+    let end = abort.char_indices().find_map(|(index, c)| match c {
+        '(' | '{' => {
+            depth += 1;
+            None
+        }
+        ')' | '}' if depth > 0 => {
+            depth -= 1;
+            None
+        }
+        ')' => Some(index),
+        _ => None,
+    })?;
+    let (location, code) = abort[..end].rsplit_once(',')?;
+    let code: u64 = code.trim().parse().ok()?;
+    let module = location
+        .split("name: Identifier(\"")
+        .nth(1)
+        .and_then(|rest| rest.split('"').next());
+    Some(match module {
+        Some(module) => format!("{}::{}", module, code),
+        None => code.to_string(),
+    })
+}
+
+fn register<C: prometheus::core::Collector + Clone + 'static>(collector: C) -> C {
+    REGISTRY
+        .register(Box::new(collector.clone()))
+        .expect("metric registered twice");
+    collector
+}
+
+/// All registered metrics in the Prometheus text format.
+pub fn gather() -> anyhow::Result<String> {
+    let mut buffer = Vec::new();
+    TextEncoder::new().encode(&REGISTRY.gather(), &mut buffer)?;
+    Ok(String::from_utf8(buffer)?)
+}
@@ -0,0 +1,508 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, bail, Context};
+use fastcrypto::encoding::{Base64, Encoding};
+use fastcrypto::traits::{EncodeDecodeBase64, ToFromBytes};
+use hierarchies::client::HierarchiesClient;
+use hierarchies::core::types::{
+    property::FederationProperty, property_name::PropertyName, property_value::PropertyValue,
+};
+use iota_interaction::IotaKeySignature;
+use iota_json_rpc_types::{IotaExecutionStatus, IotaTransactionBlockEffectsAPI};
+use iota_sdk::{
+    rpc_types::IotaTransactionBlockResponseOptions,
+    types::{
+        base_types::{IotaAddress, ObjectID},
+        crypto::{PublicKey, Signature},
+        digests::TransactionDigest,
+        multisig::{MultiSig, MultiSigPublicKey},
+        quorum_driver_types::ExecuteTransactionRequestType,
+        signature::GenericSignature,
+        transaction::{Transaction, TransactionData},
+    },
+};
+use secret_storage::{SignatureScheme as SignerSignatureScheme, Signer as SignerTrait};
+use serde::{Deserialize, Serialize};
+use shared_crypto::intent::{Intent, IntentMessage};
+use tokio::sync::Mutex;
+use utoipa::ToSchema;
+
+use crate::data::{data_path, read_data, write_data};
+use crate::keystore::{get_hierarchies_client, get_hierarchies_read_only_client, ROOT_AUTH_ALIAS};
+use crate::metrics::time_chain_call;
+use crate::network::iota_client;
+use crate::signer::{account_signer, signer_backend};
+
+const PROPOSALS_DIR: &str = "multisig";
+
+/// Serialises updates of proposals, so that concurrent signatures are not lost.
+static PROPOSAL_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// Members of the multisig root authority, read from the environment.
+///
+/// `ROOT_AUTH_MULTISIG_PUBLIC_KEYS` holds the comma separated base64 flagged
+/// public keys, `ROOT_AUTH_MULTISIG_WEIGHTS` their weights (1 each by default)
+/// and `ROOT_AUTH_MULTISIG_THRESHOLD` the weight needed to execute.
+pub struct MultisigCommittee {
+    pub public_keys: Vec<PublicKey>,
+    pub weights: Vec<u8>,
+    pub threshold: u16,
+}
+
+impl MultisigCommittee {
+    pub fn from_env() -> anyhow::Result<Self> {
+        let public_keys = std::env::var("ROOT_AUTH_MULTISIG_PUBLIC_KEYS")
+            .context("ROOT_AUTH_MULTISIG_PUBLIC_KEYS must be set")?
+            .split(',')
+            .map(|key| {
+                PublicKey::decode_base64(key.trim())
+                    .map_err(|e| anyhow!("Invalid multisig public key {}: {}", key, e))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let weights = match std::env::var("ROOT_AUTH_MULTISIG_WEIGHTS") {
+            Ok(weights) => weights
+                .split(',')
+                .map(|weight| weight.trim().parse::<u8>())
+                .collect::<Result<Vec<_>, _>>()
+                .context("Invalid ROOT_AUTH_MULTISIG_WEIGHTS")?,
+            Err(_) => vec![1; public_keys.len()],
+        };
+        if weights.len() != public_keys.len() {
+            bail!("ROOT_AUTH_MULTISIG_WEIGHTS must have one weight per public key");
+        }
+
+        let threshold = std::env::var("ROOT_AUTH_MULTISIG_THRESHOLD")
+            .context("ROOT_AUTH_MULTISIG_THRESHOLD must be set")?
+            .parse()
+            .context("Invalid ROOT_AUTH_MULTISIG_THRESHOLD")?;
+
+        Ok(Self {
+            public_keys,
+            weights,
+            threshold,
+        })
+    }
+
+    pub fn multisig_public_key(&self) -> anyhow::Result<MultiSigPublicKey> {
+        MultiSigPublicKey::new(
+            self.public_keys.clone(),
+            self.weights.clone(),
+            self.threshold,
+        )
+        .map_err(|e| anyhow!("Invalid multisig committee: {}", e))
+    }
+
+    pub fn address(&self) -> anyhow::Result<IotaAddress> {
+        Ok(IotaAddress::from(&self.multisig_public_key()?))
+    }
+
+    fn weight_of(&self, public_key: &PublicKey) -> Option<u16> {
+        self.public_keys
+            .iter()
+            .position(|member| member == public_key)
+            .map(|index| self.weights[index] as u16)
+    }
+}
+
+/// Signer of the client building proposals.
+///
+/// It only stands in for the committee: proposals are signed by the members,
+/// so signing with it always fails and no backend key is loaded.
+struct CommitteeStandIn {
+    public_key: PublicKey,
+}
+
+#[async_trait::async_trait]
+impl SignerTrait<IotaKeySignature> for CommitteeStandIn {
+    type KeyId = String;
+
+    async fn sign(
+        &self,
+        _data: &TransactionData,
+    ) -> secret_storage::Result<<IotaKeySignature as SignerSignatureScheme>::Signature> {
+        Err(secret_storage::Error::Other(anyhow!(
+            "Proposals are signed by the multisig committee"
+        )))
+    }
+
+    async fn public_key(
+        &self,
+    ) -> secret_storage::Result<<IotaKeySignature as SignerSignatureScheme>::PublicKey> {
+        Ok(self.public_key.clone())
+    }
+
+    fn key_id(&self) -> Self::KeyId {
+        "multisig-committee".to_string()
+    }
+}
+
+/// Federation administration that can be proposed to the multisig root authority.
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FederationOperation {
+    AddProperty {
+        #[schema(value_type = String)]
+        federation_id: ObjectID,
+        property_name: String,
+        allowed_values: Vec<String>,
+    },
+    AccreditToAttest {
+        #[schema(value_type = String)]
+        federation_id: ObjectID,
+        #[schema(value_type = String)]
+        receiver: ObjectID,
+        property_name: String,
+        allowed_values: Vec<String>,
+    },
+    AccreditToAccredit {
+        #[schema(value_type = String)]
+        federation_id: ObjectID,
+        #[schema(value_type = String)]
+        receiver: ObjectID,
+        property_name: String,
+        allowed_values: Vec<String>,
+    },
+}
+
+/// An unsigned transaction of the multisig address collecting partial signatures.
+///
+/// The transaction pins the version of the multisig gas coin it was built
+/// with. Executing any proposal changes that version, so every other pending
+/// proposal paid with the same coin can no longer execute and has to be
+/// proposed and signed again.
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct Proposal {
+    /// Digest of the proposed transaction.
+    pub id: String,
+    pub operation: FederationOperation,
+    /// Base64 BCS encoded `TransactionData`, the payload to sign.
+    pub tx_bytes: String,
+    /// Base64 encoded partial signatures.
+    pub signatures: Vec<String>,
+    pub signed_weight: u16,
+    pub threshold: u16,
+    pub created_at: u64,
+    pub executed_digest: Option<String>,
+}
+
+impl Proposal {
+    pub fn tx_data(&self) -> anyhow::Result<TransactionData> {
+        let bytes = Base64::decode(&self.tx_bytes).map_err(|e| anyhow!("{}", e))?;
+        Ok(bcs::from_bytes(&bytes)?)
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.signed_weight >= self.threshold
+    }
+
+    fn save(&self) -> anyhow::Result<()> {
+        write_data(&proposal_file(&self.id), self)
+    }
+}
+
+fn proposal_file(id: &str) -> String {
+    format!("{}/{}.json", PROPOSALS_DIR, id)
+}
+
+fn federation_property(property_name: &str, allowed_values: &[String]) -> FederationProperty {
+    FederationProperty::new(PropertyName::from(property_name)).with_allowed_values(
+        allowed_values
+            .iter()
+            .map(|value| PropertyValue::Text(value.clone())),
+    )
+}
+
+/// Makes the multisig address a root authority of `federation_id`, signed by the single root key.
+pub async fn enroll(federation_id: ObjectID) -> anyhow::Result<IotaAddress> {
+    let multisig_address = MultisigCommittee::from_env()?.address()?;
+    let hierarchies_client = get_hierarchies_client(ROOT_AUTH_ALIAS.to_string()).await?;
+
+    hierarchies_client
+        .add_root_authority(
+            federation_id,
+            ObjectID::from_address(multisig_address.into()),
+        )
+        .build_and_execute(&hierarchies_client)
+        .await
+        .context("Failed to add the multisig root authority")?;
+
+    println!(
+        "✅ Multisig {} is a root authority of {}",
+        multisig_address, federation_id
+    );
+    Ok(multisig_address)
+}
+
+/// Revokes the single `root-auth` key as root authority of `federation_id`,
+/// signed by that key, once the multisig address has been enrolled.
+///
+/// Until then the single key can still administer the federation on its own,
+/// bypassing the committee.
+pub async fn retire_single_key_root(federation_id: ObjectID) -> anyhow::Result<()> {
+    let multisig_id = ObjectID::from_address(MultisigCommittee::from_env()?.address()?.into());
+    let hierarchies_client = get_hierarchies_client(ROOT_AUTH_ALIAS.to_string()).await?;
+
+    // Revoking the single key first would leave the federation without a root
+    let enrolled = time_chain_call(
+        "is_root_authority",
+        hierarchies_client.is_root_authority(federation_id, multisig_id),
+    )
+    .await?;
+    if !enrolled {
+        bail!(
+            "The multisig is not a root authority of {}, enroll it first",
+            federation_id
+        );
+    }
+
+    let root_id = ObjectID::from_address(account_signer(ROOT_AUTH_ALIAS).await?.address().into());
+    hierarchies_client
+        .revoke_root_authority(federation_id, root_id)
+        .build_and_execute(&hierarchies_client)
+        .await
+        .context("Failed to revoke the single-key root authority")?;
+
+    println!(
+        "✅ {} is no longer a root authority of {}",
+        ROOT_AUTH_ALIAS, federation_id
+    );
+    Ok(())
+}
+
+/// Builds the unsigned transaction of `operation` with the multisig address as sender and gas owner.
+pub async fn propose(operation: FederationOperation) -> anyhow::Result<Proposal> {
+    let committee = MultisigCommittee::from_env()?;
+    let multisig_address = committee.address()?;
+    // The client only builds, sender and gas owner are the multisig address
+    let hierarchies_client = HierarchiesClient::new(
+        get_hierarchies_read_only_client().await?,
+        CommitteeStandIn {
+            public_key: committee.public_keys[0].clone(),
+        },
+    )
+    .await?;
+
+    let tx_data = match &operation {
+        FederationOperation::AddProperty {
+            federation_id,
+            property_name,
+            allowed_values,
+        } => {
+            let (tx_data, ..) = hierarchies_client
+                .add_property(
+                    *federation_id,
+                    federation_property(property_name, allowed_values),
+                )
+                .with_sender(multisig_address)
+                .with_gas_owner(multisig_address)
+                .build(&hierarchies_client)
+                .await?;
+            tx_data
+        }
+        FederationOperation::AccreditToAttest {
+            federation_id,
+            receiver,
+            property_name,
+            allowed_values,
+        } => {
+            let (tx_data, ..) = hierarchies_client
+                .create_accreditation_to_attest(
+                    *federation_id,
+                    *receiver,
+                    [federation_property(property_name, allowed_values)],
+                )
+                .with_sender(multisig_address)
+                .with_gas_owner(multisig_address)
+                .build(&hierarchies_client)
+                .await?;
+            tx_data
+        }
+        FederationOperation::AccreditToAccredit {
+            federation_id,
+            receiver,
+            property_name,
+            allowed_values,
+        } => {
+            let (tx_data, ..) = hierarchies_client
+                .create_accreditation_to_accredit(
+                    *federation_id,
+                    *receiver,
+                    [federation_property(property_name, allowed_values)],
+                )
+                .with_sender(multisig_address)
+                .with_gas_owner(multisig_address)
+                .build(&hierarchies_client)
+                .await?;
+            tx_data
+        }
+    };
+
+    let proposal = Proposal {
+        id: tx_data.digest().to_string(),
+        operation,
+        tx_bytes: Base64::encode(bcs::to_bytes(&tx_data)?),
+        signatures: Vec::new(),
+        signed_weight: 0,
+        threshold: committee.threshold,
+        created_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        executed_digest: None,
+    };
+    proposal.save()?;
+    println!("📝 Multisig proposal {} created", proposal.id);
+    Ok(proposal)
+}
+
+pub fn load_proposal(id: &str) -> anyhow::Result<Proposal> {
+    // Ids are transaction digests, anything else cannot be a stored proposal
+    if TransactionDigest::from_str(id).is_err() {
+        bail!("Unknown proposal {}", id);
+    }
+    read_data(&proposal_file(id))?.with_context(|| format!("Unknown proposal {}", id))
+}
+
+/// Stored proposals, oldest first.
+///
+/// Only `<digest>.json` files are proposals, anything else in the folder is
+/// ignored and a proposal that cannot be read is skipped with a warning, so
+/// that one bad file does not hide the others.
+pub fn list_proposals() -> anyhow::Result<Vec<Proposal>> {
+    let dir = data_path(PROPOSALS_DIR);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut proposals = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        match load_proposal(id) {
+            Ok(proposal) => proposals.push(proposal),
+            Err(err) => eprintln!(
+                "⚠️ Skipping unreadable proposal {}: {:#}",
+                path.display(),
+                err
+            ),
+        }
+    }
+    proposals.sort_by_key(|proposal| proposal.created_at);
+    Ok(proposals)
+}
+
+/// Adds a partial signature produced by a committee member, e.g. with
+/// `iota keytool sign --address <member> --data <tx_bytes>`.
+pub async fn add_signature(id: &str, signature: &str) -> anyhow::Result<Proposal> {
+    let committee = MultisigCommittee::from_env()?;
+    let _guard = PROPOSAL_LOCK.lock().await;
+    let mut proposal = load_proposal(id)?;
+    if proposal.executed_digest.is_some() {
+        bail!("Proposal {} has already been executed", id);
+    }
+
+    let bytes = Base64::decode(signature.trim()).map_err(|e| anyhow!("{}", e))?;
+    let signature = Signature::from_bytes(&bytes).map_err(|e| anyhow!("{}", e))?;
+    let public_key = signature.to_public_key()?;
+    let weight = committee
+        .weight_of(&public_key)
+        .context("Signer is not a member of the multisig committee")?;
+
+    let tx_data = proposal.tx_data()?;
+    signature
+        .verify_secure(
+            &IntentMessage::new(Intent::iota_transaction(), tx_data),
+            IotaAddress::from(&public_key),
+            public_key.scheme(),
+        )
+        .map_err(|e| anyhow!("Invalid signature for proposal {}: {}", id, e))?;
+
+    for existing in &proposal.signatures {
+        let existing =
+            Signature::from_bytes(&Base64::decode(existing).map_err(|e| anyhow!("{}", e))?)
+                .map_err(|e| anyhow!("{}", e))?;
+        if existing.to_public_key()? == public_key {
+            bail!("Proposal {} is already signed by this member", id);
+        }
+    }
+
+    proposal.signatures.push(Base64::encode(signature.as_ref()));
+    proposal.signed_weight += weight;
+    proposal.save()?;
+    println!(
+        "✍️ Proposal {} signed ({}/{})",
+        id, proposal.signed_weight, proposal.threshold
+    );
+    Ok(proposal)
+}
+
+/// Signs a proposal with a committee member key held by the configured signer backend.
+pub async fn sign_with_alias(id: &str, alias: &str) -> anyhow::Result<Proposal> {
+    let tx_data = load_proposal(id)?.tx_data()?;
+    let signature = signer_backend()?.sign(alias, &tx_data).await?;
+    add_signature(id, &Base64::encode(signature.as_ref())).await
+}
+
+/// Combines the partial signatures into a multisig and executes the proposal.
+pub async fn execute(id: &str) -> anyhow::Result<Proposal> {
+    let committee = MultisigCommittee::from_env()?;
+    // Held until the digest is saved, so that a proposal is executed once
+    let _guard = PROPOSAL_LOCK.lock().await;
+    let mut proposal = load_proposal(id)?;
+    if proposal.executed_digest.is_some() {
+        bail!("Proposal {} has already been executed", id);
+    }
+    if !proposal.is_ready() {
+        bail!(
+            "Proposal {} needs weight {}, only {} signed",
+            id,
+            proposal.threshold,
+            proposal.signed_weight
+        );
+    }
+
+    let signatures = proposal
+        .signatures
+        .iter()
+        .map(|signature| {
+            let bytes = Base64::decode(signature).map_err(|e| anyhow!("{}", e))?;
+            Ok(GenericSignature::Signature(
+                Signature::from_bytes(&bytes).map_err(|e| anyhow!("{}", e))?,
+            ))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let multisig = MultiSig::combine(signatures, committee.multisig_public_key()?)
+        .map_err(|e| anyhow!("Error combining signatures: {}", e))?;
+
+    let client = iota_client().await?;
+    let response = time_chain_call(
+        "execute_transaction_block",
+        client.quorum_driver_api().execute_transaction_block(
+            Transaction::from_generic_sig_data(
+                proposal.tx_data()?,
+                vec![GenericSignature::MultiSig(multisig)],
+            ),
+            IotaTransactionBlockResponseOptions::full_content(),
+            ExecuteTransactionRequestType::WaitForLocalExecution,
+        ),
+    )
+    .await?;
+
+    if let Some(effects) = &response.effects {
+        if let IotaExecutionStatus::Failure { error } = effects.status() {
+            bail!("Proposal {} failed on chain: {}", id, error);
+        }
+    }
+
+    proposal.executed_digest = Some(response.digest.to_string());
+    proposal.save()?;
+    println!("✅ Proposal {} executed: {}", id, response.digest);
+    Ok(proposal)
+}
@@ -0,0 +1,29 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Context;
+use iota_sdk::{IotaClient, IotaClientBuilder, IOTA_TESTNET_URL};
+
+const TESTNET_FAUCET_URL: &str = "https://faucet.testnet.iota.cafe/gas";
+
+/// JSON-RPC endpoint of the IOTA node, `API_ENDPOINT` or the testnet.
+pub fn rpc_url() -> String {
+    std::env::var("API_ENDPOINT").unwrap_or_else(|_| IOTA_TESTNET_URL.to_string())
+}
+
+/// Faucet of the network, `FAUCET_URL` or the testnet faucet.
+pub fn faucet_url() -> String {
+    std::env::var("FAUCET_URL").unwrap_or_else(|_| TESTNET_FAUCET_URL.to_string())
+}
+
+/// Client of the node at [`rpc_url`].
+///
+/// Every client of the backend is built here, so that pointing
+/// `API_ENDPOINT` at another node, e.g. a mock in tests, covers all of them.
+pub async fn iota_client() -> anyhow::Result<IotaClient> {
+    let url = rpc_url();
+    IotaClientBuilder::default()
+        .build(&url)
+        .await
+        .with_context(|| format!("Error connecting to IOTA node {}", url))
+}
@@ -0,0 +1,332 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+
+use anyhow::{bail, Context};
+use iota_json_rpc_types::{IotaExecutionStatus, IotaTransactionBlockEffectsAPI};
+use iota_sdk::{
+    rpc_types::{IotaEvent, IotaObjectDataFilter, IotaObjectDataOptions, IotaObjectResponseQuery},
+    types::{
+        base_types::{IotaAddress, ObjectID},
+        programmable_transaction_builder::ProgrammableTransactionBuilder,
+        transaction::TransactionKind,
+    },
+    IotaClient,
+};
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::StructTag;
+use serde::Serialize;
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use crate::events::{contract_events, package_id};
+use crate::identity::TEST_GAS_BUDGET;
+use crate::keystore::ROOT_AUTH_ALIAS;
+use crate::lcc::object_arg;
+use crate::metrics::time_chain_call;
+use crate::products::move_fields;
+use crate::signer::AccountSigner;
+use crate::transaction::sign_and_execute_transaction;
+
+pub const NFT_REWARD_MODULE: &str = "nft_reward";
+/// `authorize_address` calls per transaction, each one scans the growing whitelist.
+const AUTHORIZATIONS_PER_TRANSACTION: usize = 50;
+
+/// Objects of the published `nft_reward` module.
+///
+/// Read from `IOTA_CUSTOM_NOTARIZATION_PKG_ID`, `REWARD_WHITELIST_ID` and `REWARD_ADMIN_CAP_ID`.
+/// The cap is held by `REWARD_ADMIN_ALIAS` (default: `root-auth`).
+#[derive(Clone, Debug)]
+pub struct NftRewardConfig {
+    pub package_id: ObjectID,
+    pub whitelist_id: ObjectID,
+    pub admin_cap_id: Option<ObjectID>,
+    pub admin_alias: String,
+}
+
+impl NftRewardConfig {
+    pub fn from_env() -> anyhow::Result<Self> {
+        let object_id = |name: &str| -> anyhow::Result<Option<ObjectID>> {
+            std::env::var(name)
+                .ok()
+                .filter(|value| !value.is_empty())
+                .map(|value| {
+                    ObjectID::from_hex_literal(&value).with_context(|| format!("Invalid {}", name))
+                })
+                .transpose()
+        };
+        Ok(Self {
+            package_id: package_id()?,
+            whitelist_id: object_id("REWARD_WHITELIST_ID")?
+                .context("REWARD_WHITELIST_ID must be set")?,
+            admin_cap_id: object_id("REWARD_ADMIN_CAP_ID")?,
+            admin_alias: std::env::var("REWARD_ADMIN_ALIAS")
+                .ok()
+                .filter(|alias| !alias.is_empty())
+                .unwrap_or_else(|| ROOT_AUTH_ALIAS.to_string()),
+        })
+    }
+
+    fn admin_cap_id(&self) -> anyhow::Result<ObjectID> {
+        self.admin_cap_id.context("REWARD_ADMIN_CAP_ID must be set")
+    }
+}
+
+/// Outcome of authorizing a batch of addresses.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AuthorizationResult {
+    #[schema(value_type = Vec<String>)]
+    pub authorized: Vec<IotaAddress>,
+    /// Addresses that were on the whitelist already and were skipped.
+    #[schema(value_type = Vec<String>)]
+    pub already_authorized: Vec<IotaAddress>,
+    pub digests: Vec<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AuthorizedAddress {
+    #[schema(value_type = String)]
+    pub account: IotaAddress,
+    pub authorized_at: Option<u64>,
+    pub digest: String,
+    /// Whether the address has already minted its reward NFT.
+    pub has_minted: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RewardNft {
+    #[schema(value_type = String)]
+    pub object_id: ObjectID,
+    pub name: String,
+    pub minted_at: Option<u64>,
+    pub digest: String,
+    /// Current owner, the address the NFTs were listed for.
+    #[schema(value_type = Option<String>)]
+    pub owner: Option<IotaAddress>,
+}
+
+/// Adds `addresses` to the reward whitelist, the signer must own the `REWARD_ADMIN_CAP`.
+///
+/// `nft_reward::authorize_address` aborts for addresses already on the
+/// whitelist, so those are skipped. The rest is authorized in batches of
+/// `AUTHORIZATIONS_PER_TRANSACTION` calls per transaction.
+pub async fn authorize_addresses(
+    client: &IotaClient,
+    signer: &AccountSigner,
+    addresses: &[IotaAddress],
+    gas: Option<ObjectID>,
+) -> anyhow::Result<AuthorizationResult> {
+    let config = NftRewardConfig::from_env()?;
+    let whitelist = read_whitelist(client, &config).await?;
+
+    let mut pending = Vec::new();
+    let mut already_authorized = Vec::new();
+    for address in addresses.iter().copied().collect::<BTreeSet<_>>() {
+        if whitelist.contains_key(&address) {
+            already_authorized.push(address);
+        } else {
+            pending.push(address);
+        }
+    }
+
+    let mut digests = Vec::new();
+    for batch in pending.chunks(AUTHORIZATIONS_PER_TRANSACTION) {
+        // Owned and shared object versions change with every batch
+        let (cap, owner) = object_arg(client, config.admin_cap_id()?, false).await?;
+        if owner != signer.address() {
+            bail!("REWARD_ADMIN_CAP is owned by {}, not the signer", owner);
+        }
+        let (whitelist, _) = object_arg(client, config.whitelist_id, true).await?;
+
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let cap = ptb.obj(cap)?;
+        let whitelist = ptb.obj(whitelist)?;
+        for address in batch {
+            let recipient = ptb.pure(*address)?;
+            ptb.programmable_move_call(
+                config.package_id,
+                Identifier::from_str(NFT_REWARD_MODULE)?,
+                Identifier::from_str("authorize_address")?,
+                vec![],
+                vec![cap, whitelist, recipient],
+            );
+        }
+
+        let gas_price = time_chain_call(
+            "get_reference_gas_price",
+            client.read_api().get_reference_gas_price(),
+        )
+        .await?;
+        let tx_data = client
+            .transaction_builder()
+            .tx_data(
+                signer.address(),
+                TransactionKind::programmable(ptb.finish()),
+                TEST_GAS_BUDGET,
+                gas_price,
+                vec![],
+                gas,
+            )
+            .await?;
+        let response = sign_and_execute_transaction(client, signer, tx_data).await?;
+        if let Some(effects) = &response.effects {
+            if let IotaExecutionStatus::Failure { error } = effects.status() {
+                bail!(
+                    "{}::authorize_address failed on chain: {}",
+                    NFT_REWARD_MODULE,
+                    error
+                );
+            }
+        }
+        digests.push(response.digest.to_string());
+    }
+
+    Ok(AuthorizationResult {
+        authorized: pending,
+        already_authorized,
+        digests,
+    })
+}
+
+/// Addresses authorized through `AddressAuthorized` events, with their mint status.
+pub async fn list_authorized(client: &IotaClient) -> anyhow::Result<Vec<AuthorizedAddress>> {
+    let config = NftRewardConfig::from_env()?;
+    let whitelist = read_whitelist(client, &config).await?;
+    let events =
+        contract_events(client, &format!("{}::AddressAuthorized", NFT_REWARD_MODULE)).await?;
+
+    Ok(events
+        .into_iter()
+        .filter_map(|event| {
+            let account = event_address(&event, "account")?;
+            Some(AuthorizedAddress {
+                account,
+                authorized_at: event.timestamp_ms,
+                digest: event.id.tx_digest.to_string(),
+                has_minted: whitelist.get(&account).copied().unwrap_or(false),
+            })
+        })
+        .collect())
+}
+
+/// Reward NFTs currently owned by `address`.
+///
+/// Mint time and digest come from the `NFTMinted` events, NFTs received by
+/// transfer are listed as well.
+pub async fn reward_nfts(
+    client: &IotaClient,
+    address: IotaAddress,
+) -> anyhow::Result<Vec<RewardNft>> {
+    let nft_type = StructTag::from_str(&format!(
+        "{}::{}::RewardNFT",
+        package_id()?,
+        NFT_REWARD_MODULE
+    ))?;
+    let query = IotaObjectResponseQuery::new(
+        Some(IotaObjectDataFilter::StructType(nft_type)),
+        Some(IotaObjectDataOptions::new().with_content()),
+    );
+
+    let mut owned = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page = time_chain_call(
+            "get_owned_objects",
+            client
+                .read_api()
+                .get_owned_objects(address, Some(query.clone()), cursor, None),
+        )
+        .await?;
+        owned.extend(page.data);
+        if page.next_cursor.is_some() {
+            cursor = page.next_cursor;
+        }
+        if !page.has_next_page {
+            break;
+        }
+    }
+    if owned.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let minted: BTreeMap<ObjectID, IotaEvent> =
+        contract_events(client, &format!("{}::NFTMinted", NFT_REWARD_MODULE))
+            .await?
+            .into_iter()
+            .filter_map(|event| {
+                let object_id = event.parsed_json["object_id"]
+                    .as_str()
+                    .and_then(|id| ObjectID::from_hex_literal(id).ok())?;
+                Some((object_id, event))
+            })
+            .collect();
+
+    Ok(owned
+        .iter()
+        .filter_map(|response| {
+            let object_id = response.data.as_ref()?.object_id;
+            let event = minted.get(&object_id);
+            Some(RewardNft {
+                object_id,
+                name: move_fields(response)
+                    .ok()
+                    .and_then(|fields| fields["name"].as_str().map(str::to_string))
+                    .unwrap_or_default(),
+                minted_at: event.and_then(|event| event.timestamp_ms),
+                digest: event
+                    .map(|event| event.id.tx_digest.to_string())
+                    .unwrap_or_default(),
+                owner: Some(address),
+            })
+        })
+        .collect())
+}
+
+/// The `hasMinted` map of the whitelist.
+async fn read_whitelist(
+    client: &IotaClient,
+    config: &NftRewardConfig,
+) -> anyhow::Result<BTreeMap<IotaAddress, bool>> {
+    let response = time_chain_call(
+        "get_object",
+        client.read_api().get_object_with_options(
+            config.whitelist_id,
+            IotaObjectDataOptions::new().with_content(),
+        ),
+    )
+    .await?;
+    let fields = move_fields(&response)
+        .with_context(|| format!("Whitelist {} not found", config.whitelist_id))?;
+
+    // VecMap is rendered as { contents: [{ key, value }] }, possibly wrapped in "fields"
+    let map = &fields["hasMinted"];
+    let contents = map["contents"]
+        .as_array()
+        .or_else(|| map["fields"]["contents"].as_array())
+        .context("Unexpected whitelist layout")?;
+    contents
+        .iter()
+        .map(|entry| {
+            let entry = if entry["fields"].is_object() {
+                &entry["fields"]
+            } else {
+                entry
+            };
+            let key = entry["key"]
+                .as_str()
+                .context("Whitelist entry without key")?;
+            Ok((
+                IotaAddress::from_str(key)?,
+                entry["value"] == Value::Bool(true),
+            ))
+        })
+        .collect()
+}
+
+fn event_address(event: &IotaEvent, field: &str) -> Option<IotaAddress> {
+    event.parsed_json[field]
+        .as_str()
+        .and_then(|address| IotaAddress::from_str(address).ok())
+}
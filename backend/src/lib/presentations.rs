@@ -0,0 +1,295 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, bail, Context};
+use identity_iota::verification::jws::Decoder;
+use iota_sdk::types::base_types::ObjectID;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::Mutex;
+use utoipa::ToSchema;
+
+use crate::credentials::AUTHORIZED_REPAIRER_CREDENTIAL;
+use crate::data::{read_data, write_data};
+use crate::verification::{
+    decode_jwt, verify_jwt, AccreditationCheck, Check, CheckStatus, CredentialReport, DecodedJwt,
+    JwtKind, ValidationReport,
+};
+
+pub const CHALLENGES_FILE: &str = "challenges.json";
+/// Seconds a holder has to present against a challenge.
+pub const CHALLENGE_VALIDITY_SECS: u64 = 300;
+
+/// Serialises reads and writes of the challenge file, so that a nonce is consumed once.
+static CHALLENGE_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// Nonce and audience a holder has to sign into a presentation.
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct Challenge {
+    pub nonce: String,
+    /// Verifier the presentation is meant for, expected in the `aud` claim.
+    pub audience: String,
+    pub issued_at: u64,
+    pub expires_at: u64,
+    #[serde(default)]
+    pub used: bool,
+}
+
+/// Result of a role presentation check.
+#[derive(Serialize, Debug, ToSchema)]
+pub struct RolePresentationReport {
+    pub valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub holder: Option<String>,
+    #[schema(value_type = String)]
+    pub federation_id: ObjectID,
+    /// Id of the credential proving the role.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credential_id: Option<String>,
+    pub checks: Vec<Check>,
+    /// Full validation report of the presentation and its credentials.
+    pub presentation: ValidationReport,
+}
+
+fn now() -> anyhow::Result<u64> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs())
+}
+
+/// Issues a single use challenge for a presentation to `audience`.
+pub async fn create_challenge(audience: &str) -> anyhow::Result<Challenge> {
+    if audience.trim().is_empty() {
+        bail!("Audience must not be empty");
+    }
+    let issued_at = now()?;
+    let challenge = Challenge {
+        nonce: uuid::Uuid::new_v4().simple().to_string(),
+        audience: audience.trim().to_string(),
+        issued_at,
+        expires_at: issued_at + CHALLENGE_VALIDITY_SECS,
+        used: false,
+    };
+
+    let _guard = CHALLENGE_LOCK.lock().await;
+    let mut challenges: Vec<Challenge> = read_data(CHALLENGES_FILE)?.unwrap_or_default();
+    // Expired nonces are rejected whether they are known or not
+    challenges.retain(|existing| existing.expires_at > issued_at);
+    challenges.push(challenge.clone());
+    write_data(CHALLENGES_FILE, &challenges)?;
+    Ok(challenge)
+}
+
+/// The open challenge of `nonce`, or why it cannot be answered.
+fn open_challenge<'a>(
+    challenges: &'a mut [Challenge],
+    nonce: &str,
+    now: u64,
+) -> Result<&'a mut Challenge, String> {
+    let challenge = challenges
+        .iter_mut()
+        .find(|challenge| challenge.nonce == nonce)
+        .ok_or_else(|| format!("Unknown nonce {}", nonce))?;
+    if challenge.used {
+        return Err(format!("Nonce {} was already used", nonce));
+    }
+    if challenge.expires_at <= now {
+        return Err("Challenge expired".to_string());
+    }
+    Ok(challenge)
+}
+
+/// Reads the challenge of `nonce` without consuming it.
+///
+/// The inner error is the reason the challenge is rejected, the outer one a
+/// failure to read the challenge file.
+pub async fn find_challenge(nonce: &str) -> anyhow::Result<Result<Challenge, String>> {
+    let _guard = CHALLENGE_LOCK.lock().await;
+    let mut challenges: Vec<Challenge> = read_data(CHALLENGES_FILE)?.unwrap_or_default();
+    Ok(open_challenge(&mut challenges, nonce, now()?).map(|challenge| challenge.clone()))
+}
+
+/// Marks the challenge of `nonce` as used, rejecting it like [`find_challenge`]
+/// if it is unknown, used or expired by now.
+pub async fn consume_challenge(nonce: &str) -> anyhow::Result<Result<Challenge, String>> {
+    let _guard = CHALLENGE_LOCK.lock().await;
+    let mut challenges: Vec<Challenge> = read_data(CHALLENGES_FILE)?.unwrap_or_default();
+    let consumed = match open_challenge(&mut challenges, nonce, now()?) {
+        Ok(challenge) => {
+            challenge.used = true;
+            challenge.clone()
+        }
+        Err(reason) => return Ok(Err(reason)),
+    };
+    write_data(CHALLENGES_FILE, &challenges)?;
+    Ok(Ok(consumed))
+}
+
+/// Decodes a VP JWT, failing on malformed input and on credentials.
+pub fn decode_presentation(jwt: &str) -> anyhow::Result<DecodedJwt> {
+    let decoded = decode_jwt(jwt)?;
+    if decoded.kind != JwtKind::Presentation {
+        bail!("JWT is not a presentation");
+    }
+    Ok(decoded)
+}
+
+/// Nonce of a presentation JWT, from its protected header or else its claims.
+fn presentation_nonce(jwt: &str, claims: &Value) -> anyhow::Result<Option<String>> {
+    let token = Decoder::new()
+        .decode_compact_serialization(jwt.as_bytes(), None)
+        .map_err(|e| anyhow!("Malformed JWT: {}", e))?;
+    let header_nonce = token
+        .protected_header()
+        .and_then(|header| header.nonce())
+        .map(str::to_string);
+    Ok(header_nonce.or_else(|| claims["nonce"].as_str().map(str::to_string)))
+}
+
+/// Verifies that the holder of a presentation is an authorized repairer of `federation_id`.
+///
+/// The presentation must answer a challenge of this backend, be signed by the
+/// holder DID and contain a valid `AuthorizedRepairer` credential issued to the
+/// holder by the manufacturer DID, which must still be accredited in the federation.
+///
+/// Failed checks are part of the report, an error means the checks could not
+/// be run. The challenge is only consumed by a presentation that passed every
+/// other check, so a failed attempt can be retried with the same nonce.
+pub async fn verify_role_presentation(
+    decoded: &DecodedJwt,
+    federation_id: ObjectID,
+) -> anyhow::Result<RolePresentationReport> {
+    let claims = decoded.claims();
+    let mut checks = Vec::new();
+
+    let nonce = presentation_nonce(decoded.jwt(), claims)
+        .and_then(|nonce| nonce.context("Presentation has no nonce"));
+    let mut challenge = match &nonce {
+        Ok(nonce) => find_challenge(nonce).await?,
+        Err(err) => Err(format!("{:#}", err)),
+    };
+    checks.push(match &challenge {
+        Ok(challenge) if audiences(claims).contains(&challenge.audience) => {
+            Check::new("audience", CheckStatus::Passed, None)
+        }
+        Ok(challenge) => Check::new(
+            "audience",
+            CheckStatus::Failed,
+            Some(format!(
+                "Presentation is not addressed to {}",
+                challenge.audience
+            )),
+        ),
+        Err(_) => Check::new("audience", CheckStatus::Skipped, None),
+    });
+
+    let presentation = verify_jwt(
+        decoded,
+        Some(&AccreditationCheck {
+            federation_id,
+            issuer_address: None,
+        }),
+    )
+    .await?;
+
+    let holder_failures: Vec<String> = presentation
+        .checks
+        .iter()
+        .filter(|check| check.status == CheckStatus::Failed)
+        .map(|check| match &check.detail {
+            Some(detail) => format!("{}: {}", check.name, detail),
+            None => check.name.to_string(),
+        })
+        .collect();
+    checks.push(if holder_failures.is_empty() {
+        Check::new("holder_signature", CheckStatus::Passed, None)
+    } else {
+        Check::new(
+            "holder_signature",
+            CheckStatus::Failed,
+            Some(holder_failures.join("; ")),
+        )
+    });
+
+    let credential = presentation.holder.as_deref().and_then(|holder| {
+        presentation
+            .credentials
+            .iter()
+            .find(|credential| is_role_credential(credential, holder, federation_id))
+    });
+    let credential_id = credential.and_then(|credential| credential.id.clone());
+    checks.push(match credential {
+        Some(_) => Check::new("role_credential", CheckStatus::Passed, None),
+        None => Check::new(
+            "role_credential",
+            CheckStatus::Failed,
+            Some(format!(
+                "No valid {} credential of the holder for federation {}",
+                AUTHORIZED_REPAIRER_CREDENTIAL, federation_id
+            )),
+        ),
+    });
+
+    if let (Ok(nonce), Ok(_)) = (&nonce, &challenge) {
+        if checks
+            .iter()
+            .all(|check| check.status == CheckStatus::Passed)
+        {
+            // Consumed under the lock, a concurrent replay of the same presentation fails here
+            challenge = consume_challenge(nonce).await?;
+        }
+    }
+    checks.insert(
+        0,
+        match challenge {
+            Ok(_) => Check::new("challenge", CheckStatus::Passed, None),
+            Err(reason) => Check::new("challenge", CheckStatus::Failed, Some(reason)),
+        },
+    );
+
+    Ok(RolePresentationReport {
+        valid: checks
+            .iter()
+            .all(|check| check.status == CheckStatus::Passed),
+        holder: presentation.holder.clone(),
+        federation_id,
+        credential_id,
+        checks,
+        presentation,
+    })
+}
+
+/// Values of the `aud` claim, which is either a string or an array.
+fn audiences(claims: &Value) -> Vec<String> {
+    match &claims["aud"] {
+        Value::String(audience) => vec![audience.clone()],
+        Value::Array(audiences) => audiences
+            .iter()
+            .filter_map(|audience| audience.as_str().map(str::to_string))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// A valid repairer credential of the manufacturer, issued to `holder` for `federation_id`.
+fn is_role_credential(
+    credential: &CredentialReport,
+    holder: &str,
+    federation_id: ObjectID,
+) -> bool {
+    let manufacturer_did = std::env::var("MANUFACTURER_DID").ok();
+    let subject = credential.subject.as_ref().unwrap_or(&Value::Null);
+    credential.valid
+        && credential.issuer.is_some()
+        && credential.issuer == manufacturer_did
+        && credential
+            .types
+            .iter()
+            .any(|credential_type| credential_type == AUTHORIZED_REPAIRER_CREDENTIAL)
+        && subject["id"].as_str() == Some(holder)
+        && subject["role"].as_str() == Some("repairer")
+        && subject["federation"]
+            .as_str()
+            .and_then(|id| ObjectID::from_hex_literal(id).ok())
+            == Some(federation_id)
+}
@@ -0,0 +1,393 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use fastcrypto::hash::{Blake2b256, HashFunction};
+use hierarchies::core::types::{property_name::PropertyName, property_value::PropertyValue};
+use identity_iota::iota::{IotaDID, IotaDocument};
+use iota_sdk::{
+    rpc_types::{
+        IotaObjectDataFilter, IotaObjectDataOptions, IotaObjectResponse, IotaObjectResponseQuery,
+        IotaParsedData, IotaTransactionBlockDataAPI, IotaTransactionBlockResponseOptions,
+        ObjectChange,
+    },
+    types::base_types::{IotaAddress, ObjectID},
+    IotaClient,
+};
+use move_core_types::language_storage::StructTag;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use crate::did_resolution::CachedResolver;
+use crate::domain_linkage::{verify_domain_linkage, ConfigurationSource};
+use crate::keystore::get_hierarchies_read_only_client;
+use crate::metrics::time_chain_call;
+use crate::verification::{Check, CheckStatus};
+
+/// Signature scheme flags prefixed to a public key when deriving an address.
+const ED25519_FLAG: u8 = 0x00;
+const SECP256R1_FLAG: u8 = 0x02;
+
+/// A `Product` object of the `audit_trails::app` module.
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct Product {
+    #[schema(value_type = String)]
+    pub id: ObjectID,
+    #[schema(value_type = String)]
+    pub federation_id: ObjectID,
+    pub name: String,
+    pub serial_number: String,
+    /// DID passed to `new_product`, not checked on chain.
+    pub manufacturer: String,
+    /// Sender of the transaction that created the product.
+    #[schema(value_type = String)]
+    pub creator: IotaAddress,
+}
+
+/// Fields of the Move object in `response`.
+pub fn move_fields(response: &IotaObjectResponse) -> anyhow::Result<Value> {
+    match response
+        .data
+        .as_ref()
+        .and_then(|data| data.content.as_ref())
+    {
+        Some(IotaParsedData::MoveObject(object)) => Ok(object.fields.clone().to_json_value()),
+        _ => bail!("Object has no Move content"),
+    }
+}
+
+/// Reads a product and the address that created it, `None` if no object
+/// `product_id` exists.
+pub async fn fetch_product(
+    client: &IotaClient,
+    product_id: ObjectID,
+) -> anyhow::Result<Option<Product>> {
+    let response = time_chain_call(
+        "get_object",
+        client.read_api().get_object_with_options(
+            product_id,
+            IotaObjectDataOptions::new()
+                .with_content()
+                .with_previous_transaction(),
+        ),
+    )
+    .await?;
+    if response.data.is_none() {
+        return Ok(None);
+    }
+    let fields =
+        move_fields(&response).with_context(|| format!("{} is not a product", product_id))?;
+    let field = |name: &str| -> anyhow::Result<String> {
+        fields[name]
+            .as_str()
+            .map(str::to_string)
+            .with_context(|| format!("Product field {} missing", name))
+    };
+
+    // Products are shared and only ever borrowed immutably, so their previous
+    // transaction is the one that created them
+    let digest = response
+        .data
+        .as_ref()
+        .and_then(|data| data.previous_transaction)
+        .context("Product has no previous transaction")?;
+    let transaction = time_chain_call(
+        "get_transaction_block",
+        client.read_api().get_transaction_with_options(
+            digest,
+            IotaTransactionBlockResponseOptions::new()
+                .with_input()
+                .with_object_changes(),
+        ),
+    )
+    .await?;
+    let created = transaction
+        .object_changes
+        .unwrap_or_default()
+        .iter()
+        .any(|change| {
+            matches!(change, ObjectChange::Created { object_id, .. } if *object_id == product_id)
+        });
+    if !created {
+        bail!(
+            "Transaction {} did not create product {}",
+            digest,
+            product_id
+        );
+    }
+    let creator = *transaction
+        .transaction
+        .context("Creation transaction has no input")?
+        .data
+        .sender();
+
+    Ok(Some(Product {
+        id: product_id,
+        federation_id: ObjectID::from_hex_literal(&field("federation_addr")?)?,
+        name: field("name")?,
+        serial_number: field("serial_number")?,
+        manufacturer: field("manufacturer")?,
+        creator,
+    }))
+}
+
+/// Reward type of a product, `LCC`, `NFT` or `None`.
+pub async fn fetch_reward_type(
+    client: &IotaClient,
+    product_id: ObjectID,
+) -> anyhow::Result<String> {
+    let response = time_chain_call(
+        "get_object",
+        client
+            .read_api()
+            .get_object_with_options(product_id, IotaObjectDataOptions::new().with_content()),
+    )
+    .await?;
+    let fields =
+        move_fields(&response).with_context(|| format!("Product {} not found", product_id))?;
+    // Enum values are rendered either as the variant name or as { "variant": ... }
+    let reward_type = match &fields["reward_type"] {
+        Value::String(variant) => Some(variant.as_str()),
+        value => value["variant"].as_str(),
+    };
+    reward_type
+        .map(str::to_string)
+        .with_context(|| format!("Product {} has no reward type", product_id))
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TrustStatus {
+    /// Every check passed.
+    Trusted,
+    Untrusted,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct ProductTrust {
+    pub product: Product,
+    pub status: TrustStatus,
+    pub checks: Vec<Check>,
+}
+
+/// Checks that the manufacturer DID of a product belongs to the address that
+/// created it and that the DID's linked domains validate, `None` if the
+/// product does not exist.
+pub async fn verify_product(
+    client: &IotaClient,
+    resolver: &CachedResolver,
+    product_id: ObjectID,
+) -> anyhow::Result<Option<ProductTrust>> {
+    let Some(product) = fetch_product(client, product_id).await? else {
+        return Ok(None);
+    };
+    let mut checks = vec![creator_accreditation_check(&product).await];
+
+    let resolution = resolver.resolve(&product.manufacturer).await;
+    match resolution.document {
+        Some(document) => {
+            checks.push(Check::new("did_resolution", CheckStatus::Passed, None));
+            checks.push(did_ownership_check(client, &document, product.creator).await);
+            checks.push(domain_linkage_check(&product.manufacturer).await);
+        }
+        None => {
+            checks.push(Check::new(
+                "did_resolution",
+                CheckStatus::Failed,
+                resolution.did_resolution_metadata["errorMessage"]
+                    .as_str()
+                    .map(str::to_string),
+            ));
+            checks.push(Check::new("did_ownership", CheckStatus::Skipped, None));
+            checks.push(Check::new("domain_linkage", CheckStatus::Skipped, None));
+        }
+    }
+
+    let status = if checks
+        .iter()
+        .all(|check| check.status == CheckStatus::Passed)
+    {
+        TrustStatus::Trusted
+    } else {
+        TrustStatus::Untrusted
+    };
+    Ok(Some(ProductTrust {
+        product,
+        status,
+        checks,
+    }))
+}
+
+/// The creator must still hold the manufacturer role in the product federation.
+async fn creator_accreditation_check(product: &Product) -> Check {
+    let result = async {
+        let hierarchies_client = get_hierarchies_read_only_client().await?;
+        time_chain_call(
+            "validate_property",
+            hierarchies_client.validate_property(
+                product.federation_id,
+                ObjectID::from_address(product.creator.into()),
+                PropertyName::from("role"),
+                PropertyValue::Text("manufacturer".to_string()),
+            ),
+        )
+        .await
+        .map_err(|e| anyhow!("{}", e))
+    }
+    .await;
+
+    match result {
+        Ok(true) => Check::new("creator_accreditation", CheckStatus::Passed, None),
+        Ok(false) => Check::new(
+            "creator_accreditation",
+            CheckStatus::Failed,
+            Some(format!(
+                "{} is no longer accredited as manufacturer",
+                product.creator
+            )),
+        ),
+        Err(err) => Check::new(
+            "creator_accreditation",
+            CheckStatus::Failed,
+            Some(format!("{:#}", err)),
+        ),
+    }
+}
+
+async fn did_ownership_check(
+    client: &IotaClient,
+    document: &IotaDocument,
+    creator: IotaAddress,
+) -> Check {
+    if method_addresses(document).contains(&creator) {
+        return Check::new(
+            "did_ownership",
+            CheckStatus::Passed,
+            Some("A verification method key belongs to the creator".to_string()),
+        );
+    }
+    match controls_identity(client, document.id(), creator).await {
+        Ok(true) => Check::new(
+            "did_ownership",
+            CheckStatus::Passed,
+            Some("The creator controls the identity".to_string()),
+        ),
+        Ok(false) => Check::new(
+            "did_ownership",
+            CheckStatus::Failed,
+            Some(format!(
+                "{} neither controls nor holds a key of the DID",
+                creator
+            )),
+        ),
+        Err(err) => Check::new(
+            "did_ownership",
+            CheckStatus::Failed,
+            Some(format!("{:#}", err)),
+        ),
+    }
+}
+
+/// Addresses derived from the Ed25519 and P-256 verification methods of `document`.
+pub(crate) fn method_addresses(document: &IotaDocument) -> Vec<IotaAddress> {
+    document
+        .methods(None)
+        .iter()
+        .filter_map(|method| method.data().public_key_jwk())
+        .filter_map(|jwk| {
+            let (flag, public_key) = if let Ok(params) = jwk.try_okp_params() {
+                if params.crv != "Ed25519" {
+                    return None;
+                }
+                (ED25519_FLAG, URL_SAFE_NO_PAD.decode(&params.x).ok()?)
+            } else if let Ok(params) = jwk.try_ec_params() {
+                if params.crv != "P-256" {
+                    return None;
+                }
+                // Compressed point: parity of y followed by x
+                let x = URL_SAFE_NO_PAD.decode(&params.x).ok()?;
+                let y = URL_SAFE_NO_PAD.decode(&params.y).ok()?;
+                let mut compressed = vec![0x02 | (y.last()? & 1)];
+                compressed.extend(x);
+                (SECP256R1_FLAG, compressed)
+            } else {
+                return None;
+            };
+
+            let mut hasher = Blake2b256::default();
+            hasher.update([flag]);
+            hasher.update(&public_key);
+            IotaAddress::from_bytes(hasher.finalize().digest).ok()
+        })
+        .collect()
+}
+
+/// Whether `address` owns a controller capability of the identity behind `did`.
+pub(crate) async fn controls_identity(
+    client: &IotaClient,
+    did: &IotaDID,
+    address: IotaAddress,
+) -> anyhow::Result<bool> {
+    let identity_pkg_id =
+        std::env::var("IOTA_IDENTITY_PKG_ID").context("IOTA_IDENTITY_PKG_ID must be set")?;
+    let cap_type = StructTag::from_str(&format!("{}::controller::ControllerCap", identity_pkg_id))?;
+    let identity_id = ObjectID::from_hex_literal(did.tag_str())?;
+
+    let caps = time_chain_call(
+        "get_owned_objects",
+        client.read_api().get_owned_objects(
+            address,
+            Some(IotaObjectResponseQuery::new(
+                Some(IotaObjectDataFilter::StructType(cap_type)),
+                Some(IotaObjectDataOptions::new().with_content()),
+            )),
+            None,
+            None,
+        ),
+    )
+    .await?
+    .data;
+
+    Ok(caps.iter().any(|cap| {
+        move_fields(cap)
+            .ok()
+            .and_then(|fields| fields["controller_of"].as_str().map(str::to_string))
+            .and_then(|id| ObjectID::from_hex_literal(&id).ok())
+            == Some(identity_id)
+    }))
+}
+
+async fn domain_linkage_check(did: &str) -> Check {
+    match verify_domain_linkage(did, &[], &ConfigurationSource::Domain).await {
+        Ok(report) if report.valid => Check::new("domain_linkage", CheckStatus::Passed, None),
+        Ok(report) if report.domains.is_empty() => Check::new(
+            "domain_linkage",
+            CheckStatus::Failed,
+            Some("The DID has no linked domain".to_string()),
+        ),
+        Ok(report) => Check::new(
+            "domain_linkage",
+            CheckStatus::Failed,
+            Some(format!(
+                "Linkage failed for {}",
+                report
+                    .domains
+                    .iter()
+                    .filter(|domain| !domain.valid)
+                    .map(|domain| domain.domain.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        ),
+        Err(err) => Check::new(
+            "domain_linkage",
+            CheckStatus::Failed,
+            Some(format!("{:#}", err)),
+        ),
+    }
+}
@@ -0,0 +1,258 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write;
+
+use anyhow::Context;
+use identity_iota::core::Timestamp;
+use iota_sdk::{
+    rpc_types::{IotaEvent, IotaTransactionBlockResponseOptions},
+    types::{
+        base_types::{IotaAddress, ObjectID},
+        event::EventID,
+        object::Owner,
+    },
+    IotaClient,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use crate::data::{read_data, write_data};
+use crate::events::contract_events_since;
+use crate::lcc::{LccConfig, REWARD_VALUE};
+use crate::metrics::time_chain_call;
+use crate::nft_rewards::NFT_REWARD_MODULE;
+use crate::products::fetch_reward_type;
+
+pub const LEDGER_FILE: &str = "reward_ledger.json";
+
+static LEDGER_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, ToSchema)]
+pub enum RewardKind {
+    #[serde(rename = "LCC")]
+    Lcc,
+    #[serde(rename = "NFT")]
+    Nft,
+}
+
+/// A reward paid by `log_entry_data`.
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
+pub struct RewardPayout {
+    pub digest: String,
+    #[schema(value_type = String)]
+    pub product: ObjectID,
+    /// `ProductEntry` the reward was paid for.
+    #[schema(value_type = Option<String>)]
+    pub entry: Option<ObjectID>,
+    #[schema(value_type = String)]
+    pub recipient: IotaAddress,
+    pub timestamp_ms: Option<u64>,
+    pub reward_type: RewardKind,
+    /// LCC base units, for LCC rewards.
+    pub amount: Option<u64>,
+    /// `RewardNFT` object, for NFT rewards.
+    #[schema(value_type = Option<String>)]
+    pub nft_id: Option<ObjectID>,
+}
+
+/// Payouts collected so far and the `ProductEntryLogged` event to continue from.
+#[derive(Serialize, Deserialize, Default, Debug)]
+struct RewardLedger {
+    cursor: Option<EventID>,
+    payouts: Vec<RewardPayout>,
+}
+
+/// Filter of the ledger, every set field must match. `to` is exclusive.
+#[derive(Default, Debug)]
+pub struct LedgerQuery {
+    pub recipient: Option<IotaAddress>,
+    pub product: Option<ObjectID>,
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+}
+
+impl LedgerQuery {
+    pub fn matches(&self, payout: &RewardPayout) -> bool {
+        let timestamp = payout.timestamp_ms.unwrap_or(0);
+        self.recipient
+            .is_none_or(|recipient| recipient == payout.recipient)
+            && self.product.is_none_or(|product| product == payout.product)
+            && self.from.is_none_or(|from| timestamp >= from)
+            && self.to.is_none_or(|to| timestamp < to)
+    }
+}
+
+/// Parses a bound of the date range: unix milliseconds, an RFC 3339 timestamp
+/// or a `YYYY-MM-DD` date, which is midnight UTC.
+pub fn parse_time(value: &str) -> anyhow::Result<u64> {
+    if let Ok(millis) = value.parse() {
+        return Ok(millis);
+    }
+    let timestamp = if value.len() == 10 {
+        Timestamp::parse(&format!("{}T00:00:00Z", value))
+    } else {
+        Timestamp::parse(value)
+    }
+    .with_context(|| format!("Invalid time {}", value))?;
+    u64::try_from(timestamp.to_unix() * 1000).with_context(|| format!("Time {} before 1970", value))
+}
+
+/// Adds the rewards of entries logged since the last sync to the local ledger.
+///
+/// Entries are found through `ProductEntryLogged` events. Within each
+/// transaction, NFT rewards are matched to the `NFTMinted` events and LCC
+/// rewards to the LCC the sender received, so entries that paid nothing,
+/// e.g. for addresses off the whitelist, are left out.
+pub async fn sync_ledger(client: &IotaClient) -> anyhow::Result<usize> {
+    let _guard = LEDGER_LOCK.lock().await;
+    let mut ledger: RewardLedger = read_data(LEDGER_FILE)?.unwrap_or_default();
+    let lcc = LccConfig::from_env()?;
+
+    let (events, cursor) =
+        contract_events_since(client, "app::ProductEntryLogged", ledger.cursor).await?;
+
+    // Events of one transaction are consecutive. `new_product` emits the
+    // event without an entry and pays no reward, so it is left out before
+    // the rewards of a transaction are matched to its entries
+    let mut transactions: Vec<Vec<IotaEvent>> = Vec::new();
+    for event in events
+        .into_iter()
+        .filter(|event| object_id(&event.parsed_json["entry_addr"]).is_some())
+    {
+        match transactions.last_mut() {
+            Some(entries) if entries[0].id.tx_digest == event.id.tx_digest => entries.push(event),
+            _ => transactions.push(vec![event]),
+        }
+    }
+
+    let mut reward_types = HashMap::new();
+    let mut payouts = Vec::new();
+    for entries in transactions {
+        let response = time_chain_call(
+            "get_transaction_block",
+            client.read_api().get_transaction_with_options(
+                entries[0].id.tx_digest,
+                IotaTransactionBlockResponseOptions::new()
+                    .with_events()
+                    .with_balance_changes(),
+            ),
+        )
+        .await?;
+        let recipient = entries[0].sender;
+
+        let mut nfts: VecDeque<ObjectID> = response
+            .events
+            .map(|events| events.data)
+            .unwrap_or_default()
+            .iter()
+            .filter(|event| {
+                ObjectID::from(event.type_.address) == lcc.package_id
+                    && event.type_.module.as_str() == NFT_REWARD_MODULE
+                    && event.type_.name.as_str() == "NFTMinted"
+            })
+            .filter_map(|event| object_id(&event.parsed_json["object_id"]))
+            .collect();
+        let mut lcc_received: u64 = response
+            .balance_changes
+            .unwrap_or_default()
+            .iter()
+            .filter(|change| {
+                change.owner == Owner::AddressOwner(recipient) && lcc.is_lcc_type(&change.coin_type)
+            })
+            .map(|change| u64::try_from(change.amount).unwrap_or(0))
+            .sum();
+
+        for entry in &entries {
+            let product = object_id(&entry.parsed_json["product_addr"])
+                .context("ProductEntryLogged without product_addr")?;
+            if !reward_types.contains_key(&product) {
+                reward_types.insert(product, fetch_reward_type(client, product).await?);
+            }
+            let (reward_type, amount, nft_id) = match reward_types[&product].as_str() {
+                "LCC" if lcc_received >= REWARD_VALUE => {
+                    lcc_received -= REWARD_VALUE;
+                    (RewardKind::Lcc, Some(REWARD_VALUE), None)
+                }
+                "NFT" => match nfts.pop_front() {
+                    Some(nft) => (RewardKind::Nft, None, Some(nft)),
+                    None => continue,
+                },
+                _ => continue,
+            };
+            payouts.push(RewardPayout {
+                digest: entry.id.tx_digest.to_string(),
+                product,
+                entry: object_id(&entry.parsed_json["entry_addr"]),
+                recipient,
+                timestamp_ms: entry.timestamp_ms,
+                reward_type,
+                amount,
+                nft_id,
+            });
+        }
+    }
+
+    let added = payouts.len();
+    ledger.payouts.extend(payouts);
+    ledger.cursor = cursor;
+    write_data(LEDGER_FILE, &ledger)?;
+    Ok(added)
+}
+
+/// Payouts of the local ledger matching `query`, oldest first.
+pub fn query_ledger(query: &LedgerQuery) -> anyhow::Result<Vec<RewardPayout>> {
+    let ledger: RewardLedger = read_data(LEDGER_FILE)?.unwrap_or_default();
+    Ok(ledger
+        .payouts
+        .into_iter()
+        .filter(|payout| query.matches(payout))
+        .collect())
+}
+
+/// Renders payouts as CSV with a header row.
+pub fn payouts_csv(payouts: &[RewardPayout]) -> String {
+    let mut csv = String::from(
+        "timestamp,timestamp_ms,recipient,product,entry,reward_type,amount,nft_id,digest\n",
+    );
+    let optional = |value: Option<String>| value.unwrap_or_default();
+    for payout in payouts {
+        let timestamp = payout
+            .timestamp_ms
+            .and_then(|millis| Timestamp::from_unix(millis as i64 / 1000).ok())
+            .map(|timestamp| timestamp.to_rfc3339());
+        let reward_type = match payout.reward_type {
+            RewardKind::Lcc => "LCC",
+            RewardKind::Nft => "NFT",
+        };
+        // Every field is an address, a number or a digest, none needs quoting
+        let _ = writeln!(
+            csv,
+            "{},{},{},{},{},{},{},{},{}",
+            optional(timestamp),
+            optional(payout.timestamp_ms.map(|millis| millis.to_string())),
+            payout.recipient,
+            payout.product,
+            optional(payout.entry.map(|entry| entry.to_string())),
+            reward_type,
+            optional(payout.amount.map(|amount| amount.to_string())),
+            optional(payout.nft_id.map(|nft| nft.to_string())),
+            payout.digest,
+        );
+    }
+    csv
+}
+
+/// Reads an address rendered as a string, or an `Option<address>` rendered
+/// as the address, `null` or `{ "vec": [...] }`.
+fn object_id(value: &Value) -> Option<ObjectID> {
+    let value = match &value["vec"] {
+        Value::Array(values) => values.first()?,
+        _ => value,
+    };
+    value
+        .as_str()
+        .and_then(|id| ObjectID::from_hex_literal(id).ok())
+}
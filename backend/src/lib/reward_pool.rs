@@ -0,0 +1,294 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Watcher of the LCC reward pools held in the vault.
+//!
+//! Only products without a vault entry are funded. `lcc_reward::top_up_dpp`
+//! aborts with `EKeyAlreadyExists` for a product that already has one, so an
+//! entry running low cannot be refilled by the backend: it is only reported
+//! through `dpp_reward_pool_low`, and `log_entry_data` aborts for the product
+//! once the entry runs dry.
+
+use std::collections::BTreeSet;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{bail, Context};
+use iota_sdk::{
+    rpc_types::EventFilter,
+    types::{base_types::ObjectID, event::EventID},
+    IotaClient,
+};
+use move_core_types::language_storage::StructTag;
+
+use crate::lcc::{lcc_coin, read_dpp_value, top_up_dpp, LccConfig, REWARD_VALUE};
+use crate::metrics::{time_chain_call, REWARD_POOL_BALANCE, REWARD_POOL_LOW, REWARD_POOL_TOP_UPS};
+use crate::products::fetch_reward_type;
+use crate::signer::account_signer;
+use crate::submitter::TxSubmitter;
+use crate::treasury::{treasury_mint, TreasuryConfig};
+
+const DEFAULT_MIN_REWARDS: u64 = 10;
+const DEFAULT_TOP_UP_REWARDS: u64 = 100;
+const DEFAULT_CHECK_INTERVAL: Duration = Duration::from_secs(600);
+const EVENT_PAGE_SIZE: usize = 50;
+/// Operator recorded in the treasury audit log for automatic mints.
+const WATCHER_OPERATOR: &str = "reward-pool-watcher";
+
+/// Thresholds of the reward pool watcher, read from the environment.
+///
+/// `REWARD_POOL_MIN_REWARDS` is the number of rewards below which a pool is
+/// reported as low, checked every `REWARD_POOL_CHECK_INTERVAL_SECS`. Products
+/// without vault entry are funded with `REWARD_POOL_TOP_UP_REWARDS` rewards,
+/// unless `REWARD_POOL_AUTO_TOP_UP=false`.
+#[derive(Clone, Debug)]
+pub struct RewardPoolConfig {
+    pub min_rewards: u64,
+    pub top_up_rewards: u64,
+    pub interval: Duration,
+    pub auto_top_up: bool,
+}
+
+impl RewardPoolConfig {
+    pub fn from_env() -> anyhow::Result<Self> {
+        let number = |name: &str, default: u64| -> anyhow::Result<u64> {
+            match std::env::var(name) {
+                Ok(value) => value.parse().with_context(|| format!("Invalid {}", name)),
+                Err(_) => Ok(default),
+            }
+        };
+        Ok(Self {
+            min_rewards: number("REWARD_POOL_MIN_REWARDS", DEFAULT_MIN_REWARDS)?,
+            top_up_rewards: number("REWARD_POOL_TOP_UP_REWARDS", DEFAULT_TOP_UP_REWARDS)?,
+            interval: Duration::from_secs(number(
+                "REWARD_POOL_CHECK_INTERVAL_SECS",
+                DEFAULT_CHECK_INTERVAL.as_secs(),
+            )?),
+            auto_top_up: std::env::var("REWARD_POOL_AUTO_TOP_UP")
+                .map(|value| value != "false")
+                .unwrap_or(true),
+        })
+    }
+
+    fn min_balance(&self) -> u64 {
+        self.min_rewards.saturating_mul(REWARD_VALUE)
+    }
+
+    fn top_up_amount(&self) -> u64 {
+        self.top_up_rewards.saturating_mul(REWARD_VALUE)
+    }
+}
+
+/// Watches the vault balance of every LCC product, so that low pools are
+/// noticed before `log_entry_data` aborts when paying the reward.
+///
+/// The vault holds a single entry per product and `lcc_reward::top_up_dpp`
+/// aborts for an existing one, so only products without entry are funded.
+/// Low entries are reported through `dpp_reward_pool_low`.
+pub struct RewardPoolWatcher {
+    client: IotaClient,
+    submitter: Arc<TxSubmitter>,
+    config: RewardPoolConfig,
+    lcc: LccConfig,
+    /// Products paying LCC rewards, collected from `ProductEntryLogged` events.
+    products: BTreeSet<ObjectID>,
+    /// Products paying NFT rewards, which have no vault entry.
+    skipped: BTreeSet<ObjectID>,
+    /// Products whose reward type could not be read yet.
+    unresolved: BTreeSet<ObjectID>,
+    cursor: Option<EventID>,
+}
+
+impl RewardPoolWatcher {
+    pub fn new(
+        client: IotaClient,
+        submitter: Arc<TxSubmitter>,
+        config: RewardPoolConfig,
+    ) -> anyhow::Result<Self> {
+        let lcc = LccConfig::from_env()?;
+        if lcc.vault_id.is_none() {
+            bail!("LCC_VAULT_ID must be set");
+        }
+        Ok(Self {
+            client,
+            submitter,
+            config,
+            lcc,
+            products: BTreeSet::new(),
+            skipped: BTreeSet::new(),
+            unresolved: BTreeSet::new(),
+            cursor: None,
+        })
+    }
+
+    /// Checks the reward pools every interval, for the lifetime of the server.
+    pub async fn run(mut self) {
+        let mut interval = tokio::time::interval(self.config.interval);
+        loop {
+            interval.tick().await;
+            if let Err(err) = self.check().await {
+                eprintln!("❌ Reward pool check failed: {:#}", err);
+            }
+        }
+    }
+
+    /// Checks every known product once, funding missing entries and reporting
+    /// low pools.
+    pub async fn check(&mut self) -> anyhow::Result<()> {
+        self.discover_products().await?;
+
+        for product in self.products.clone() {
+            let entry = match read_dpp_value(&self.client, product).await {
+                Ok(entry) => entry,
+                Err(err) => {
+                    eprintln!("⚠️ Cannot read reward pool of {}: {:#}", product, err);
+                    continue;
+                }
+            };
+            // A product without vault entry cannot pay any reward yet
+            let balance = entry.unwrap_or(0);
+            let label = product.to_string();
+            REWARD_POOL_BALANCE
+                .with_label_values(&[&label])
+                .set(i64::try_from(balance).unwrap_or(i64::MAX));
+
+            if balance >= self.config.min_balance() {
+                REWARD_POOL_LOW.with_label_values(&[&label]).set(0);
+                continue;
+            }
+            REWARD_POOL_LOW.with_label_values(&[&label]).set(1);
+            eprintln!(
+                "⚠️ Reward pool of {} is low: {} rewards left, minimum {}",
+                product,
+                balance / REWARD_VALUE,
+                self.config.min_rewards
+            );
+            // Topping up an existing entry aborts with EKeyAlreadyExists
+            if entry.is_some() || !self.config.auto_top_up {
+                continue;
+            }
+
+            match self.top_up(product).await {
+                Ok(()) => {
+                    REWARD_POOL_TOP_UPS.with_label_values(&["success"]).inc();
+                    REWARD_POOL_LOW.with_label_values(&[&label]).set(0);
+                }
+                Err(err) => {
+                    REWARD_POOL_TOP_UPS.with_label_values(&["failure"]).inc();
+                    eprintln!("❌ Automatic top-up of {} failed: {:#}", product, err);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the LCC products of new `ProductEntryLogged` events.
+    async fn discover_products(&mut self) -> anyhow::Result<()> {
+        let event_type =
+            StructTag::from_str(&format!("{}::app::ProductEntryLogged", self.lcc.package_id))?;
+        let mut found = std::mem::take(&mut self.unresolved);
+        loop {
+            let page = match time_chain_call(
+                "query_events",
+                self.client.event_api().query_events(
+                    EventFilter::MoveEventType(event_type.clone()),
+                    self.cursor,
+                    Some(EVENT_PAGE_SIZE),
+                    false,
+                ),
+            )
+            .await
+            {
+                Ok(page) => page,
+                Err(err) => {
+                    // Products of the pages read so far are resolved next time
+                    self.unresolved = found;
+                    return Err(err.into());
+                }
+            };
+            found.extend(page.data.iter().filter_map(|event| {
+                event.parsed_json["product_addr"]
+                    .as_str()
+                    .and_then(|addr| ObjectID::from_hex_literal(addr).ok())
+            }));
+            if page.next_cursor.is_some() {
+                self.cursor = page.next_cursor;
+            }
+            if !page.has_next_page {
+                break;
+            }
+        }
+
+        for product in found {
+            if self.products.contains(&product) || self.skipped.contains(&product) {
+                continue;
+            }
+            match self.pays_lcc(product).await {
+                Ok(true) => {
+                    println!("👀 Watching reward pool of {}", product);
+                    self.products.insert(product);
+                }
+                Ok(false) => {
+                    self.skipped.insert(product);
+                }
+                Err(err) => {
+                    eprintln!("⚠️ Cannot read product {}: {:#}", product, err);
+                    self.unresolved.insert(product);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `product` pays LCC rather than NFT rewards.
+    async fn pays_lcc(&self, product: ObjectID) -> anyhow::Result<bool> {
+        Ok(fetch_reward_type(&self.client, product).await? == "LCC")
+    }
+
+    /// Creates the vault entry of `product` from the treasury alias, minting the
+    /// LCC if it holds too few.
+    async fn top_up(&self, product: ObjectID) -> anyhow::Result<()> {
+        let amount = self.config.top_up_amount();
+        let signer = account_signer(&TreasuryConfig::from_env()?.alias).await?;
+
+        // Only a balance too low calls for a mint, a failed read is an error
+        let coin = match lcc_coin(&self.client, signer.address(), amount).await? {
+            Some(coin) => coin,
+            None => {
+                let minted = self
+                    .submitter
+                    .submit(&signer, |gas_coin| {
+                        treasury_mint(&self.client, WATCHER_OPERATOR, amount, Some(gas_coin.0))
+                    })
+                    .await
+                    .context("Not enough LCC and minting failed")?;
+                minted.coin_id.context("Minted coin not found")?
+            }
+        };
+
+        self.submitter
+            .submit(&signer, |gas_coin| {
+                top_up_dpp(
+                    &self.client,
+                    &signer,
+                    product,
+                    amount,
+                    Some(coin),
+                    Some(gas_coin.0),
+                )
+            })
+            .await?;
+        Ok(())
+    }
+}
+
+/// Starts the reward pool watcher if the LCC contract is configured.
+pub async fn run_reward_pool_watcher(client: IotaClient, submitter: Arc<TxSubmitter>) {
+    let watcher = RewardPoolConfig::from_env()
+        .and_then(|config| RewardPoolWatcher::new(client, submitter, config));
+    match watcher {
+        Ok(watcher) => watcher.run().await,
+        Err(err) => eprintln!("⚠️ Reward pool watcher disabled: {:#}", err),
+    }
+}
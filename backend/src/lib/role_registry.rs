@@ -0,0 +1,303 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Context};
+use hierarchies::core::types::{
+    property::FederationProperty, property_name::PropertyName, property_value::PropertyValue,
+};
+use iota_sdk::types::base_types::ObjectID;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use utoipa::ToSchema;
+
+use crate::keystore::{
+    get_hierarchies_client, get_hierarchies_read_only_client, MANUFACTURER_ALIAS,
+};
+use crate::metrics::time_chain_call;
+use crate::signer::account_signer;
+use crate::submitter::TxSubmitter;
+
+/// Federation property holding the role of an account.
+const ROLE_PROPERTY: &str = "role";
+
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, ToSchema,
+)]
+pub enum Role {
+    Manufacturer,
+    Repairer,
+}
+
+impl Role {
+    pub const ALL: [Role; 2] = [Role::Manufacturer, Role::Repairer];
+
+    /// Value of the `role` property attested for this role.
+    fn property_value(self) -> PropertyValue {
+        PropertyValue::Text(self.to_string().to_lowercase())
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let role_str = match self {
+            Role::Manufacturer => "Manufacturer",
+            Role::Repairer => "Repairer",
+        };
+        write!(f, "{}", role_str)
+    }
+}
+
+/// Roles of accounts within a federation.
+///
+/// Accounts are identified by the object ID of their address, as in the
+/// hierarchies contract.
+#[async_trait::async_trait]
+pub trait RoleRegistry: Send + Sync {
+    /// Accredits `user_id` to attest `role` in `federation_id`.
+    async fn grant(
+        &self,
+        federation_id: ObjectID,
+        user_id: ObjectID,
+        role: Role,
+    ) -> anyhow::Result<()>;
+
+    /// Withdraws every accreditation of `user_id` allowing `role`, fails if there is none.
+    async fn revoke(
+        &self,
+        federation_id: ObjectID,
+        user_id: ObjectID,
+        role: Role,
+    ) -> anyhow::Result<()>;
+
+    /// Whether `user_id` currently holds `role`.
+    async fn validate(
+        &self,
+        federation_id: ObjectID,
+        user_id: ObjectID,
+        role: Role,
+    ) -> anyhow::Result<bool>;
+
+    /// Roles held by `user_id`, sorted.
+    async fn list(&self, federation_id: ObjectID, user_id: ObjectID) -> anyhow::Result<Vec<Role>>;
+}
+
+/// Role registry selected through the `ROLE_REGISTRY` env var: `hierarchies`
+/// (default) or `memory`.
+pub fn role_registry(submitter: Arc<TxSubmitter>) -> anyhow::Result<Arc<dyn RoleRegistry>> {
+    let registry = std::env::var("ROLE_REGISTRY").unwrap_or_else(|_| "hierarchies".to_string());
+    match registry.as_str() {
+        "hierarchies" => Ok(Arc::new(HierarchiesRoleRegistry::new(submitter))),
+        "memory" => Ok(Arc::new(InMemoryRoleRegistry::default())),
+        other => bail!("Unknown ROLE_REGISTRY '{}'", other),
+    }
+}
+
+/// Roles kept as accreditations to attest in the hierarchies contract,
+/// managed by the manufacturer account.
+pub struct HierarchiesRoleRegistry {
+    submitter: Arc<TxSubmitter>,
+}
+
+impl HierarchiesRoleRegistry {
+    pub fn new(submitter: Arc<TxSubmitter>) -> Self {
+        Self { submitter }
+    }
+}
+
+#[async_trait::async_trait]
+impl RoleRegistry for HierarchiesRoleRegistry {
+    async fn grant(
+        &self,
+        federation_id: ObjectID,
+        user_id: ObjectID,
+        role: Role,
+    ) -> anyhow::Result<()> {
+        let hierarchies_client = get_hierarchies_client(MANUFACTURER_ALIAS.to_string()).await?;
+        let signer = account_signer(MANUFACTURER_ALIAS).await?;
+        let property = FederationProperty::new(PropertyName::from(ROLE_PROPERTY))
+            .with_allowed_values([role.property_value()]);
+
+        self.submitter
+            .submit(&signer, |gas_coin| {
+                let hierarchies_client = &hierarchies_client;
+                let property = property.clone();
+                async move {
+                    hierarchies_client
+                        .create_accreditation_to_attest(federation_id, user_id, [property])
+                        .with_gas_payment(vec![gas_coin])
+                        .build_and_execute(hierarchies_client)
+                        .await?;
+                    Ok(())
+                }
+            })
+            .await
+            .context("Failed to create accreditation to attest")?;
+        println!("✅ Attestation created");
+
+        if !self.validate(federation_id, user_id, role).await? {
+            bail!(
+                "{} is not accredited to attest {} after the grant",
+                user_id,
+                role
+            );
+        }
+        println!("✅ Attester is accredited to attest");
+        Ok(())
+    }
+
+    async fn revoke(
+        &self,
+        federation_id: ObjectID,
+        user_id: ObjectID,
+        role: Role,
+    ) -> anyhow::Result<()> {
+        let hierarchies_client = get_hierarchies_client(MANUFACTURER_ALIAS.to_string()).await?;
+        let signer = account_signer(MANUFACTURER_ALIAS).await?;
+        let role_property = PropertyName::from(ROLE_PROPERTY);
+        let value = role.property_value();
+
+        let accreditations: Vec<ObjectID> = time_chain_call(
+            "get_accreditations_to_attest",
+            hierarchies_client.get_accreditations_to_attest(federation_id, user_id),
+        )
+        .await?
+        .accreditations
+        .iter()
+        .filter(|accreditation| {
+            accreditation
+                .properties
+                .get(&role_property)
+                .is_some_and(|property| property.allowed_values.contains(&value))
+        })
+        .map(|accreditation| *accreditation.id.object_id())
+        .collect();
+        if accreditations.is_empty() {
+            bail!("{} does not hold the {} role", user_id, role);
+        }
+
+        for accreditation_id in accreditations {
+            self.submitter
+                .submit(&signer, |gas_coin| {
+                    let hierarchies_client = &hierarchies_client;
+                    async move {
+                        hierarchies_client
+                            .revoke_accreditation_to_attest(
+                                federation_id,
+                                user_id,
+                                accreditation_id,
+                            )
+                            .with_gas_payment(vec![gas_coin])
+                            .build_and_execute(hierarchies_client)
+                            .await?;
+                        Ok(())
+                    }
+                })
+                .await
+                .context("Failed to revoke accreditation to attest")?;
+            println!("✅ Accreditation {} revoked", accreditation_id);
+        }
+        Ok(())
+    }
+
+    async fn validate(
+        &self,
+        federation_id: ObjectID,
+        user_id: ObjectID,
+        role: Role,
+    ) -> anyhow::Result<bool> {
+        let hierarchies_client = get_hierarchies_read_only_client().await?;
+        time_chain_call(
+            "validate_property",
+            hierarchies_client.validate_property(
+                federation_id,
+                user_id,
+                PropertyName::from(ROLE_PROPERTY),
+                role.property_value(),
+            ),
+        )
+        .await
+        .map_err(|e| anyhow!("Failed to validate attestation allowance: {}", e))
+    }
+
+    async fn list(&self, federation_id: ObjectID, user_id: ObjectID) -> anyhow::Result<Vec<Role>> {
+        let mut roles = Vec::new();
+        for role in Role::ALL {
+            if self.validate(federation_id, user_id, role).await? {
+                roles.push(role);
+            }
+        }
+        Ok(roles)
+    }
+}
+
+/// Roles kept in memory, for running the API without a federation on chain.
+///
+/// Everything is lost on restart.
+#[derive(Default)]
+pub struct InMemoryRoleRegistry {
+    roles: RwLock<HashMap<(ObjectID, ObjectID), BTreeSet<Role>>>,
+}
+
+#[async_trait::async_trait]
+impl RoleRegistry for InMemoryRoleRegistry {
+    async fn grant(
+        &self,
+        federation_id: ObjectID,
+        user_id: ObjectID,
+        role: Role,
+    ) -> anyhow::Result<()> {
+        self.roles
+            .write()
+            .await
+            .entry((federation_id, user_id))
+            .or_default()
+            .insert(role);
+        Ok(())
+    }
+
+    async fn revoke(
+        &self,
+        federation_id: ObjectID,
+        user_id: ObjectID,
+        role: Role,
+    ) -> anyhow::Result<()> {
+        let removed = self
+            .roles
+            .write()
+            .await
+            .get_mut(&(federation_id, user_id))
+            .is_some_and(|roles| roles.remove(&role));
+        if !removed {
+            bail!("{} does not hold the {} role", user_id, role);
+        }
+        Ok(())
+    }
+
+    async fn validate(
+        &self,
+        federation_id: ObjectID,
+        user_id: ObjectID,
+        role: Role,
+    ) -> anyhow::Result<bool> {
+        Ok(self
+            .roles
+            .read()
+            .await
+            .get(&(federation_id, user_id))
+            .is_some_and(|roles| roles.contains(&role)))
+    }
+
+    async fn list(&self, federation_id: ObjectID, user_id: ObjectID) -> anyhow::Result<Vec<Role>> {
+        Ok(self
+            .roles
+            .read()
+            .await
+            .get(&(federation_id, user_id))
+            .map(|roles| roles.iter().copied().collect())
+            .unwrap_or_default())
+    }
+}
@@ -0,0 +1,574 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context};
+use hierarchies::client::HierarchiesClient;
+use identity_iota::did::DIDUrl;
+use identity_iota::iota::IotaDID;
+use identity_iota::storage::JwkDocumentExt;
+use identity_iota::verification::{MethodScope, VerificationMethod};
+use iota_sdk::{
+    rpc_types::{IotaObjectDataFilter, IotaObjectResponseQuery},
+    types::base_types::{IotaAddress, ObjectID},
+    IotaClient,
+};
+use move_core_types::language_storage::StructTag;
+use serde::{Deserialize, Serialize};
+
+use crate::data::{read_data, write_data};
+use crate::domain_linkage::refresh_linkages;
+use crate::identity::{
+    delete_method_key, get_client_with_signer, method_key_storage, MethodKey, TEST_GAS_BUDGET,
+};
+use crate::keystore::{
+    get_hierarchies_client, KeystoreClient, MANUFACTURER_ALIAS, ROOT_AUTH_ALIAS,
+};
+use crate::network::iota_client;
+use crate::signer::{AccountSigner, SignerConfig};
+use crate::transaction::sign_and_execute_transaction;
+
+/// Progress of a key rotation, persisted after every step so that an
+/// interrupted rotation can be resumed or aborted.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RotationState {
+    pub alias: String,
+    /// Alias the leaked key has been moved to.
+    pub retired_alias: String,
+    pub old_address: IotaAddress,
+    /// Same as `old_address` while `keys_pending`.
+    pub new_address: IotaAddress,
+    /// Recorded before the keystore is changed, so that the retired alias is
+    /// known even if the rotation stops right after.
+    #[serde(default)]
+    pub keys_pending: bool,
+    pub started_at: u64,
+    pub federations: Vec<FederationRotation>,
+    /// Verification methods replaced in the DID document, recorded before
+    /// publishing. Their keys are deleted once the update is on chain.
+    #[serde(default)]
+    pub retired_did_methods: Vec<VerificationMethod>,
+    #[serde(default)]
+    pub did_published: bool,
+    pub did_rotated: bool,
+    pub controller_transferred: bool,
+    pub funds_moved: bool,
+    pub completed: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FederationRotation {
+    pub federation_id: ObjectID,
+    /// Accreditations of the old address, revoked once the new address holds copies of them.
+    pub old_attest_accreditations: Vec<ObjectID>,
+    pub old_accredit_accreditations: Vec<ObjectID>,
+    /// Old accreditations already copied to the new address, recorded after
+    /// each copy so that resuming does not accredit the new address twice.
+    #[serde(default)]
+    pub copied_accreditations: Vec<ObjectID>,
+    /// Old accreditations already revoked, recorded after each revocation so
+    /// that resuming does not revoke them again.
+    #[serde(default)]
+    pub revoked_accreditations: Vec<ObjectID>,
+    pub recreated: bool,
+    pub revoked: bool,
+}
+
+impl RotationState {
+    /// Whether nothing irreversible has happened yet. A rotated DID method
+    /// belongs to the new key, so it rules out an abort as well.
+    pub fn can_abort(&self) -> bool {
+        !self.controller_transferred
+            && !self.did_published
+            && !(self.did_rotated && self.alias == MANUFACTURER_ALIAS)
+            && self.federations.iter().all(|f| !f.revoked)
+    }
+
+    fn save(&self) -> anyhow::Result<()> {
+        write_data(&rotation_file(&self.alias), self)
+    }
+}
+
+fn rotation_file(alias: &str) -> String {
+    format!("rotation-{}.json", alias)
+}
+
+pub fn load_rotation(alias: &str) -> anyhow::Result<Option<RotationState>> {
+    read_data(&rotation_file(alias))
+}
+
+/// Generates a new key for `alias`, or returns the pending rotation of `alias` to resume it.
+///
+/// The server reads the keystore once, it has to be restarted to sign with the new key.
+pub fn start_rotation(alias: &str, federations: &[ObjectID]) -> anyhow::Result<RotationState> {
+    if let Some(mut state) = load_rotation(alias)?.filter(|state| !state.completed) {
+        println!(
+            "↩️ Resuming rotation of '{}' started at {}",
+            alias, state.started_at
+        );
+        if state.keys_pending {
+            rotate_keys(&mut state)?;
+        }
+        return Ok(state);
+    }
+
+    let signer_config = SignerConfig::from_env()?;
+    if !matches!(
+        signer_config,
+        SignerConfig::File | SignerConfig::Encrypted { .. }
+    ) {
+        bail!("Key rotation requires a local keystore signer backend");
+    }
+    if alias == ROOT_AUTH_ALIAS {
+        bail!("The root authority cannot accredit itself, rotate it as a root authority instead");
+    }
+    if alias == MANUFACTURER_ALIAS {
+        // The DID method key is rotated as well
+        method_key_storage()?;
+    }
+
+    let started_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let retired_alias = format!("{}-retired-{}", alias, started_at);
+    let old_address = KeystoreClient::open()?.get_address(alias.to_string())?;
+
+    let mut state = RotationState {
+        alias: alias.to_string(),
+        retired_alias,
+        old_address,
+        new_address: old_address,
+        keys_pending: true,
+        started_at,
+        federations: federations
+            .iter()
+            .map(|federation_id| FederationRotation {
+                federation_id: *federation_id,
+                old_attest_accreditations: Vec::new(),
+                old_accredit_accreditations: Vec::new(),
+                copied_accreditations: Vec::new(),
+                revoked_accreditations: Vec::new(),
+                recreated: false,
+                revoked: false,
+            })
+            .collect(),
+        retired_did_methods: Vec::new(),
+        did_published: false,
+        did_rotated: false,
+        controller_transferred: false,
+        funds_moved: false,
+        completed: false,
+    };
+    state.save()?;
+    rotate_keys(&mut state)?;
+    Ok(state)
+}
+
+/// Moves the key of the account to the retired alias and generates a new
+/// one, unless the previous run did so before it stopped.
+fn rotate_keys(state: &mut RotationState) -> anyhow::Result<()> {
+    let keystore = KeystoreClient::open()?;
+    state.new_address = if keystore.get_address(state.retired_alias.clone()).is_ok() {
+        keystore.get_address(state.alias.clone())?
+    } else {
+        KeystoreClient::rotate_key(&state.alias, &state.retired_alias)?.1
+    };
+    state.keys_pending = false;
+    state.save()?;
+    println!(
+        "🔑 New key for '{}': {} (was {})",
+        state.alias, state.new_address, state.old_address
+    );
+    Ok(())
+}
+
+/// Runs the remaining steps of `state`.
+///
+/// The new address is accredited before anything of the old one is revoked,
+/// so the account keeps its permissions throughout the rotation.
+pub async fn run_rotation(state: &mut RotationState) -> anyhow::Result<()> {
+    let root_client = get_hierarchies_client(ROOT_AUTH_ALIAS.to_string()).await?;
+    let iota_client = iota_client().await?;
+    let old_signer = retired_signer(state).await?;
+
+    for i in 0..state.federations.len() {
+        if !state.federations[i].recreated {
+            recreate_accreditations(&root_client, state, i).await?;
+            state.federations[i].recreated = true;
+            state.save()?;
+        }
+    }
+
+    if !state.did_rotated {
+        if state.alias == MANUFACTURER_ALIAS {
+            let did_str =
+                std::env::var("MANUFACTURER_DID").context("MANUFACTURER_DID must be set")?;
+            rotate_did_method(old_signer.clone(), &IotaDID::from_str(&did_str)?, state).await?;
+            println!("✅ DID verification method rotated");
+        }
+        state.did_rotated = true;
+        state.save()?;
+    }
+
+    if !state.controller_transferred {
+        transfer_controller_caps(&iota_client, &old_signer, state.new_address).await?;
+        state.controller_transferred = true;
+        state.save()?;
+    }
+
+    for i in 0..state.federations.len() {
+        if !state.federations[i].revoked {
+            revoke_old_accreditations(&root_client, state, i).await?;
+            state.federations[i].revoked = true;
+            state.save()?;
+        }
+    }
+
+    if !state.funds_moved {
+        move_funds(&iota_client, &old_signer, state.new_address).await?;
+        state.funds_moved = true;
+        state.save()?;
+    }
+
+    state.completed = true;
+    state.save()?;
+    println!("✅ Rotation of '{}' completed", state.alias);
+    println!("🔁 Restart the server, it still signs with the key it read at startup");
+    Ok(())
+}
+
+/// Undoes a rotation that has not revoked anything yet: the accreditations of
+/// the new address are revoked and the old key gets its alias back.
+pub async fn abort_rotation(alias: &str) -> anyhow::Result<()> {
+    let state = load_rotation(alias)?
+        .filter(|state| !state.completed)
+        .context(format!("No pending rotation for '{}'", alias))?;
+    if !state.can_abort() {
+        bail!(
+            "Rotation of '{}' can no longer be undone, resume it instead",
+            alias
+        );
+    }
+
+    if !state.keys_pending {
+        let root_client = get_hierarchies_client(ROOT_AUTH_ALIAS.to_string()).await?;
+        let new_id = ObjectID::from_address(state.new_address.into());
+        for federation in state.federations.iter().filter(|f| f.recreated) {
+            revoke_all_accreditations(&root_client, federation.federation_id, new_id).await?;
+        }
+    }
+
+    // The keys may have been rotated right before a pending rotation stopped
+    if KeystoreClient::open()?
+        .get_address(state.retired_alias.clone())
+        .is_ok()
+    {
+        KeystoreClient::swap_aliases(alias, &state.retired_alias)?;
+    }
+    std::fs::remove_file(crate::data::data_path(&rotation_file(alias)))?;
+    println!("↩️ Rotation of '{}' aborted, old key restored", alias);
+    Ok(())
+}
+
+async fn retired_signer(state: &RotationState) -> anyhow::Result<AccountSigner> {
+    // Read the keystore again, the process-wide backend predates the rotation
+    AccountSigner::new(&state.retired_alias, Arc::new(KeystoreClient::open()?)).await
+}
+
+async fn recreate_accreditations(
+    root_client: &HierarchiesClient<AccountSigner>,
+    state: &mut RotationState,
+    index: usize,
+) -> anyhow::Result<()> {
+    let federation_id = state.federations[index].federation_id;
+    let old_id = ObjectID::from_address(state.old_address.into());
+    let new_id = ObjectID::from_address(state.new_address.into());
+
+    let to_attest = root_client
+        .get_accreditations_to_attest(federation_id, old_id)
+        .await?
+        .accreditations;
+    let to_accredit = root_client
+        .get_accreditations_to_accredit(federation_id, old_id)
+        .await?
+        .accreditations;
+
+    let federation = &mut state.federations[index];
+    federation.old_attest_accreditations = to_attest.iter().map(|a| *a.id.object_id()).collect();
+    federation.old_accredit_accreditations =
+        to_accredit.iter().map(|a| *a.id.object_id()).collect();
+    state.save()?;
+
+    for accreditation in &to_attest {
+        let old_accreditation = *accreditation.id.object_id();
+        if state.federations[index]
+            .copied_accreditations
+            .contains(&old_accreditation)
+        {
+            continue;
+        }
+        root_client
+            .create_accreditation_to_attest(
+                federation_id,
+                new_id,
+                accreditation.properties.values().cloned(),
+            )
+            .build_and_execute(root_client)
+            .await
+            .context("Failed to recreate accreditation to attest")?;
+        state.federations[index]
+            .copied_accreditations
+            .push(old_accreditation);
+        state.save()?;
+    }
+    for accreditation in &to_accredit {
+        let old_accreditation = *accreditation.id.object_id();
+        if state.federations[index]
+            .copied_accreditations
+            .contains(&old_accreditation)
+        {
+            continue;
+        }
+        root_client
+            .create_accreditation_to_accredit(
+                federation_id,
+                new_id,
+                accreditation.properties.values().cloned(),
+            )
+            .build_and_execute(root_client)
+            .await
+            .context("Failed to recreate accreditation to accredit")?;
+        state.federations[index]
+            .copied_accreditations
+            .push(old_accreditation);
+        state.save()?;
+    }
+
+    println!(
+        "✅ {} accreditations recreated in federation {}",
+        to_attest.len() + to_accredit.len(),
+        federation_id
+    );
+    Ok(())
+}
+
+async fn revoke_old_accreditations(
+    root_client: &HierarchiesClient<AccountSigner>,
+    state: &mut RotationState,
+    index: usize,
+) -> anyhow::Result<()> {
+    let federation_id = state.federations[index].federation_id;
+    let old_id = ObjectID::from_address(state.old_address.into());
+
+    let to_attest = state.federations[index].old_attest_accreditations.clone();
+    for accreditation_id in to_attest {
+        if state.federations[index]
+            .revoked_accreditations
+            .contains(&accreditation_id)
+        {
+            continue;
+        }
+        root_client
+            .revoke_accreditation_to_attest(federation_id, old_id, accreditation_id)
+            .build_and_execute(root_client)
+            .await
+            .context("Failed to revoke accreditation to attest")?;
+        state.federations[index]
+            .revoked_accreditations
+            .push(accreditation_id);
+        state.save()?;
+    }
+    let to_accredit = state.federations[index].old_accredit_accreditations.clone();
+    for accreditation_id in to_accredit {
+        if state.federations[index]
+            .revoked_accreditations
+            .contains(&accreditation_id)
+        {
+            continue;
+        }
+        root_client
+            .revoke_accreditation_to_accredit(federation_id, old_id, accreditation_id)
+            .build_and_execute(root_client)
+            .await
+            .context("Failed to revoke accreditation to accredit")?;
+        state.federations[index]
+            .revoked_accreditations
+            .push(accreditation_id);
+        state.save()?;
+    }
+
+    println!(
+        "✅ Old accreditations revoked in federation {}",
+        federation_id
+    );
+    Ok(())
+}
+
+async fn revoke_all_accreditations(
+    root_client: &HierarchiesClient<AccountSigner>,
+    federation_id: ObjectID,
+    user_id: ObjectID,
+) -> anyhow::Result<()> {
+    let to_attest = root_client
+        .get_accreditations_to_attest(federation_id, user_id)
+        .await?
+        .accreditations;
+    for accreditation in to_attest {
+        root_client
+            .revoke_accreditation_to_attest(federation_id, user_id, *accreditation.id.object_id())
+            .build_and_execute(root_client)
+            .await?;
+    }
+
+    let to_accredit = root_client
+        .get_accreditations_to_accredit(federation_id, user_id)
+        .await?
+        .accreditations;
+    for accreditation in to_accredit {
+        root_client
+            .revoke_accreditation_to_accredit(federation_id, user_id, *accreditation.id.object_id())
+            .build_and_execute(root_client)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Replaces every verification method of the DID with a freshly generated one
+/// of the `DID_KEY_TYPE` key type.
+///
+/// The old methods are recorded in `state` and removed from the document, and
+/// their keys deleted only once the update is on chain.
+async fn rotate_did_method(
+    signer: AccountSigner,
+    did: &IotaDID,
+    state: &mut RotationState,
+) -> anyhow::Result<()> {
+    let keytool_storage = method_key_storage()?;
+
+    if !state.did_published {
+        let key = MethodKey::from_env()?;
+        let identity_client = get_client_with_signer(signer).await?;
+        let mut did_document = identity_client.resolve_did(did).await?;
+
+        if state.retired_did_methods.is_empty() {
+            state.retired_did_methods = did_document.methods(None).into_iter().cloned().collect();
+            state.save()?;
+        }
+        let pending: Vec<DIDUrl> = state
+            .retired_did_methods
+            .iter()
+            .map(|method| method.id().clone())
+            .filter(|method_id| did_document.resolve_method(method_id, None).is_some())
+            .collect();
+
+        // None left means the update was published before the previous run stopped
+        if !pending.is_empty() {
+            let fragment = did_document
+                .generate_method(
+                    &keytool_storage,
+                    key.key_type(),
+                    key.algorithm(),
+                    None,
+                    MethodScope::VerificationMethod,
+                )
+                .await?;
+            let new_method = did_document
+                .resolve_method(&did.clone().join(format!("#{}", fragment))?, None)
+                .cloned()
+                .context("Generated verification method not found")?;
+            for method_id in &pending {
+                did_document.remove_method(method_id);
+            }
+
+            if let Err(err) = identity_client
+                .publish_did_document_update(did_document, TEST_GAS_BUDGET)
+                .await
+            {
+                // The key was never published, it would only be left behind
+                delete_method_key(&keytool_storage, &new_method).await?;
+                return Err(err.into());
+            }
+            println!("🔑 New verification method: #{}", fragment);
+        }
+        state.did_published = true;
+        state.save()?;
+    }
+
+    for method in &state.retired_did_methods {
+        delete_method_key(&keytool_storage, method).await?;
+    }
+
+    // The served domain linkage credentials were signed by a retired method
+    let renewed = refresh_linkages().await?;
+    println!("✅ {} domain linkage credential(s) re-signed", renewed);
+    Ok(())
+}
+
+/// Hands the identity controller capabilities of the old address over to the new one.
+async fn transfer_controller_caps(
+    client: &IotaClient,
+    old_signer: &AccountSigner,
+    new_address: IotaAddress,
+) -> anyhow::Result<()> {
+    let identity_pkg_id =
+        std::env::var("IOTA_IDENTITY_PKG_ID").context("IOTA_IDENTITY_PKG_ID must be set")?;
+    let cap_type = StructTag::from_str(&format!("{}::controller::ControllerCap", identity_pkg_id))?;
+
+    let caps = client
+        .read_api()
+        .get_owned_objects(
+            old_signer.address(),
+            Some(IotaObjectResponseQuery::new_with_filter(
+                IotaObjectDataFilter::StructType(cap_type),
+            )),
+            None,
+            None,
+        )
+        .await?
+        .data;
+
+    for cap in caps {
+        let cap_id = cap.object_id()?;
+        let tx_data = client
+            .transaction_builder()
+            .transfer_object(
+                old_signer.address(),
+                cap_id,
+                None,
+                TEST_GAS_BUDGET,
+                new_address,
+            )
+            .await?;
+        sign_and_execute_transaction(client, old_signer, tx_data).await?;
+        println!("✅ Controller capability {} transferred", cap_id);
+    }
+    Ok(())
+}
+
+/// Sends the remaining IOTA of the old address to the new one.
+async fn move_funds(
+    client: &IotaClient,
+    old_signer: &AccountSigner,
+    new_address: IotaAddress,
+) -> anyhow::Result<()> {
+    let coins: Vec<ObjectID> = client
+        .coin_read_api()
+        .get_coins(old_signer.address(), None, None, None)
+        .await?
+        .data
+        .into_iter()
+        .map(|coin| coin.coin_object_id)
+        .collect();
+    if coins.is_empty() {
+        return Ok(());
+    }
+
+    let tx_data = client
+        .transaction_builder()
+        .pay_all_iota(old_signer.address(), coins, new_address, TEST_GAS_BUDGET)
+        .await?;
+    sign_and_execute_transaction(client, old_signer, tx_data).await?;
+    println!("✅ Funds moved to {}", new_address);
+    Ok(())
+}
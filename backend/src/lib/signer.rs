@@ -0,0 +1,339 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context};
+use fastcrypto::encoding::{Base64, Encoding};
+use fastcrypto::traits::{EncodeDecodeBase64, ToFromBytes};
+use iota_interaction::IotaKeySignature;
+use iota_sdk::types::{
+    base_types::IotaAddress,
+    crypto::{IotaKeyPair, PublicKey, Signature},
+    transaction::TransactionData,
+};
+use secret_storage::{SignatureScheme as SignerSignatureScheme, Signer as SignerTrait};
+use serde::{Deserialize, Serialize};
+use shared_crypto::intent::{Intent, IntentMessage};
+
+use crate::keystore::{KeystoreClient, GAS_STATION_ALIAS, MANUFACTURER_ALIAS, ROOT_AUTH_ALIAS};
+
+pub const DEFAULT_ENCRYPTED_KEYSTORE_PATH: &str = "../keystore/iota.keystore.enc";
+/// Longest a remote signer may take to accept a connection.
+const REMOTE_SIGNER_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Longest a remote signer may take to answer, so that a stalled one does
+/// not block its gas lane.
+const REMOTE_SIGNER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A source of account keys, addressed by keystore alias.
+#[async_trait::async_trait]
+pub trait KeyBackend: Send + Sync {
+    async fn public_key(&self, alias: &str) -> anyhow::Result<PublicKey>;
+
+    async fn sign(&self, alias: &str, data: &TransactionData) -> anyhow::Result<Signature>;
+
+    async fn address(&self, alias: &str) -> anyhow::Result<IotaAddress> {
+        Ok(IotaAddress::from(&self.public_key(alias).await?))
+    }
+}
+
+/// Signer backend selected through the `SIGNER_BACKEND` env var.
+pub enum SignerConfig {
+    /// The IOTA CLI keystore in `iota_config_dir()`.
+    File,
+    /// Private keys read from `<ACCOUNT>_SECRET_KEY` or the file named by `<ACCOUNT>_SECRET_KEY_FILE`.
+    RawKey,
+    /// A passphrase-encrypted keystore file.
+    Encrypted { path: String, passphrase: String },
+    /// A remote signing service spoken to over HTTP.
+    Remote { url: String, token: Option<String> },
+}
+
+impl SignerConfig {
+    pub fn from_env() -> anyhow::Result<Self> {
+        let backend = std::env::var("SIGNER_BACKEND").unwrap_or_else(|_| "file".to_string());
+
+        match backend.as_str() {
+            "file" => {
+                if read_secret("KEYSTORE_PASSPHRASE").is_ok() {
+                    bail!("KEYSTORE_PASSPHRASE is set, use SIGNER_BACKEND=encrypted for the encrypted keystore");
+                }
+                Ok(SignerConfig::File)
+            }
+            "raw" => Ok(SignerConfig::RawKey),
+            "encrypted" => Ok(SignerConfig::Encrypted {
+                path: std::env::var("ENCRYPTED_KEYSTORE_PATH")
+                    .unwrap_or_else(|_| DEFAULT_ENCRYPTED_KEYSTORE_PATH.to_string()),
+                passphrase: read_secret("KEYSTORE_PASSPHRASE")?,
+            }),
+            "remote" => Ok(SignerConfig::Remote {
+                url: std::env::var("REMOTE_SIGNER_URL")
+                    .context("REMOTE_SIGNER_URL must be set for the remote signer backend")?,
+                token: std::env::var("REMOTE_SIGNER_TOKEN").ok(),
+            }),
+            other => bail!("Unknown SIGNER_BACKEND '{}'", other),
+        }
+    }
+
+    pub fn into_backend(self) -> anyhow::Result<Arc<dyn KeyBackend>> {
+        Ok(match self {
+            SignerConfig::File => Arc::new(KeystoreClient::get_keystore()),
+            SignerConfig::RawKey => Arc::new(RawKeyBackend::from_env()?),
+            SignerConfig::Encrypted { path, passphrase } => {
+                Arc::new(KeystoreClient::unlock(path, &passphrase)?)
+            }
+            SignerConfig::Remote { url, token } => Arc::new(RemoteSigner::new(url, token)?),
+        })
+    }
+}
+
+static SIGNER_BACKEND: OnceLock<Arc<dyn KeyBackend>> = OnceLock::new();
+
+/// Returns the key backend configured in the environment.
+///
+/// The backend is opened, and an encrypted keystore unlocked, on first use
+/// and shared by the rest of the process.
+pub fn signer_backend() -> anyhow::Result<Arc<dyn KeyBackend>> {
+    if let Some(backend) = SIGNER_BACKEND.get() {
+        return Ok(backend.clone());
+    }
+    let backend = SignerConfig::from_env()?.into_backend()?;
+    Ok(SIGNER_BACKEND.get_or_init(|| backend).clone())
+}
+
+/// Returns a transaction signer for `alias` using the configured backend.
+pub async fn account_signer(alias: &str) -> anyhow::Result<AccountSigner> {
+    AccountSigner::new(alias, signer_backend()?).await
+}
+
+/// Signs transactions for a single account of a [`KeyBackend`].
+#[derive(Clone)]
+pub struct AccountSigner {
+    alias: String,
+    address: IotaAddress,
+    public_key: PublicKey,
+    backend: Arc<dyn KeyBackend>,
+}
+
+impl AccountSigner {
+    pub async fn new(alias: &str, backend: Arc<dyn KeyBackend>) -> anyhow::Result<Self> {
+        let public_key = backend
+            .public_key(alias)
+            .await
+            .with_context(|| format!("No key found for alias {}", alias))?;
+
+        Ok(Self {
+            alias: alias.to_string(),
+            address: IotaAddress::from(&public_key),
+            public_key,
+            backend,
+        })
+    }
+
+    pub fn alias(&self) -> &str {
+        &self.alias
+    }
+
+    pub fn address(&self) -> IotaAddress {
+        self.address
+    }
+
+    pub async fn sign_transaction(&self, data: &TransactionData) -> anyhow::Result<Signature> {
+        self.backend.sign(&self.alias, data).await
+    }
+}
+
+#[async_trait::async_trait]
+impl SignerTrait<IotaKeySignature> for AccountSigner {
+    type KeyId = String;
+
+    async fn sign(
+        &self,
+        data: &TransactionData,
+    ) -> secret_storage::Result<<IotaKeySignature as SignerSignatureScheme>::Signature> {
+        self.sign_transaction(data)
+            .await
+            .map_err(secret_storage::Error::Other)
+    }
+
+    async fn public_key(
+        &self,
+    ) -> secret_storage::Result<<IotaKeySignature as secret_storage::SignatureScheme>::PublicKey>
+    {
+        Ok(self.public_key.clone())
+    }
+
+    fn key_id(&self) -> Self::KeyId {
+        self.alias.clone()
+    }
+}
+
+/// Keys held in memory, loaded from env vars or secret files.
+pub struct RawKeyBackend {
+    keys: HashMap<String, IotaKeyPair>,
+}
+
+impl RawKeyBackend {
+    /// Loads the key of every known alias whose secret is present in the environment.
+    pub fn from_env() -> anyhow::Result<Self> {
+        let mut keys = HashMap::new();
+        for alias in [ROOT_AUTH_ALIAS, MANUFACTURER_ALIAS, GAS_STATION_ALIAS] {
+            let var = secret_key_var(alias);
+            let Ok(secret) = read_secret(&var) else {
+                continue;
+            };
+            let keypair = IotaKeyPair::decode(secret.trim())
+                .map_err(|e| anyhow!("Invalid private key in {}: {}", var, e))?;
+            keys.insert(alias.to_string(), keypair);
+        }
+        Ok(Self { keys })
+    }
+
+    fn key(&self, alias: &str) -> anyhow::Result<&IotaKeyPair> {
+        self.keys.get(alias).with_context(|| {
+            format!(
+                "No key found for alias {}, set {}",
+                alias,
+                secret_key_var(alias)
+            )
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl KeyBackend for RawKeyBackend {
+    async fn public_key(&self, alias: &str) -> anyhow::Result<PublicKey> {
+        Ok(self.key(alias)?.public())
+    }
+
+    async fn sign(&self, alias: &str, data: &TransactionData) -> anyhow::Result<Signature> {
+        Ok(Signature::new_secure(
+            &IntentMessage::new(Intent::iota_transaction(), data),
+            self.key(alias)?,
+        ))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RemotePublicKeyResponse {
+    pub public_key: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RemoteSignRequest {
+    pub alias: String,
+    pub tx_data: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RemoteSignResponse {
+    pub signature: String,
+}
+
+/// Client of a remote signing service.
+///
+/// The service exposes `GET /v1/keys/{alias}` returning the base64 encoded
+/// flagged public key, and `POST /v1/sign` taking the base64 BCS encoded
+/// `TransactionData` and returning the base64 encoded signature.
+pub struct RemoteSigner {
+    url: reqwest::Url,
+    token: Option<String>,
+    http: reqwest::Client,
+}
+
+impl RemoteSigner {
+    pub fn new(url: String, token: Option<String>) -> anyhow::Result<Self> {
+        let url = reqwest::Url::parse(&url)
+            .with_context(|| format!("Invalid REMOTE_SIGNER_URL {}", url))?;
+        if url.cannot_be_a_base() {
+            bail!("Invalid REMOTE_SIGNER_URL {}", url);
+        }
+        Ok(Self {
+            url,
+            token,
+            http: reqwest::Client::builder()
+                .connect_timeout(REMOTE_SIGNER_CONNECT_TIMEOUT)
+                .timeout(REMOTE_SIGNER_TIMEOUT)
+                .build()?,
+        })
+    }
+
+    /// URL of the service with `segments` appended, each percent-encoded.
+    fn endpoint(&self, segments: &[&str]) -> reqwest::Url {
+        let mut url = self.url.clone();
+        url.path_segments_mut()
+            .expect("checked in RemoteSigner::new")
+            .pop_if_empty()
+            .extend(segments);
+        url
+    }
+
+    fn authorized(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl KeyBackend for RemoteSigner {
+    async fn public_key(&self, alias: &str) -> anyhow::Result<PublicKey> {
+        let response: RemotePublicKeyResponse = self
+            .authorized(self.http.get(self.endpoint(&["v1", "keys", alias])))
+            .send()
+            .await?
+            .error_for_status()
+            .context("Remote signer rejected the public key request")?
+            .json()
+            .await?;
+
+        PublicKey::decode_base64(&response.public_key)
+            .map_err(|e| anyhow!("Invalid public key from remote signer: {}", e))
+    }
+
+    async fn sign(&self, alias: &str, data: &TransactionData) -> anyhow::Result<Signature> {
+        let request = RemoteSignRequest {
+            alias: alias.to_string(),
+            tx_data: Base64::encode(bcs::to_bytes(data)?),
+        };
+        let response: RemoteSignResponse = self
+            .authorized(self.http.post(self.endpoint(&["v1", "sign"])))
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()
+            .context("Remote signer rejected the sign request")?
+            .json()
+            .await?;
+
+        let bytes = Base64::decode(&response.signature)
+            .map_err(|e| anyhow!("Invalid signature encoding from remote signer: {}", e))?;
+        Signature::from_bytes(&bytes)
+            .map_err(|e| anyhow!("Invalid signature from remote signer: {}", e))
+    }
+}
+
+/// Name of the env var holding the private key of `alias`.
+pub fn secret_key_var(alias: &str) -> String {
+    match alias {
+        ROOT_AUTH_ALIAS => "ROOT_AUTH_SECRET_KEY".to_string(),
+        MANUFACTURER_ALIAS => "MANUFACTURER_SECRET_KEY".to_string(),
+        GAS_STATION_ALIAS => "GAS_STATION_SECRET_KEY".to_string(),
+        other => format!("{}_SECRET_KEY", other.to_uppercase().replace('-', "_")),
+    }
+}
+
+/// Reads a secret from `var`, or from the file named by `<var>_FILE`.
+pub fn read_secret(var: &str) -> anyhow::Result<String> {
+    if let Ok(value) = std::env::var(var) {
+        return Ok(value);
+    }
+    let file_var = format!("{}_FILE", var);
+    let path = std::env::var(&file_var).with_context(|| format!("{} must be set", var))?;
+    std::fs::read_to_string(&path)
+        .map(|content| content.trim_end().to_string())
+        .with_context(|| format!("Error reading {} from {}", var, path))
+}
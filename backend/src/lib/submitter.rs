@@ -0,0 +1,264 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use iota_sdk::{
+    rpc_types::IotaObjectDataOptions,
+    types::{
+        base_types::{IotaAddress, ObjectRef},
+        programmable_transaction_builder::ProgrammableTransactionBuilder,
+        transaction::{Argument, Command, TransactionData},
+    },
+    IotaClient,
+};
+use tokio::sync::{Mutex, OnceCell, Semaphore};
+
+use crate::metrics::{record_gas, record_transaction, submission, time_chain_call};
+use crate::products::move_fields;
+use crate::signer::AccountSigner;
+use crate::transaction::sign_and_execute_transaction;
+
+pub const DEFAULT_GAS_POOL_SIZE: usize = 4;
+
+/// Coins below this balance are not worth keeping in a gas pool.
+const MIN_GAS_COIN_BALANCE: u64 = 50_000_000;
+const SPLIT_GAS_BUDGET: u64 = 10_000_000;
+const MAX_SUBMIT_ATTEMPTS: usize = 3;
+const RETRY_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Submits transactions on behalf of the backend signers.
+///
+/// Every signer gets its own lane holding a pool of gas coins. A submission
+/// borrows one coin for its whole lifetime, so two transactions of the same
+/// signer never race for the same gas object, while transactions holding
+/// different coins run in parallel. A signer with a single coin is therefore
+/// fully serialised.
+pub struct TxSubmitter {
+    client: IotaClient,
+    pool_size: usize,
+    /// Lanes by signer, prepared once on first use. The map is only locked
+    /// to look up the cell, so preparing one lane never blocks the others.
+    lanes: Mutex<HashMap<IotaAddress, Arc<OnceCell<Arc<SignerLane>>>>>,
+}
+
+struct SignerLane {
+    /// Gas coins along with their balance, to tell the gas spent.
+    coins: Mutex<VecDeque<(ObjectRef, u64)>>,
+    available: Semaphore,
+}
+
+impl TxSubmitter {
+    pub fn new(client: IotaClient, pool_size: usize) -> Self {
+        Self {
+            client,
+            pool_size: pool_size.max(1),
+            lanes: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Runs `op` with a gas coin reserved for `signer` and returns its result.
+    ///
+    /// `op` may be invoked several times: equivocation and object version
+    /// conflicts are retried with a refreshed gas coin reference. The final
+    /// outcome and the gas spent are exported as metrics.
+    pub async fn submit<T, F, Fut>(&self, signer: &AccountSigner, op: F) -> anyhow::Result<T>
+    where
+        F: Fn(ObjectRef) -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        let lane = self.lane(signer).await?;
+
+        let mut attempt = 1;
+        loop {
+            let _permit = lane.available.acquire().await.context("Gas pool closed")?;
+            let (gas_coin, balance) = lane
+                .coins
+                .lock()
+                .await
+                .pop_front()
+                .context("Gas pool is empty")?;
+
+            let result = submission(op(gas_coin)).await;
+
+            // The coin version changes with every execution, even a failed one
+            let refreshed = match self.refresh(gas_coin).await {
+                Ok((refreshed, refreshed_balance)) => {
+                    record_gas(signer.alias(), balance.saturating_sub(refreshed_balance));
+                    (refreshed, refreshed_balance)
+                }
+                Err(_) => (gas_coin, balance),
+            };
+            lane.coins.lock().await.push_back(refreshed);
+
+            match result {
+                Err(err) if attempt < MAX_SUBMIT_ATTEMPTS && is_retryable(&err) => {
+                    eprintln!(
+                        "⚠️ Transaction of {} conflicted (attempt {}/{}): {:#}",
+                        signer.alias(),
+                        attempt,
+                        MAX_SUBMIT_ATTEMPTS,
+                        err
+                    );
+                    attempt += 1;
+                    tokio::time::sleep(RETRY_BACKOFF * attempt as u32).await;
+                }
+                result => {
+                    record_outcome(signer.alias(), &result);
+                    return result;
+                }
+            }
+        }
+    }
+
+    async fn lane(&self, signer: &AccountSigner) -> anyhow::Result<Arc<SignerLane>> {
+        let cell = self
+            .lanes
+            .lock()
+            .await
+            .entry(signer.address())
+            .or_default()
+            .clone();
+
+        // Concurrent first submissions wait for a single preparation, a failed
+        // one leaves the cell empty for the next submission to retry
+        let lane = cell
+            .get_or_try_init(|| async {
+                let coins = self.prepare_gas_coins(signer).await?;
+                println!(
+                    "⛽ Gas pool for {} ready with {} coins",
+                    signer.alias(),
+                    coins.len()
+                );
+                Ok::<_, anyhow::Error>(Arc::new(SignerLane {
+                    available: Semaphore::new(coins.len()),
+                    coins: Mutex::new(coins.into()),
+                }))
+            })
+            .await?;
+        Ok(lane.clone())
+    }
+
+    /// Returns up to `pool_size` gas coins, splitting the largest coin of the
+    /// signer when it does not own enough of them.
+    ///
+    /// The largest coin pays for the split itself, so the transaction only
+    /// touches that one coin: `SplitCoins(GasCoin, ..)` carves the missing
+    /// coins out of it and transfers them back to the signer.
+    async fn prepare_gas_coins(
+        &self,
+        signer: &AccountSigner,
+    ) -> anyhow::Result<Vec<(ObjectRef, u64)>> {
+        let sender = signer.address();
+        let mut coins = self.gas_coins(sender).await?;
+
+        if coins.len() < self.pool_size {
+            let (largest, balance) = *coins
+                .first()
+                .context(format!("No gas coins owned by {}", sender))?;
+            // The gas coin keeps an equal share besides the split ones
+            let spendable = balance.saturating_sub(SPLIT_GAS_BUDGET);
+            let missing = (self.pool_size - coins.len())
+                .min((spendable / MIN_GAS_COIN_BALANCE).saturating_sub(1) as usize);
+            if missing == 0 {
+                return Ok(coins);
+            }
+            let amount = spendable / (missing as u64 + 1);
+
+            let mut ptb = ProgrammableTransactionBuilder::new();
+            let amounts = (0..missing)
+                .map(|_| ptb.pure(amount))
+                .collect::<Result<Vec<_>, _>>()?;
+            let Argument::Result(split) =
+                ptb.command(Command::SplitCoins(Argument::GasCoin, amounts))
+            else {
+                unreachable!("commands always return a result argument");
+            };
+            let split_coins = (0..missing as u16)
+                .map(|index| Argument::NestedResult(split, index))
+                .collect();
+            ptb.transfer_args(sender, split_coins);
+
+            let gas_price = time_chain_call(
+                "get_reference_gas_price",
+                self.client.read_api().get_reference_gas_price(),
+            )
+            .await?;
+            let tx_data = TransactionData::new_programmable(
+                sender,
+                vec![largest],
+                ptb.finish(),
+                SPLIT_GAS_BUDGET,
+                gas_price,
+            );
+            sign_and_execute_transaction(&self.client, signer, tx_data)
+                .await
+                .context("Failed to split gas coins")?;
+
+            coins = self.gas_coins(sender).await?;
+        }
+
+        Ok(coins.into_iter().take(self.pool_size).collect())
+    }
+
+    /// Gas coins of `sender` usable for the pool, largest first.
+    async fn gas_coins(&self, sender: IotaAddress) -> anyhow::Result<Vec<(ObjectRef, u64)>> {
+        let mut coins = time_chain_call(
+            "get_coins",
+            self.client
+                .coin_read_api()
+                .get_coins(sender, None, None, None),
+        )
+        .await?
+        .data
+        .into_iter()
+        .filter(|coin| coin.balance >= MIN_GAS_COIN_BALANCE)
+        .map(|coin| (coin.object_ref(), coin.balance))
+        .collect::<Vec<_>>();
+        coins.sort_by(|(_, a), (_, b)| b.cmp(a));
+        Ok(coins)
+    }
+
+    /// Current reference and balance of a gas coin.
+    async fn refresh(&self, coin: ObjectRef) -> anyhow::Result<(ObjectRef, u64)> {
+        let response = time_chain_call(
+            "get_object",
+            self.client
+                .read_api()
+                .get_object_with_options(coin.0, IotaObjectDataOptions::new().with_content()),
+        )
+        .await?;
+        let object_ref = response
+            .object_ref_if_exists()
+            .context(format!("Gas coin {} no longer exists", coin.0))?;
+        let balance = move_fields(&response)?["balance"]
+            .as_str()
+            .and_then(|balance| balance.parse().ok())
+            .context(format!("Gas coin {} has no balance", coin.0))?;
+        Ok((object_ref, balance))
+    }
+}
+
+/// Counts the final outcome of a submission, failures by Move abort code.
+fn record_outcome<T>(alias: &str, result: &anyhow::Result<T>) {
+    let error = result.as_ref().err().map(|err| format!("{:#}", err));
+    record_transaction(alias, error.as_deref());
+}
+
+/// Whether a failed submission lost a race for an object and may be retried.
+fn is_retryable(err: &anyhow::Error) -> bool {
+    let message = format!("{:#}", err);
+    [
+        "equivocat",
+        "ObjectVersionUnavailableForConsumption",
+        "is not available for consumption",
+        "already locked by a different transaction",
+        "ObjectLockConflict",
+    ]
+    .iter()
+    .any(|pattern| message.contains(pattern))
+}
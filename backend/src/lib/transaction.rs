@@ -2,36 +2,64 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anyhow::Result;
-use iota_config::{iota_config_dir, IOTA_KEYSTORE_FILENAME};
-use iota_json_rpc_types::IotaTransactionBlockResponse;
-use iota_keys::keystore::{AccountKeystore, FileBasedKeystore};
+use iota_json_rpc_types::{
+    IotaExecutionStatus, IotaTransactionBlockEffectsAPI, IotaTransactionBlockResponse,
+};
 use iota_sdk::{
     rpc_types::IotaTransactionBlockResponseOptions,
     types::{
-        base_types::IotaAddress,
         quorum_driver_types::ExecuteTransactionRequestType,
         transaction::{Transaction, TransactionData},
     },
     IotaClient,
 };
-use shared_crypto::intent::Intent;
+
+use crate::metrics::{in_submission, record_gas, record_transaction, time_chain_call};
+use crate::signer::AccountSigner;
 
 pub async fn sign_and_execute_transaction(
     client: &IotaClient,
-    sender: &IotaAddress,
+    signer: &AccountSigner,
     tx_data: TransactionData,
 ) -> Result<IotaTransactionBlockResponse, anyhow::Error> {
-    let keystore = FileBasedKeystore::new(&iota_config_dir()?.join(IOTA_KEYSTORE_FILENAME))?;
-    let signature = keystore.sign_secure(sender, &tx_data, Intent::iota_transaction())?;
+    let signature = signer.sign_transaction(&tx_data).await?;
 
-    let transaction_block_response = client
-        .quorum_driver_api()
-        .execute_transaction_block(
+    let transaction_block_response = time_chain_call(
+        "execute_transaction_block",
+        client.quorum_driver_api().execute_transaction_block(
             Transaction::from_data(tx_data, vec![signature]),
             IotaTransactionBlockResponseOptions::full_content(),
             ExecuteTransactionRequestType::WaitForLocalExecution,
-        )
-        .await?;
+        ),
+    )
+    .await;
+
+    // The TxSubmitter records its submissions once, retries included
+    if !in_submission() {
+        record_execution(signer.alias(), &transaction_block_response);
+    }
+
+    Ok(transaction_block_response?)
+}
 
-    Ok(transaction_block_response)
-}
\ No newline at end of file
+/// Counts the outcome and the gas of a transaction executed by `alias`.
+fn record_execution<E: std::fmt::Display>(
+    alias: &str,
+    result: &Result<IotaTransactionBlockResponse, E>,
+) {
+    let effects = match result {
+        Ok(response) => response.effects.as_ref(),
+        Err(err) => return record_transaction(alias, Some(&format!("{:#}", err))),
+    };
+    let Some(effects) = effects else {
+        return record_transaction(alias, None);
+    };
+    match effects.status() {
+        IotaExecutionStatus::Success => record_transaction(alias, None),
+        IotaExecutionStatus::Failure { error } => record_transaction(alias, Some(error)),
+    }
+    record_gas(
+        alias,
+        effects.gas_cost_summary().net_gas_usage().max(0) as u64,
+    );
+}
@@ -0,0 +1,330 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context};
+use iota_json_rpc_types::{IotaExecutionStatus, IotaTransactionBlockEffectsAPI};
+use iota_sdk::{
+    types::base_types::{IotaAddress, ObjectID},
+    IotaClient,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::data::{append_record, lock_data, read_records};
+use crate::identity::TEST_GAS_BUDGET;
+use crate::keystore::ROOT_AUTH_ALIAS;
+use crate::lcc::{self, object_arg, LccConfig, LccTransaction};
+use crate::signer::{account_signer, AccountSigner};
+use crate::transaction::sign_and_execute_transaction;
+
+pub const TREASURY_AUDIT_FILE: &str = "lcc_audit.jsonl";
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Custody and mint limits of the LCC treasury cap, read from the environment.
+///
+/// The cap is held by `LCC_TREASURY_ALIAS` (default: `root-auth`). Mints above
+/// `LCC_MINT_LIMIT_PER_OPERATION` base units, or beyond
+/// `LCC_MINT_LIMIT_PER_DAY` base units per UTC day, are rejected. Unset limits
+/// are not enforced.
+#[derive(Clone, Debug)]
+pub struct TreasuryConfig {
+    pub alias: String,
+    pub limit_per_operation: Option<u64>,
+    pub limit_per_day: Option<u64>,
+}
+
+impl TreasuryConfig {
+    pub fn from_env() -> anyhow::Result<Self> {
+        let limit = |name: &str| -> anyhow::Result<Option<u64>> {
+            std::env::var(name)
+                .ok()
+                .filter(|value| !value.is_empty())
+                .map(|value| value.parse().with_context(|| format!("Invalid {}", name)))
+                .transpose()
+        };
+        Ok(Self {
+            alias: std::env::var("LCC_TREASURY_ALIAS")
+                .ok()
+                .filter(|alias| !alias.is_empty())
+                .unwrap_or_else(|| ROOT_AUTH_ALIAS.to_string()),
+            limit_per_operation: limit("LCC_MINT_LIMIT_PER_OPERATION")?,
+            limit_per_day: limit("LCC_MINT_LIMIT_PER_DAY")?,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TreasuryOperation {
+    Mint,
+    Burn,
+    TransferCap,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditStatus {
+    /// Appended before submitting and superseded by the outcome. Left alone,
+    /// the process stopped while the transaction was in flight: check the
+    /// chain for it. Pending mints count toward the daily limit.
+    Pending,
+    Success,
+    /// Refused before submitting, e.g. over a mint limit.
+    Rejected,
+    Failed,
+}
+
+/// A line of the treasury audit log.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AuditRecord {
+    /// Shared by the pending record of an operation and its outcome.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Unix seconds.
+    pub timestamp: u64,
+    pub operation: TreasuryOperation,
+    /// Who requested the operation, e.g. the CLI user or the reward pool watcher.
+    pub operator: String,
+    /// Keystore alias and address that signed the transaction.
+    pub alias: String,
+    pub signer: IotaAddress,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coin_id: Option<ObjectID>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recipient: Option<IotaAddress>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
+    pub status: AuditStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+fn now() -> anyhow::Result<u64> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs())
+}
+
+/// Every operation of the treasury audit log, oldest first, in the state of
+/// its latest record.
+pub fn audit_log() -> anyhow::Result<Vec<AuditRecord>> {
+    let mut log: Vec<AuditRecord> = Vec::new();
+    for record in read_records::<AuditRecord>(TREASURY_AUDIT_FILE)? {
+        let previous = record.id.as_ref().and_then(|id| {
+            log.iter()
+                .position(|existing| existing.id.as_ref() == Some(id))
+        });
+        match previous {
+            Some(index) => log[index] = record,
+            None => log.push(record),
+        }
+    }
+    Ok(log)
+}
+
+/// LCC base units minted since midnight UTC, including mints still pending.
+pub fn minted_today() -> anyhow::Result<u64> {
+    let day_start = now()? / SECONDS_PER_DAY * SECONDS_PER_DAY;
+    Ok(audit_log()?
+        .iter()
+        .filter(|record| {
+            record.operation == TreasuryOperation::Mint
+                && matches!(record.status, AuditStatus::Success | AuditStatus::Pending)
+                && record.timestamp >= day_start
+        })
+        .filter_map(|record| record.amount)
+        .sum())
+}
+
+/// Signer of the treasury alias, after checking that it holds the treasury cap.
+async fn custodian(client: &IotaClient, config: &TreasuryConfig) -> anyhow::Result<AccountSigner> {
+    let cap_id = LccConfig::from_env()?.treasury_cap_id()?;
+    let signer = account_signer(&config.alias).await?;
+    let (_, owner) = object_arg(client, cap_id, true).await?;
+    if owner != signer.address() {
+        bail!(
+            "Treasury cap {} is held by {}, not by LCC_TREASURY_ALIAS {} ({})",
+            cap_id,
+            owner,
+            config.alias,
+            signer.address()
+        );
+    }
+    Ok(signer)
+}
+
+/// Mints `amount` base units of LCC to the treasury alias within the mint limits.
+///
+/// Every attempt, including rejected ones, is recorded in the audit log with
+/// `operator`. The audit log is locked from the limit check until the outcome
+/// is recorded, so concurrent mints of any process cannot both pass the check.
+pub async fn treasury_mint(
+    client: &IotaClient,
+    operator: &str,
+    amount: u64,
+    gas: Option<ObjectID>,
+) -> anyhow::Result<LccTransaction> {
+    let config = TreasuryConfig::from_env()?;
+    let signer = custodian(client, &config).await?;
+    let _lock = lock_data(TREASURY_AUDIT_FILE).await?;
+
+    let mut record = AuditRecord {
+        id: Some(uuid::Uuid::new_v4().to_string()),
+        timestamp: now()?,
+        operation: TreasuryOperation::Mint,
+        operator: operator.to_string(),
+        alias: config.alias.clone(),
+        signer: signer.address(),
+        amount: Some(amount),
+        coin_id: None,
+        recipient: None,
+        digest: None,
+        status: AuditStatus::Rejected,
+        error: None,
+    };
+
+    let rejection = match (config.limit_per_operation, config.limit_per_day) {
+        (Some(limit), _) if amount > limit => Some(format!(
+            "Mint of {} exceeds the limit of {} per operation",
+            amount, limit
+        )),
+        (_, Some(limit)) => {
+            let minted = minted_today()?;
+            (minted.saturating_add(amount) > limit).then(|| {
+                format!(
+                    "Mint of {} exceeds the daily limit of {}, {} minted today",
+                    amount, limit, minted
+                )
+            })
+        }
+        _ => None,
+    };
+    if let Some(error) = rejection {
+        record.error = Some(error.clone());
+        append_record(TREASURY_AUDIT_FILE, &record)?;
+        bail!(error);
+    }
+
+    record_pending(&mut record)?;
+    let result = lcc::mint(client, &signer, amount, gas).await;
+    record_outcome(record, &result);
+    result
+}
+
+/// Burns the LCC coin `coin_id` of the treasury alias.
+pub async fn treasury_burn(
+    client: &IotaClient,
+    operator: &str,
+    coin_id: ObjectID,
+    gas: Option<ObjectID>,
+) -> anyhow::Result<LccTransaction> {
+    let config = TreasuryConfig::from_env()?;
+    let signer = custodian(client, &config).await?;
+    let _lock = lock_data(TREASURY_AUDIT_FILE).await?;
+
+    let mut record = AuditRecord {
+        id: Some(uuid::Uuid::new_v4().to_string()),
+        timestamp: now()?,
+        operation: TreasuryOperation::Burn,
+        operator: operator.to_string(),
+        alias: config.alias.clone(),
+        signer: signer.address(),
+        amount: None,
+        coin_id: Some(coin_id),
+        recipient: None,
+        digest: None,
+        status: AuditStatus::Pending,
+        error: None,
+    };
+    record_pending(&mut record)?;
+    let result = lcc::burn(client, &signer, coin_id, gas).await;
+    record_outcome(record, &result);
+    result
+}
+
+/// Transfers the treasury cap from the treasury alias to `recipient`, e.g. the multisig address.
+///
+/// The backend cannot mint or burn afterwards.
+pub async fn transfer_treasury_cap(
+    client: &IotaClient,
+    operator: &str,
+    recipient: IotaAddress,
+    gas: Option<ObjectID>,
+) -> anyhow::Result<LccTransaction> {
+    let config = TreasuryConfig::from_env()?;
+    let signer = custodian(client, &config).await?;
+    let cap_id = LccConfig::from_env()?.treasury_cap_id()?;
+    let _lock = lock_data(TREASURY_AUDIT_FILE).await?;
+
+    let mut record = AuditRecord {
+        id: Some(uuid::Uuid::new_v4().to_string()),
+        timestamp: now()?,
+        operation: TreasuryOperation::TransferCap,
+        operator: operator.to_string(),
+        alias: config.alias.clone(),
+        signer: signer.address(),
+        amount: None,
+        coin_id: None,
+        recipient: Some(recipient),
+        digest: None,
+        status: AuditStatus::Pending,
+        error: None,
+    };
+    record_pending(&mut record)?;
+    let result = async {
+        let tx_data = client
+            .transaction_builder()
+            .transfer_object(signer.address(), cap_id, gas, TEST_GAS_BUDGET, recipient)
+            .await?;
+        let response = sign_and_execute_transaction(client, &signer, tx_data).await?;
+        if let Some(effects) = &response.effects {
+            if let IotaExecutionStatus::Failure { error } = effects.status() {
+                bail!("Treasury cap transfer failed on chain: {}", error);
+            }
+        }
+        println!(
+            "🔐 Transferred treasury cap {} to {}: {}",
+            cap_id, recipient, response.digest
+        );
+        Ok(LccTransaction {
+            digest: response.digest.to_string(),
+            coin_id: None,
+        })
+    }
+    .await;
+    record_outcome(record, &result);
+    result
+}
+
+/// Appends `record` as pending before its transaction is submitted.
+///
+/// An operation that cannot be audited is not submitted.
+fn record_pending(record: &mut AuditRecord) -> anyhow::Result<()> {
+    record.status = AuditStatus::Pending;
+    append_record(TREASURY_AUDIT_FILE, record)
+        .context("Cannot write treasury audit log, the operation was not submitted")
+}
+
+/// Appends the outcome of `record`, superseding its pending record.
+///
+/// A failed write is only logged, the transaction has been executed already
+/// and the pending record stays in the log.
+fn record_outcome(mut record: AuditRecord, result: &anyhow::Result<LccTransaction>) {
+    match result {
+        Ok(transaction) => {
+            record.status = AuditStatus::Success;
+            record.digest = Some(transaction.digest.clone());
+            if record.operation == TreasuryOperation::Mint {
+                record.coin_id = transaction.coin_id;
+            }
+        }
+        Err(err) => {
+            record.status = AuditStatus::Failed;
+            record.error = Some(format!("{:#}", err));
+        }
+    }
+    if let Err(err) = append_record(TREASURY_AUDIT_FILE, &record) {
+        eprintln!("❌ Cannot write treasury audit log: {:#} {:?}", err, record);
+    }
+}
@@ -0,0 +1,508 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context};
+use hierarchies::core::types::{property_name::PropertyName, property_value::PropertyValue};
+use identity_ecdsa_verifier::EcDSAJwsVerifier;
+use identity_eddsa_verifier::EdDSAJwsVerifier;
+use identity_iota::core::Object;
+use identity_iota::credential::{
+    CompoundCredentialValidationError, FailFast, Jwt, JwtCredentialValidationOptions,
+    JwtCredentialValidator, JwtPresentationValidationOptions, JwtPresentationValidator,
+    JwtValidationError,
+};
+use identity_iota::did::CoreDID;
+use identity_iota::iota::IotaDocument;
+use identity_iota::resolver::Resolver;
+use identity_iota::verification::jwk::Jwk;
+use identity_iota::verification::jws::{
+    Decoder, JwsAlgorithm, JwsVerifier, SignatureVerificationError, SignatureVerificationErrorKind,
+    VerificationInput,
+};
+use iota_sdk::types::base_types::{IotaAddress, ObjectID};
+use serde::Serialize;
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use crate::identity::get_read_only_client;
+use crate::keystore::{get_hierarchies_read_only_client, MANUFACTURER_ALIAS};
+use crate::metrics::time_chain_call;
+use crate::network::iota_client;
+use crate::products::{controls_identity, method_addresses};
+use crate::signer::signer_backend;
+
+/// Verifies JWS signatures with the verifier matching their `alg` header, so
+/// documents may use Ed25519 as well as P-256 and secp256k1 methods.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MethodJwsVerifier;
+
+impl MethodJwsVerifier {
+    /// Algorithms the verifier accepts, any other method could not be verified.
+    pub const ALGORITHMS: [JwsAlgorithm; 3] = [
+        JwsAlgorithm::EdDSA,
+        JwsAlgorithm::ES256,
+        JwsAlgorithm::ES256K,
+    ];
+}
+
+impl JwsVerifier for MethodJwsVerifier {
+    fn verify(
+        &self,
+        input: VerificationInput,
+        public_key: &Jwk,
+    ) -> Result<(), SignatureVerificationError> {
+        match input.alg {
+            JwsAlgorithm::EdDSA => EdDSAJwsVerifier::default().verify(input, public_key),
+            JwsAlgorithm::ES256 | JwsAlgorithm::ES256K => {
+                EcDSAJwsVerifier::default().verify(input, public_key)
+            }
+            _ => Err(SignatureVerificationError::new(
+                SignatureVerificationErrorKind::UnsupportedAlg,
+            )),
+        }
+    }
+}
+
+/// Outcome of a single check of a validation report.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl Check {
+    pub fn new(name: &'static str, status: CheckStatus, detail: Option<String>) -> Self {
+        Self {
+            name,
+            status,
+            detail,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct CredentialReport {
+    pub valid: bool,
+    pub id: Option<String>,
+    pub issuer: Option<String>,
+    pub types: Vec<String>,
+    pub subject: Option<Value>,
+    pub checks: Vec<Check>,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JwtKind {
+    Credential,
+    Presentation,
+}
+
+/// Validation report of a VC or VP JWT.
+#[derive(Serialize, Debug, ToSchema)]
+pub struct ValidationReport {
+    pub valid: bool,
+    pub kind: JwtKind,
+    /// Holder of a presentation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub holder: Option<String>,
+    /// Checks on the presentation itself, empty for a credential.
+    pub checks: Vec<Check>,
+    pub credentials: Vec<CredentialReport>,
+}
+
+/// Optional accreditation check of the credential issuers.
+pub struct AccreditationCheck {
+    pub federation_id: ObjectID,
+    /// Account of the issuer, defaults to the manufacturer account for
+    /// `MANUFACTURER_DID`. Has to control the issuer DID or hold one of its keys.
+    pub issuer_address: Option<IotaAddress>,
+}
+
+/// A VC JWT or a VP JWT whose claims, and those of the credentials it
+/// contains, could be decoded.
+pub struct DecodedJwt {
+    jwt: String,
+    claims: Value,
+    pub kind: JwtKind,
+}
+
+impl DecodedJwt {
+    pub fn jwt(&self) -> &str {
+        &self.jwt
+    }
+
+    pub fn claims(&self) -> &Value {
+        &self.claims
+    }
+}
+
+/// Decodes a VC JWT or a VP JWT without verifying it, failing only on
+/// malformed input.
+pub fn decode_jwt(jwt: &str) -> anyhow::Result<DecodedJwt> {
+    let claims = decode_claims(jwt)?;
+    let kind = if claims.get("vp").is_some() {
+        for credential in embedded_credentials(&claims) {
+            let credential = credential
+                .as_str()
+                .context("Presentation contains a credential that is not a JWT")?;
+            decode_claims(credential)?;
+        }
+        JwtKind::Presentation
+    } else if claims.get("vc").is_some() {
+        JwtKind::Credential
+    } else {
+        bail!("JWT is neither a credential nor a presentation");
+    };
+    Ok(DecodedJwt {
+        jwt: jwt.to_string(),
+        claims,
+        kind,
+    })
+}
+
+/// Verifies a decoded JWT and the credentials it contains.
+///
+/// Failed checks are part of the report, an error means the checks could
+/// not be run, e.g. because the node is unreachable.
+pub async fn verify_jwt(
+    decoded: &DecodedJwt,
+    accreditation: Option<&AccreditationCheck>,
+) -> anyhow::Result<ValidationReport> {
+    let resolver = resolver().await?;
+
+    match decoded.kind {
+        JwtKind::Presentation => {
+            verify_presentation(&resolver, &decoded.jwt, &decoded.claims, accreditation).await
+        }
+        JwtKind::Credential => {
+            let report = verify_credential(&resolver, &decoded.jwt, accreditation).await?;
+            Ok(ValidationReport {
+                valid: report.valid,
+                kind: JwtKind::Credential,
+                holder: None,
+                checks: Vec::new(),
+                credentials: vec![report],
+            })
+        }
+    }
+}
+
+fn embedded_credentials(claims: &Value) -> Vec<Value> {
+    claims["vp"]["verifiableCredential"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default()
+}
+
+pub(crate) async fn resolver() -> anyhow::Result<Resolver<IotaDocument>> {
+    let mut resolver: Resolver<IotaDocument> = Resolver::new();
+    resolver.attach_iota_handler(get_read_only_client().await?);
+    Ok(resolver)
+}
+
+/// Claims of a JWT, without verifying its signature.
+pub(crate) fn decode_claims(jwt: &str) -> anyhow::Result<Value> {
+    let token = Decoder::new()
+        .decode_compact_serialization(jwt.as_bytes(), None)
+        .map_err(|e| anyhow!("Malformed JWT: {}", e))?;
+    serde_json::from_slice(token.claims()).context("Malformed JWT claims")
+}
+
+async fn verify_presentation(
+    resolver: &Resolver<IotaDocument>,
+    jwt: &str,
+    claims: &Value,
+    accreditation: Option<&AccreditationCheck>,
+) -> anyhow::Result<ValidationReport> {
+    let holder = claims["iss"].as_str().map(str::to_string);
+    let mut checks = Vec::new();
+
+    let holder_document = match holder.as_deref() {
+        Some(holder) => resolve_document(resolver, holder).await,
+        None => Err(anyhow!("Presentation has no holder")),
+    };
+    let holder_document = match holder_document {
+        Ok(document) => {
+            checks.push(Check::new("holder_resolution", CheckStatus::Passed, None));
+            Some(document)
+        }
+        Err(err) => {
+            checks.push(Check::new(
+                "holder_resolution",
+                CheckStatus::Failed,
+                Some(format!("{:#}", err)),
+            ));
+            None
+        }
+    };
+
+    if let Some(holder_document) = holder_document {
+        let result = JwtPresentationValidator::with_signature_verifier(MethodJwsVerifier)
+            .validate::<IotaDocument, Jwt, Object>(
+            &Jwt::from(jwt.to_string()),
+            &holder_document,
+            &JwtPresentationValidationOptions::default(),
+        );
+        match result {
+            Ok(_) => {
+                checks.push(Check::new("signature", CheckStatus::Passed, None));
+                checks.push(Check::new("expiry", CheckStatus::Passed, None));
+            }
+            Err(err) => checks.extend(error_checks(&err.presentation_validation_errors, false)),
+        }
+    } else {
+        checks.push(Check::new("signature", CheckStatus::Skipped, None));
+        checks.push(Check::new("expiry", CheckStatus::Skipped, None));
+    }
+
+    let mut credentials = Vec::new();
+    for credential in embedded_credentials(claims) {
+        let credential_jwt = credential
+            .as_str()
+            .context("Presentation contains a credential that is not a JWT")?;
+        credentials.push(verify_credential(resolver, credential_jwt, accreditation).await?);
+    }
+
+    let valid = checks
+        .iter()
+        .all(|check| check.status != CheckStatus::Failed)
+        && credentials.iter().all(|credential| credential.valid);
+    Ok(ValidationReport {
+        valid,
+        kind: JwtKind::Presentation,
+        holder,
+        checks,
+        credentials,
+    })
+}
+
+async fn verify_credential(
+    resolver: &Resolver<IotaDocument>,
+    jwt: &str,
+    accreditation: Option<&AccreditationCheck>,
+) -> anyhow::Result<CredentialReport> {
+    let claims = decode_claims(jwt)?;
+    let vc = &claims["vc"];
+    let issuer = claims["iss"].as_str().map(str::to_string);
+    let mut checks = Vec::new();
+
+    let issuer_document = match issuer.as_deref() {
+        Some(issuer) => resolve_document(resolver, issuer).await,
+        None => Err(anyhow!("Credential has no issuer")),
+    };
+    match &issuer_document {
+        Ok(issuer_document) => {
+            checks.push(Check::new("issuer_resolution", CheckStatus::Passed, None));
+
+            let result = JwtCredentialValidator::with_signature_verifier(MethodJwsVerifier)
+                .validate::<_, Object>(
+                    &Jwt::from(jwt.to_string()),
+                    issuer_document,
+                    &JwtCredentialValidationOptions::default(),
+                    FailFast::AllErrors,
+                );
+            match result {
+                Ok(_) => {
+                    checks.push(Check::new("signature", CheckStatus::Passed, None));
+                    checks.push(Check::new("expiry", CheckStatus::Passed, None));
+                    checks.push(Check::new("revocation", CheckStatus::Passed, None));
+                }
+                Err(CompoundCredentialValidationError { validation_errors }) => {
+                    checks.extend(error_checks(&validation_errors, true));
+                }
+            }
+
+            // The status is only checked when the credential has one
+            if vc.get("credentialStatus").is_none() {
+                for check in checks.iter_mut() {
+                    if check.name == "revocation" && check.status == CheckStatus::Passed {
+                        check.status = CheckStatus::Skipped;
+                        check.detail = Some("Credential has no status".to_string());
+                    }
+                }
+            }
+        }
+        Err(err) => {
+            checks.push(Check::new(
+                "issuer_resolution",
+                CheckStatus::Failed,
+                Some(format!("{:#}", err)),
+            ));
+            for name in ["signature", "expiry", "revocation"] {
+                checks.push(Check::new(name, CheckStatus::Skipped, None));
+            }
+        }
+    }
+
+    if let Some(accreditation) = accreditation {
+        checks.push(match issuer.as_deref() {
+            Some(issuer) => {
+                accreditation_check(issuer, issuer_document.as_ref().ok(), accreditation).await
+            }
+            None => Check::new("issuer_accreditation", CheckStatus::Skipped, None),
+        });
+    }
+
+    let types = match &vc["type"] {
+        Value::String(value) => vec![value.clone()],
+        Value::Array(values) => values
+            .iter()
+            .filter_map(|value| value.as_str().map(str::to_string))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    Ok(CredentialReport {
+        valid: checks
+            .iter()
+            .all(|check| check.status != CheckStatus::Failed),
+        id: claims["jti"].as_str().map(str::to_string),
+        issuer,
+        types,
+        subject: vc.get("credentialSubject").cloned(),
+        checks,
+    })
+}
+
+pub(crate) async fn resolve_document(
+    resolver: &Resolver<IotaDocument>,
+    did: &str,
+) -> anyhow::Result<IotaDocument> {
+    let did = CoreDID::from_str(did).with_context(|| format!("Invalid DID {}", did))?;
+    resolver
+        .resolve(&did)
+        .await
+        .with_context(|| format!("Failed to resolve {}", did))
+}
+
+/// Sorts validation errors into the signature, expiry and, for credentials, revocation checks.
+fn error_checks(errors: &[JwtValidationError], with_revocation: bool) -> Vec<Check> {
+    let describe = |filter: fn(&JwtValidationError) -> bool| {
+        let messages: Vec<String> = errors
+            .iter()
+            .filter(|err| filter(err))
+            .map(|err| err.to_string())
+            .collect();
+        if messages.is_empty() {
+            (CheckStatus::Passed, None)
+        } else {
+            (CheckStatus::Failed, Some(messages.join("; ")))
+        }
+    };
+
+    let (signature, signature_detail) =
+        describe(|err| !is_expiry_error(err) && !is_revocation_error(err));
+    let mut checks = vec![Check::new("signature", signature, signature_detail)];
+
+    // Without a valid signature the remaining claims were never checked
+    if signature == CheckStatus::Failed {
+        checks.push(Check::new("expiry", CheckStatus::Skipped, None));
+        if with_revocation {
+            checks.push(Check::new("revocation", CheckStatus::Skipped, None));
+        }
+        return checks;
+    }
+
+    let (expiry, expiry_detail) = describe(is_expiry_error);
+    checks.push(Check::new("expiry", expiry, expiry_detail));
+    if with_revocation {
+        let (revocation, revocation_detail) = describe(is_revocation_error);
+        checks.push(Check::new("revocation", revocation, revocation_detail));
+    }
+    checks
+}
+
+fn is_expiry_error(err: &JwtValidationError) -> bool {
+    matches!(
+        err,
+        JwtValidationError::ExpirationDate | JwtValidationError::IssuanceDate
+    )
+}
+
+fn is_revocation_error(err: &JwtValidationError) -> bool {
+    matches!(
+        err,
+        JwtValidationError::Revoked
+            | JwtValidationError::Suspended
+            | JwtValidationError::InvalidStatus(_)
+    )
+}
+
+/// Checks that the account behind `issuer` still holds the manufacturer role in the federation.
+async fn accreditation_check(
+    issuer: &str,
+    issuer_document: Option<&IotaDocument>,
+    accreditation: &AccreditationCheck,
+) -> Check {
+    match issuer_accredited(issuer, issuer_document, accreditation).await {
+        Ok(true) => Check::new("issuer_accreditation", CheckStatus::Passed, None),
+        Ok(false) => Check::new(
+            "issuer_accreditation",
+            CheckStatus::Failed,
+            Some(format!(
+                "Issuer is not accredited as manufacturer in federation {}",
+                accreditation.federation_id
+            )),
+        ),
+        Err(err) => Check::new(
+            "issuer_accreditation",
+            CheckStatus::Failed,
+            Some(format!("{:#}", err)),
+        ),
+    }
+}
+
+async fn issuer_accredited(
+    issuer: &str,
+    issuer_document: Option<&IotaDocument>,
+    accreditation: &AccreditationCheck,
+) -> anyhow::Result<bool> {
+    let issuer_address = match accreditation.issuer_address {
+        Some(address) => {
+            // Named by the client, so it has to belong to the issuer DID
+            let document = issuer_document.with_context(|| {
+                format!("Cannot tie {} to unresolved issuer {}", address, issuer)
+            })?;
+            if !method_addresses(document).contains(&address)
+                && !controls_identity(&iota_client().await?, document.id(), address).await?
+            {
+                bail!("{} neither controls nor holds a key of {}", address, issuer);
+            }
+            address
+        }
+        None if std::env::var("MANUFACTURER_DID").ok().as_deref() == Some(issuer) => {
+            signer_backend()?.address(MANUFACTURER_ALIAS).await?
+        }
+        None => {
+            return Err(anyhow!(
+                "Unknown account for issuer {}, set issuer_address",
+                issuer
+            ))
+        }
+    };
+
+    let hierarchies_client = get_hierarchies_read_only_client().await?;
+    time_chain_call(
+        "validate_property",
+        hierarchies_client.validate_property(
+            accreditation.federation_id,
+            ObjectID::from_address(issuer_address.into()),
+            PropertyName::from("role"),
+            PropertyValue::Text("manufacturer".to_string()),
+        ),
+    )
+    .await
+    .map_err(|e| anyhow!("{}", e))
+}
@@ -1,16 +1,30 @@
 // Copyright (c) IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use std::sync::Arc;
+
 use axum;
+use backend::credentials::role_credentials;
+use backend::did_resolution::CachedResolver;
+use backend::domain_linkage::{check_linked_did, run_linkage_renewal};
+use backend::gas_balances::run_gas_balance_watcher;
+use backend::network::iota_client;
+use backend::reward_pool::run_reward_pool_watcher;
+use backend::role_registry::role_registry;
+use backend::signer::signer_backend;
+use backend::submitter::TxSubmitter;
 use dotenvy::dotenv;
 use tokio::net::TcpListener;
 
 mod config;
 mod handlers;
 mod routes;
-mod services;
+mod state;
+#[cfg(test)]
+mod tests;
 
 use crate::config::config::Config;
+use crate::state::AppState;
 
 #[tokio::main]
 async fn main() {
@@ -18,7 +32,32 @@ async fn main() {
 
     let config = Config::from_env();
 
-    let app = routes::create_router();
+    // Unlock the keystore before accepting requests
+    signer_backend().expect("Error opening signer backend");
+
+    let iota_client = iota_client().await.expect("Error connecting to IOTA node");
+    let submitter = Arc::new(TxSubmitter::new(iota_client.clone(), config.gas_pool_size));
+    let state = AppState {
+        iota_client: iota_client.clone(),
+        submitter: submitter.clone(),
+        did_resolver: Arc::new(
+            CachedResolver::new(config.did_cache_ttl)
+                .await
+                .expect("Error creating DID resolver"),
+        ),
+        roles: role_registry(submitter.clone()).expect("Error creating role registry"),
+        credentials: role_credentials(submitter.clone()),
+    };
+
+    // Keep the served domain linkage credentials from expiring
+    check_linked_did().expect("Error checking domain linkage credentials");
+    tokio::spawn(run_linkage_renewal());
+    // Export the balances of the accounts paying gas
+    tokio::spawn(run_gas_balance_watcher(iota_client.clone()));
+    // Keep the reward vault funded so that logging entries does not abort
+    tokio::spawn(run_reward_pool_watcher(iota_client, submitter));
+
+    let app = routes::create_router(state);
     println!("SERVER LISTENING...");
 
     let listener = TcpListener::bind(config.tcp_listner.clone()).await.unwrap();
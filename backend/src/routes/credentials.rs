@@ -0,0 +1,18 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::handlers::credential_handler;
+use crate::state::AppState;
+
+/// Verification, open to third-party verifiers.
+pub fn public_routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new().routes(routes!(credential_handler::verify_credential))
+}
+
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(credential_handler::get_credential))
+        .routes(routes!(credential_handler::revoke))
+}
@@ -0,0 +1,18 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::handlers::did_handler;
+use crate::state::AppState;
+
+/// Resolution and domain linkage checks, open to verifiers.
+pub fn public_routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(did_handler::resolve))
+        .routes(routes!(did_handler::domain_linkage))
+}
+
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new().routes(routes!(did_handler::update_document))
+}
@@ -0,0 +1,13 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::handlers::health_handler;
+use crate::state::AppState;
+
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(health_handler::healthz))
+        .routes(routes!(health_handler::readyz))
+}
@@ -0,0 +1,11 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::handlers::metrics_handler;
+use crate::state::AppState;
+
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new().routes(routes!(metrics_handler::metrics))
+}
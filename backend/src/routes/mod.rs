@@ -1,17 +1,33 @@
 // Copyright (c) IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use std::time::Instant;
+
 use axum::{
-    extract::Request,
+    extract::{MatchedPath, Request},
     http::{HeaderMap, StatusCode},
     middleware::{self, Next},
     response::Response,
     Router,
 };
+use backend::metrics::{HTTP_REQUESTS, HTTP_REQUEST_DURATION};
+use utoipa::OpenApi;
+use utoipa_axum::router::OpenApiRouter;
 
 use crate::config::config::Config;
+use crate::state::AppState;
 
+pub mod credentials;
+pub mod dids;
+pub mod health;
+pub mod metrics;
+pub mod multisig;
+pub mod openapi;
+pub mod presentations;
+pub mod products;
+pub mod rewards;
 pub mod roles;
+pub mod well_known;
 
 async fn auth(headers: HeaderMap, request: Request, next: Next) -> Result<Response, StatusCode> {
     let config = Config::from_env();
@@ -24,10 +40,62 @@ async fn auth(headers: HeaderMap, request: Request, next: Next) -> Result<Respon
     }
 }
 
-pub fn create_router() -> Router {
-    let api_protected_routes = Router::new()
+/// Counts and times every request by method, route template and status.
+async fn track_metrics(request: Request, next: Next) -> Response {
+    // The template, not the path, so that IDs do not blow up the label values
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_default();
+    let method = request.method().to_string();
+    let start = Instant::now();
+
+    let response = next.run(request).await;
+
+    let status = response.status().as_u16().to_string();
+    let labels = [method.as_str(), route.as_str(), status.as_str()];
+    HTTP_REQUESTS.with_label_values(&labels).inc();
+    HTTP_REQUEST_DURATION
+        .with_label_values(&labels)
+        .observe(start.elapsed().as_secs_f64());
+    response
+}
+
+/// Routes of the API along with the OpenAPI document describing them.
+pub fn api_router() -> OpenApiRouter<AppState> {
+    let api_protected_routes = OpenApiRouter::new()
         .nest("/roles", roles::routes())
+        .nest("/multisig", multisig::routes())
+        .nest("/credentials", credentials::routes())
+        .nest("/dids", dids::routes())
+        .nest("/rewards", rewards::routes())
         .layer(middleware::from_fn(auth));
 
-    Router::new().merge(api_protected_routes)
+    // Public, fetched by wallets and verifiers checking the domain linkage,
+    // credentials, presentations and products, by the Prometheus scraper and
+    // by orchestrator probes. None of them writes to the chain
+    let public_routes = OpenApiRouter::new()
+        .nest("/.well-known", well_known::routes())
+        .nest("/credentials", credentials::public_routes())
+        .nest("/presentations", presentations::public_routes())
+        .nest("/dids", dids::public_routes())
+        .nest("/products", products::public_routes())
+        .merge(metrics::routes())
+        .merge(health::routes());
+
+    OpenApiRouter::with_openapi(openapi::ApiDoc::openapi())
+        .merge(public_routes)
+        .merge(api_protected_routes)
+}
+
+pub fn create_router(state: AppState) -> Router {
+    let (router, api) = api_router().split_for_parts();
+
+    // The document and its UI are public, client generators fetch it unauthenticated.
+    // Only matched routes are tracked, unknown paths would only add noise.
+    router
+        .merge(openapi::routes(api))
+        .route_layer(middleware::from_fn(track_metrics))
+        .with_state(state)
 }
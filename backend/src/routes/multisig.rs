@@ -0,0 +1,18 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::handlers::multisig_handler;
+use crate::state::AppState;
+
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(
+            multisig_handler::create_proposal,
+            multisig_handler::get_proposals
+        ))
+        .routes(routes!(multisig_handler::get_proposal))
+        .routes(routes!(multisig_handler::sign_proposal))
+        .routes(routes!(multisig_handler::execute_proposal))
+}
@@ -0,0 +1,62 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use axum::{extract::Json, routing::get, Router};
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, SecurityScheme};
+use utoipa::openapi::OpenApi as OpenApiDocument;
+use utoipa::{Modify, OpenApi};
+use utoipa_scalar::{Scalar, Servable};
+
+use crate::state::AppState;
+
+/// Base of the OpenAPI document, the paths and schemas are collected from the routes.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "DPP Demonstrator Backend",
+        description = "Roles, credentials, DIDs, product trust and rewards of the Digital Product Passport demonstrator."
+    ),
+    modifiers(&ApiKeyAuth),
+    security(("api_key" = [])),
+    tags(
+        (name = "roles", description = "Roles of accounts in IOTA Hierarchies federations"),
+        (name = "credentials", description = "Role credentials issued by the manufacturer DID"),
+        (name = "presentations", description = "Challenges and verification of role presentations"),
+        (name = "dids", description = "DID resolution, updates and domain linkage"),
+        (name = "products", description = "Trust checks of products"),
+        (name = "rewards", description = "LCC reward vault, NFT whitelist and payout ledger"),
+        (name = "multisig", description = "Federation operations of the multisig root authority"),
+        (name = "well-known", description = "Public resources of the linked domains"),
+        (name = "metrics", description = "Prometheus metrics"),
+        (name = "health", description = "Liveness and readiness probes"),
+    )
+)]
+pub struct ApiDoc;
+
+struct ApiKeyAuth;
+
+impl Modify for ApiKeyAuth {
+    fn modify(&self, openapi: &mut OpenApiDocument) {
+        openapi
+            .components
+            .get_or_insert_with(Default::default)
+            .add_security_scheme(
+                "api_key",
+                SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("x-api-key"))),
+            );
+    }
+}
+
+/// Serves `api` at `/openapi.json` and a Scalar UI rendering it at `/docs`.
+pub fn routes(api: OpenApiDocument) -> Router<AppState> {
+    let document = api.clone();
+    Router::new()
+        .route(
+            "/openapi.json",
+            get(move || {
+                let document = document.clone();
+                async move { Json(document) }
+            }),
+        )
+        .merge(Scalar::with_url("/docs", api))
+}
@@ -0,0 +1,14 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::handlers::presentation_handler;
+use crate::state::AppState;
+
+/// Challenges and verification, open to verifiers and the holder's app.
+pub fn public_routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(presentation_handler::challenge))
+        .routes(routes!(presentation_handler::verify_presentation))
+}
@@ -0,0 +1,12 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::handlers::product_handler;
+use crate::state::AppState;
+
+/// Trust checks, open to customers' apps.
+pub fn public_routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new().routes(routes!(product_handler::product_trust))
+}
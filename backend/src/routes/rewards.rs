@@ -0,0 +1,21 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::handlers::reward_handler;
+use crate::state::AppState;
+
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(reward_handler::get_vault_balance))
+        .routes(routes!(reward_handler::top_up))
+        .routes(routes!(
+            reward_handler::get_whitelist,
+            reward_handler::authorize
+        ))
+        .routes(routes!(reward_handler::authorize_one))
+        .routes(routes!(reward_handler::get_reward_nfts))
+        .routes(routes!(reward_handler::get_ledger))
+        .routes(routes!(reward_handler::export_ledger))
+}
@@ -1,10 +1,15 @@
 // Copyright (c) IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use axum::{routing::post, Router};
+use utoipa_axum::{router::OpenApiRouter, routes};
 
-use crate::handlers::permission_handler::set_role;
+use crate::handlers::permission_handler;
+use crate::state::AppState;
 
-pub fn routes() -> Router {
-    Router::new().route("/", post(set_role))
+pub fn routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(permission_handler::set_role))
+        .routes(routes!(permission_handler::revoke_role))
+        .routes(routes!(permission_handler::list_roles))
+        .routes(routes!(permission_handler::validate_role))
 }
@@ -1,21 +1,23 @@
 // Copyright (c) IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use backend::utils::{
-    faucet, KeystoreClient, GAS_STATION_ALIAS, MANUFACTURER_ALIAS, ROOT_AUTH_ALIAS,
-};
+use backend::signer::signer_backend;
+use backend::utils::{faucet, GAS_STATION_ALIAS, MANUFACTURER_ALIAS, ROOT_AUTH_ALIAS};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    let keystore_client = KeystoreClient::get_keystore();
-    let root_auth_address = keystore_client
-        .get_address(ROOT_AUTH_ALIAS.to_string())
+    let backend = signer_backend()?;
+    let root_auth_address = backend
+        .address(ROOT_AUTH_ALIAS)
+        .await
         .expect("Error get_address");
-    let manufacturer_address = keystore_client
-        .get_address(MANUFACTURER_ALIAS.to_string())
+    let manufacturer_address = backend
+        .address(MANUFACTURER_ALIAS)
+        .await
         .expect("Error get_address");
-    let gas_station_address = keystore_client
-        .get_address(GAS_STATION_ALIAS.to_string())
+    let gas_station_address = backend
+        .address(GAS_STATION_ALIAS)
+        .await
         .expect("Error get_address");
 
     faucet(root_auth_address).await.expect("Faucet error");
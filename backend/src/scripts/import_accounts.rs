@@ -2,12 +2,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use dotenvy::dotenv;
-use std::env;
 use std::fs::{self, OpenOptions};
 use std::io::Write;
 use std::path::Path;
 
 use anyhow;
+use backend::signer::{read_secret, SignerConfig};
 use backend::utils::KeystoreClient;
 use backend::utils::{MANUFACTURER_ALIAS, ROOT_AUTH_ALIAS};
 
@@ -15,12 +15,13 @@ use backend::utils::{MANUFACTURER_ALIAS, ROOT_AUTH_ALIAS};
 async fn main() -> anyhow::Result<()> {
     dotenv().ok();
 
-    let root_auth_secret_key =
-        env::var("ROOT_AUTH_SECRET_KEY").expect("ROOT_AUTH_SECRET_KEY must be set");
-    let manufacturer_secret_key =
-        env::var("MANUFACTURER_SECRET_KEY").expect("MANUFACTURER_SECRET_KEY must be set");
+    let root_auth_secret_key = read_secret("ROOT_AUTH_SECRET_KEY")?;
+    let manufacturer_secret_key = read_secret("MANUFACTURER_SECRET_KEY")?;
 
-    initialize_keystore().expect("Error initialize_keystore");
+    // The encrypted keystore is created on first save
+    if matches!(SignerConfig::from_env()?, SignerConfig::File) {
+        initialize_keystore().expect("Error initialize_keystore");
+    }
     KeystoreClient::import(root_auth_secret_key.as_str(), ROOT_AUTH_ALIAS)
         .expect("Error import root-auth");
     KeystoreClient::import(manufacturer_secret_key.as_str(), MANUFACTURER_ALIAS)
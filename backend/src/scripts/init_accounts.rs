@@ -1,16 +1,23 @@
 // Copyright (c) IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use dotenvy::dotenv;
 use std::fs::{self, OpenOptions};
 use std::io::Write;
 use std::path::Path;
 
 use anyhow;
+use backend::signer::SignerConfig;
 use backend::utils::{KeystoreClient, GAS_STATION_ALIAS, MANUFACTURER_ALIAS, ROOT_AUTH_ALIAS};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    initialize_keystore().expect("Error initialize_keystore");
+    dotenv().ok();
+
+    // The encrypted keystore is created on first save
+    if matches!(SignerConfig::from_env()?, SignerConfig::File) {
+        initialize_keystore().expect("Error initialize_keystore");
+    }
     KeystoreClient::new(ROOT_AUTH_ALIAS.to_string());
     KeystoreClient::new(MANUFACTURER_ALIAS.to_string());
     KeystoreClient::new(GAS_STATION_ALIAS.to_string());
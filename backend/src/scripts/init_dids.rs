@@ -2,65 +2,73 @@
 // SPDX-License-Identifier: Apache-2.0
 
 // generate_did_configuration.rs
+//
+// Usage:
+//   init_dids [--key-type <ed25519|p256|secp256k1|<key type>:<algorithm>>]
+use anyhow::Context;
 use dotenvy::dotenv;
 
-use identity_iota::iota_interaction::KeytoolStorage as Keytool;
-use identity_iota::storage::KeytoolStorage;
-
 use identity_iota::{
-    core::{Duration, Timestamp, ToJson, Url},
-    credential::{DomainLinkageConfiguration, DomainLinkageCredentialBuilder, Jwt},
-    storage::{JwkDocumentExt, JwsSignatureOptions},
+    core::ToJson,
+    credential::{DomainLinkageConfiguration, Jwt},
 };
 
-use backend::{identity_utils::create_did_document, utils::MANUFACTURER_ALIAS};
+use backend::{
+    domain_linkage::{issue_domain_linkages, DID_CONFIGURATION_FILE},
+    identity_utils::{create_did_document, MethodKey},
+    utils::MANUFACTURER_ALIAS,
+};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     dotenv().ok();
 
+    let mut key = MethodKey::from_env()?;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--key-type" => {
+                key = args
+                    .next()
+                    .with_context(|| format!("{} needs a value", arg))?
+                    .parse()?
+            }
+            other => anyhow::bail!("Unknown argument '{}'", other),
+        }
+    }
+
     let (manufacturer_doc, manufacturer_vm_fragment) =
-        create_did_document(MANUFACTURER_ALIAS, true).await?;
+        create_did_document(MANUFACTURER_ALIAS, true, &key).await?;
 
     let manufacturer_did = manufacturer_doc.id().clone();
-    println!("✅ Manufacturer DID created: {manufacturer_did}");
+    println!("✅ Manufacturer DID created: {manufacturer_did} ({key})");
 
     println!("🔗 LinkedDomainService added to DID");
 
-    let domain_str = std::env::var("NEXT_PUBLIC_DAPP_URL")?;
-    let domain_url: Url = Url::parse(&domain_str)?;
-
-    // Create Domain Linkage Credential
-    let domain_linkage_credential = DomainLinkageCredentialBuilder::new()
-        .issuer(manufacturer_did.clone().into())
-        .origin(domain_url.clone())
-        .issuance_date(Timestamp::now_utc())
-        .expiration_date(
-            Timestamp::now_utc()
-                .checked_add(Duration::days(365))
-                .ok_or_else(|| anyhow::anyhow!("Timestamp overflow"))?,
-        )
-        .build()?;
+    // Create and sign a Domain Linkage Credential for every linked domain
+    let linked_origins =
+        issue_domain_linkages(&manufacturer_doc, &manufacturer_vm_fragment).await?;
 
-    // Sign the credential
-    let jwt: Jwt = manufacturer_doc
-        .create_credential_jwt(
-            &domain_linkage_credential,
-            &KeytoolStorage::from(Keytool::default()),
-            &manufacturer_vm_fragment,
-            &JwsSignatureOptions::default(),
-            None,
-        )
-        .await?;
+    for linked in linked_origins {
+        // Build did-configuration.json
+        let configuration_resource = DomainLinkageConfiguration::new(vec![Jwt::from(linked.jwt)]);
+        let configuration_json = configuration_resource.to_json()?;
 
-    // Build did-configuration.json
-    let configuration_resource = DomainLinkageConfiguration::new(vec![jwt]);
-    let configuration_json = configuration_resource.to_json()?;
+        println!(
+            "✅ DID Configuration JSON for {} (served by the backend at /.well-known/did-configuration.json):\n{configuration_json}",
+            linked.origin
+        );
+    }
+    println!("💾 Saved to ../data/{DID_CONFIGURATION_FILE}");
 
-    // Output the JSON string (you can save it to file if needed)
-    println!(
-        "✅ DID Configuration JSON (to serve at /.well-known/did-configuration.json):\n{configuration_json}"
-    );
+    // The server signs and serves linkages for MANUFACTURER_DID only
+    if std::env::var("MANUFACTURER_DID").ok().as_deref()
+        != Some(manufacturer_did.to_string().as_str())
+    {
+        println!(
+            "⚠️ Set MANUFACTURER_DID={manufacturer_did}, the server refuses to start otherwise"
+        );
+    }
 
     Ok(())
 }
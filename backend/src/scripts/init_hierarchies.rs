@@ -8,7 +8,8 @@ use std::io::Write;
 use std::path::Path;
 
 use anyhow::Context;
-use backend::utils::{get_hierarchies_client, KeystoreClient};
+use backend::signer::signer_backend;
+use backend::utils::get_hierarchies_client;
 use backend::utils::{MANUFACTURER_ALIAS, ROOT_AUTH_ALIAS};
 use hierarchies::core::types::{
     property::FederationProperty, property_name::PropertyName, property_value::PropertyValue,
@@ -20,14 +21,16 @@ use serde_json::json;
 async fn main() -> anyhow::Result<()> {
     dotenv().ok();
     let hierarchies_client = get_hierarchies_client(ROOT_AUTH_ALIAS.to_string()).await?;
-    let keystore_client = KeystoreClient::get_keystore();
-    let manufacturer_address = keystore_client
-        .get_address(MANUFACTURER_ALIAS.to_string())
+    let backend = signer_backend()?;
+    let manufacturer_address = backend
+        .address(MANUFACTURER_ALIAS)
+        .await
         .expect("Error get address: MANUFACTURER_ALIAS");
     let manufacturer_object_id = ObjectID::from_address(manufacturer_address.into());
 
-    let root_auth_address = keystore_client
-        .get_address(ROOT_AUTH_ALIAS.to_string())
+    let root_auth_address = backend
+        .address(ROOT_AUTH_ALIAS)
+        .await
         .expect("Error get address: ROOT_AUTH_ALIAS");
     let _root_auth_object_id = ObjectID::from_address(root_auth_address.into());
 
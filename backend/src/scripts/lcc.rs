@@ -0,0 +1,104 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Usage:
+//   lcc [--operator <name>] mint <amount>
+//   lcc [--operator <name>] burn <coin_id>
+//   lcc [--operator <name>] transfer-cap [address]
+//   lcc supply
+//   lcc audit
+//   lcc [--alias <alias>] top-up <product_id> <amount> [coin_id]
+//   lcc value <product_id>
+//
+// Amounts are in base units, one LCC is 10^9 base units. mint, burn and
+// transfer-cap are signed by LCC_TREASURY_ALIAS and recorded in the audit log
+// with the operator, which defaults to LCC_OPERATOR or the OS user.
+// transfer-cap defaults to the multisig address.
+use dotenvy::dotenv;
+
+use anyhow::{bail, Context};
+use backend::lcc::{read_dpp_value, top_up_dpp, total_supply};
+use backend::multisig::MultisigCommittee;
+use backend::network::iota_client;
+use backend::signer::account_signer;
+use backend::treasury::{
+    audit_log, transfer_treasury_cap, treasury_burn, treasury_mint, TreasuryConfig,
+};
+use iota_sdk::types::base_types::{IotaAddress, ObjectID};
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenv().ok();
+
+    let mut alias = TreasuryConfig::from_env()?.alias;
+    let mut operator = std::env::var("LCC_OPERATOR")
+        .or_else(|_| std::env::var("USER"))
+        .unwrap_or_else(|_| "unknown".to_string());
+    let mut args: Vec<String> = Vec::new();
+    let mut raw_args = std::env::args().skip(1);
+    while let Some(arg) = raw_args.next() {
+        match arg.as_str() {
+            "--alias" => alias = raw_args.next().context("--alias needs a value")?,
+            "--operator" => operator = raw_args.next().context("--operator needs a value")?,
+            _ => args.push(arg),
+        }
+    }
+    let arg = |index: usize| -> anyhow::Result<&str> {
+        args.get(index)
+            .map(String::as_str)
+            .with_context(|| format!("Missing argument {}", index))
+    };
+
+    let client = iota_client().await?;
+    match arg(0)? {
+        "mint" => {
+            let minted = treasury_mint(&client, &operator, arg(1)?.parse()?, None).await?;
+            if let Some(coin_id) = minted.coin_id {
+                println!("🪙 Minted coin: {}", coin_id);
+            }
+        }
+        "burn" => {
+            treasury_burn(
+                &client,
+                &operator,
+                ObjectID::from_hex_literal(arg(1)?)?,
+                None,
+            )
+            .await?;
+        }
+        "transfer-cap" => {
+            let recipient: IotaAddress = match args.get(1) {
+                Some(address) => address.parse()?,
+                None => MultisigCommittee::from_env()?.address()?,
+            };
+            transfer_treasury_cap(&client, &operator, recipient, None).await?;
+        }
+        "supply" => println!("Total supply: {}", total_supply(&client).await?),
+        "audit" => {
+            for record in audit_log()? {
+                println!("{}", serde_json::to_string(&record)?);
+            }
+        }
+        "top-up" => {
+            let signer = account_signer(&alias).await?;
+            let product = ObjectID::from_hex_literal(arg(1)?)?;
+            let coin = args
+                .get(3)
+                .map(|coin| ObjectID::from_hex_literal(coin))
+                .transpose()?;
+            top_up_dpp(&client, &signer, product, arg(2)?.parse()?, coin, None).await?;
+            if let Some(value) = read_dpp_value(&client, product).await? {
+                println!("🔎 Locked value of {}: {}", product, value);
+            }
+        }
+        "value" => {
+            let product = ObjectID::from_hex_literal(arg(1)?)?;
+            match read_dpp_value(&client, product).await? {
+                Some(value) => println!("Locked value of {}: {}", product, value),
+                None => bail!("Product {} has no reward vault entry", product),
+            }
+        }
+        other => bail!("Unknown command '{}'", other),
+    }
+    Ok(())
+}
@@ -0,0 +1,71 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Usage:
+//   manage_did [--did <did>] [--alias <alias>] [--dry-run] <command>
+//
+// Commands:
+//   add-method <ed25519|p256|secp256k1|<key type>:<algorithm>> [fragment]
+//   remove-method <fragment>
+//   add-service <fragment> <type> <endpoint>
+//   remove-service <fragment>
+//   deactivate
+use dotenvy::dotenv;
+
+use anyhow::{bail, Context};
+use backend::did_lifecycle::{update_did, DidUpdate};
+use backend::utils::MANUFACTURER_ALIAS;
+use identity_iota::core::Url;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenv().ok();
+
+    let mut did = std::env::var("MANUFACTURER_DID").ok();
+    let mut alias = MANUFACTURER_ALIAS.to_string();
+    let mut dry_run = false;
+    let mut args = Vec::new();
+
+    let mut raw_args = std::env::args().skip(1);
+    while let Some(arg) = raw_args.next() {
+        match arg.as_str() {
+            "--did" => did = Some(raw_args.next().context("--did needs a value")?),
+            "--alias" => alias = raw_args.next().context("--alias needs a value")?,
+            "--dry-run" => dry_run = true,
+            _ => args.push(arg),
+        }
+    }
+    let did = did.context("Pass --did or set MANUFACTURER_DID")?;
+    let arg = |index: usize| -> anyhow::Result<&str> {
+        args.get(index)
+            .map(String::as_str)
+            .with_context(|| format!("Missing argument {}", index))
+    };
+
+    let update = match arg(0)? {
+        "add-method" => DidUpdate::AddMethod {
+            key_type: arg(1)?.parse()?,
+            fragment: args.get(2).cloned(),
+        },
+        "remove-method" => DidUpdate::RemoveMethod {
+            fragment: arg(1)?.to_string(),
+        },
+        "add-service" => DidUpdate::AddService {
+            fragment: arg(1)?.to_string(),
+            service_type: arg(2)?.to_string(),
+            endpoint: Url::parse(arg(3)?)?,
+        },
+        "remove-service" => DidUpdate::RemoveService {
+            fragment: arg(1)?.to_string(),
+        },
+        "deactivate" => DidUpdate::Deactivate,
+        other => bail!("Unknown command '{}'", other),
+    };
+
+    let result = update_did(&alias, &did, update, dry_run).await?;
+    if !result.submitted {
+        println!("Dry run, nothing submitted");
+    }
+
+    Ok(())
+}
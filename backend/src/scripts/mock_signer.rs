@@ -0,0 +1,107 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Local stand-in for a remote signing service, backed by raw keys from the env.
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use dotenvy::dotenv;
+use fastcrypto::encoding::{Base64, Encoding};
+use fastcrypto::traits::EncodeDecodeBase64;
+use iota_sdk::types::transaction::TransactionData;
+use tokio::net::TcpListener;
+
+use backend::signer::{
+    KeyBackend, RawKeyBackend, RemotePublicKeyResponse, RemoteSignRequest, RemoteSignResponse,
+};
+
+#[derive(Clone)]
+struct MockSigner {
+    backend: Arc<RawKeyBackend>,
+    token: Option<String>,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenv().ok();
+
+    let listener =
+        std::env::var("MOCK_SIGNER_LISTENER").unwrap_or_else(|_| "127.0.0.1:3101".to_string());
+    let app = router(
+        Arc::new(RawKeyBackend::from_env()?),
+        std::env::var("REMOTE_SIGNER_TOKEN").ok(),
+    );
+
+    println!("MOCK SIGNER LISTENING ON {}...", listener);
+    axum::serve(TcpListener::bind(listener).await?, app).await?;
+
+    Ok(())
+}
+
+/// The signing service API over `backend`, requiring `token` when set.
+pub fn router(backend: Arc<RawKeyBackend>, token: Option<String>) -> Router {
+    Router::new()
+        .route("/v1/keys/{alias}", get(public_key))
+        .route("/v1/sign", post(sign))
+        .with_state(MockSigner { backend, token })
+}
+
+fn authorize(state: &MockSigner, headers: &HeaderMap) -> Result<(), StatusCode> {
+    let Some(token) = &state.token else {
+        return Ok(());
+    };
+    match headers.get("authorization") {
+        Some(value) if value == format!("Bearer {}", token).as_str() => Ok(()),
+        _ => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+async fn public_key(
+    State(state): State<MockSigner>,
+    headers: HeaderMap,
+    Path(alias): Path<String>,
+) -> Result<Json<RemotePublicKeyResponse>, StatusCode> {
+    authorize(&state, &headers)?;
+
+    let public_key = state
+        .backend
+        .public_key(&alias)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+
+    Ok(Json(RemotePublicKeyResponse {
+        public_key: public_key.encode_base64(),
+    }))
+}
+
+async fn sign(
+    State(state): State<MockSigner>,
+    headers: HeaderMap,
+    Json(request): Json<RemoteSignRequest>,
+) -> Result<Json<RemoteSignResponse>, StatusCode> {
+    authorize(&state, &headers)?;
+
+    let tx_data: TransactionData = Base64::decode(&request.tx_data)
+        .ok()
+        .and_then(|bytes| bcs::from_bytes(&bytes).ok())
+        .ok_or(StatusCode::BAD_REQUEST)?;
+
+    let signature = state
+        .backend
+        .sign(&request.alias, &tx_data)
+        .await
+        .map_err(|err| {
+            eprintln!("Error signing for {}: {:?}", request.alias, err);
+            StatusCode::NOT_FOUND
+        })?;
+    println!("✍️ Signed transaction for {}", request.alias);
+
+    Ok(Json(RemoteSignResponse {
+        signature: Base64::encode(signature.as_ref()),
+    }))
+}
@@ -0,0 +1,93 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Usage:
+//   multisig address
+//   multisig enroll [federation_id]
+//   multisig retire-root [federation_id]
+//   multisig propose <operation.json>
+//   multisig list | show <id> | export <id> <file>
+//   multisig sign <id> <alias>
+//   multisig add-signature <id> <signature | file>
+//   multisig execute <id>
+use dotenvy::dotenv;
+
+use anyhow::{bail, Context};
+use backend::data::federation_id;
+use backend::multisig::{
+    add_signature, enroll, execute, list_proposals, load_proposal, propose, retire_single_key_root,
+    sign_with_alias, FederationOperation, MultisigCommittee,
+};
+use iota_sdk::types::base_types::ObjectID;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenv().ok();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let arg = |index: usize| -> anyhow::Result<&str> {
+        args.get(index)
+            .map(String::as_str)
+            .with_context(|| format!("Missing argument {}", index))
+    };
+
+    match arg(0)? {
+        "address" => println!("{}", MultisigCommittee::from_env()?.address()?),
+        "enroll" => {
+            let federation_id = match args.get(1) {
+                Some(id) => ObjectID::from_hex_literal(id)?,
+                None => federation_id()?,
+            };
+            enroll(federation_id).await?;
+        }
+        "retire-root" => {
+            let federation_id = match args.get(1) {
+                Some(id) => ObjectID::from_hex_literal(id)?,
+                None => federation_id()?,
+            };
+            retire_single_key_root(federation_id).await?;
+        }
+        "propose" => {
+            let operation: FederationOperation =
+                serde_json::from_str(&std::fs::read_to_string(arg(1)?)?)?;
+            let proposal = propose(operation).await?;
+            println!("Payload to sign:\n{}", proposal.tx_bytes);
+        }
+        "list" => {
+            for proposal in list_proposals()? {
+                println!(
+                    "{} {}/{} {}",
+                    proposal.id,
+                    proposal.signed_weight,
+                    proposal.threshold,
+                    proposal.executed_digest.as_deref().unwrap_or("pending")
+                );
+            }
+        }
+        "show" => println!(
+            "{}",
+            serde_json::to_string_pretty(&load_proposal(arg(1)?)?)?
+        ),
+        "export" => {
+            // Hand the file to a committee member for `iota keytool sign --data`
+            std::fs::write(arg(2)?, load_proposal(arg(1)?)?.tx_bytes)?;
+            println!("✅ Payload of {} written to {}", arg(1)?, arg(2)?);
+        }
+        "sign" => {
+            sign_with_alias(arg(1)?, arg(2)?).await?;
+        }
+        "add-signature" => {
+            let signature = match std::fs::read_to_string(arg(2)?) {
+                Ok(content) => content,
+                Err(_) => arg(2)?.to_string(),
+            };
+            add_signature(arg(1)?, &signature).await?;
+        }
+        "execute" => {
+            execute(arg(1)?).await?;
+        }
+        other => bail!("Unknown command '{}'", other),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Usage:
+//   reward_ledger sync
+//   reward_ledger export [--recipient <address>] [--product <product_id>] [--from <time>] [--to <time>]
+//
+// Times are unix milliseconds, RFC 3339 timestamps or YYYY-MM-DD dates.
+// `export` syncs first and prints the payouts as CSV.
+use dotenvy::dotenv;
+
+use anyhow::{bail, Context};
+use backend::network::iota_client;
+use backend::reward_ledger::{parse_time, payouts_csv, query_ledger, sync_ledger, LedgerQuery};
+use iota_sdk::types::base_types::ObjectID;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenv().ok();
+
+    let mut query = LedgerQuery::default();
+    let mut args: Vec<String> = Vec::new();
+    let mut raw_args = std::env::args().skip(1);
+    while let Some(arg) = raw_args.next() {
+        let mut value = || {
+            raw_args
+                .next()
+                .with_context(|| format!("{} needs a value", arg))
+        };
+        match arg.as_str() {
+            "--recipient" => query.recipient = Some(value()?.parse()?),
+            "--product" => query.product = Some(ObjectID::from_hex_literal(&value()?)?),
+            "--from" => query.from = Some(parse_time(&value()?)?),
+            "--to" => query.to = Some(parse_time(&value()?)?),
+            _ => args.push(arg),
+        }
+    }
+
+    let client = iota_client().await?;
+    match args.first().map(String::as_str) {
+        Some("sync") => {
+            let added = sync_ledger(&client).await?;
+            eprintln!("📒 Added {} payouts to the reward ledger", added);
+        }
+        Some("export") => {
+            sync_ledger(&client).await?;
+            print!("{}", payouts_csv(&query_ledger(&query)?));
+        }
+        Some(other) => bail!("Unknown command '{}'", other),
+        None => bail!("Missing command, expected sync or export"),
+    }
+    Ok(())
+}
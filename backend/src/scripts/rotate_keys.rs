@@ -0,0 +1,48 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Usage: rotate_keys <alias> [federation_id...] | rotate_keys <alias> --abort | rotate_keys <alias> --status
+use dotenvy::dotenv;
+
+use backend::data::federation_id;
+use backend::rotation::{abort_rotation, load_rotation, run_rotation, start_rotation};
+use backend::utils::MANUFACTURER_ALIAS;
+use iota_sdk::types::base_types::ObjectID;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenv().ok();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let alias = args
+        .first()
+        .cloned()
+        .unwrap_or_else(|| MANUFACTURER_ALIAS.to_string());
+
+    match args.get(1).map(String::as_str) {
+        Some("--abort") => abort_rotation(&alias).await?,
+        Some("--status") => match load_rotation(&alias)? {
+            Some(state) => println!("{}", serde_json::to_string_pretty(&state)?),
+            None => println!("No rotation recorded for '{}'", alias),
+        },
+        _ => {
+            let federations = if args.len() > 1 {
+                args[1..]
+                    .iter()
+                    .map(|id| ObjectID::from_hex_literal(id))
+                    .collect::<Result<Vec<_>, _>>()?
+            } else {
+                vec![federation_id()?]
+            };
+
+            let mut state = start_rotation(&alias, &federations)?;
+            if let Err(err) = run_rotation(&mut state).await {
+                eprintln!("❌ Rotation interrupted: {:?}", err);
+                eprintln!("Run the command again to resume, or with --abort to roll back.");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    Ok(())
+}
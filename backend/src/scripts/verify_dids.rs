@@ -1,79 +1,73 @@
 // Copyright (c) IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use std::str::FromStr;
-
 // validate_domain_linkage.rs
+//
+// Usage:
+//   verify_dids [--did <did>] [--domain <url>...] [--file <path> | --url <url>]
 use dotenvy::dotenv;
 
-use identity_ecdsa_verifier::EcDSAJwsVerifier;
-use identity_iota::credential::{
-    DomainLinkageConfiguration, DomainLinkageValidationError, JwtCredentialValidationOptions,
-    JwtDomainLinkageValidator, LinkedDomainService,
-};
-use identity_iota::iota::IotaDID;
-use identity_iota::{core::Url, iota::IotaDocument, resolver::Resolver};
+use anyhow::Context;
+use identity_iota::core::Url;
 
-use backend::{identity_utils::get_client, utils::MANUFACTURER_ALIAS};
+use backend::domain_linkage::{verify_domain_linkage, ConfigurationSource};
+use backend::verification::CheckStatus;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     dotenv().ok();
 
-    // Read domain from env
-    let domain_str =
-        std::env::var("NEXT_PUBLIC_DAPP_URL").expect("Missing env var NEXT_PUBLIC_DAPP_URL");
-    let domain_url: Url = Url::parse(&domain_str.clone())?;
-
-    let did_str = std::env::var("MANUFACTURER_DID").expect("Missing env var MANUFACTURER_DID");
-    let did = IotaDID::from_str(&did_str).expect("Error: IotaDID::from_str");
-
-    let identity_client = get_client(MANUFACTURER_ALIAS)
-        .await
-        .expect("Error: get_client");
-
-    let mut resolver: Resolver<IotaDocument> = Resolver::new();
-    resolver.attach_iota_handler((*identity_client).clone());
+    let mut did = std::env::var("MANUFACTURER_DID").ok();
+    let mut domains = Vec::new();
+    let mut source = ConfigurationSource::Domain;
 
-    let did_document: IotaDocument = resolver.resolve(&did).await?;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .with_context(|| format!("{} needs a value", arg))
+        };
+        match arg.as_str() {
+            "--did" => did = Some(value()?),
+            "--domain" => domains.push(Url::parse(value()?)?),
+            "--file" => source = ConfigurationSource::File(value()?),
+            "--url" => source = ConfigurationSource::Url(value()?),
+            other => anyhow::bail!("Unknown argument '{}'", other),
+        }
+    }
+    let did = did.context("Pass --did or set MANUFACTURER_DID")?;
 
-    let linked_domain_services: Vec<LinkedDomainService> = did_document
-        .service()
-        .iter()
-        .cloned()
-        .filter_map(|service| LinkedDomainService::try_from(service).ok())
-        .collect();
-    assert_eq!(linked_domain_services.len(), 1);
+    // Read domain from env
+    if domains.is_empty() {
+        if let Ok(domain_str) = std::env::var("NEXT_PUBLIC_DAPP_URL") {
+            domains.push(Url::parse(&domain_str)?);
+        }
+    }
 
-    let domains: &[Url] = linked_domain_services
-        .first()
-        .ok_or_else(|| anyhow::anyhow!("expected a domain"))?
-        .domains();
-    let domain_from_did: Url = domains
-        .first()
-        .ok_or_else(|| anyhow::anyhow!("expected a domain"))?
-        .clone();
-    //assert_eq!(domain_from_did, domain_str.clone());
+    let report = verify_domain_linkage(&did, &domains, &source).await?;
 
-    let configuration_resource =
-        DomainLinkageConfiguration::fetch_configuration(domain_url.clone()).await?;
+    println!("DID: {}", report.did);
+    println!("Linked domains: {}", report.linked_domains.join(", "));
+    for domain in &report.domains {
+        println!("\n{}", domain.domain);
+        for check in &domain.checks {
+            let mark = match check.status {
+                CheckStatus::Passed => "✅",
+                CheckStatus::Failed => "❌",
+                CheckStatus::Skipped => "⏭️",
+            };
+            match &check.detail {
+                Some(detail) => println!("  {} {}: {}", mark, check.name, detail),
+                None => println!("  {} {}", mark, check.name),
+            }
+        }
+    }
 
-    // Validate the linkage.
-    let validation_result: Result<(), DomainLinkageValidationError> =
-        JwtDomainLinkageValidator::with_signature_verifier(EcDSAJwsVerifier::default())
-            .validate_linkage(
-                &did_document,
-                &configuration_resource,
-                &domain_from_did,
-                &JwtCredentialValidationOptions::default(),
-            );
-    if validation_result.is_ok() {
-        println!("✅ Successfull domain linkage validation");
+    if report.valid {
+        println!("\n✅ Successfull domain linkage validation");
+        Ok(())
     } else {
-        println!("❌ Unsuccessfull domain linkage validation");
-        println!("{:?}", validation_result);
-        println!("{}", did_document)
+        println!("\n❌ Unsuccessfull domain linkage validation");
+        std::process::exit(1);
     }
-
-    Ok(())
 }
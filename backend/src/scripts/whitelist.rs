@@ -0,0 +1,82 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+// Usage:
+//   whitelist [--alias <alias>] authorize <address> [address...]
+//   whitelist list
+//   whitelist nfts <address>
+//
+// The signer must own the REWARD_ADMIN_CAP, REWARD_ADMIN_ALIAS by default.
+use dotenvy::dotenv;
+
+use anyhow::{bail, Context};
+use backend::network::iota_client;
+use backend::nft_rewards::{authorize_addresses, list_authorized, reward_nfts, NftRewardConfig};
+use backend::signer::account_signer;
+use iota_sdk::types::base_types::IotaAddress;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenv().ok();
+
+    let mut alias = None;
+    let mut args: Vec<String> = Vec::new();
+    let mut raw_args = std::env::args().skip(1);
+    while let Some(arg) = raw_args.next() {
+        match arg.as_str() {
+            "--alias" => alias = Some(raw_args.next().context("--alias needs a value")?),
+            _ => args.push(arg),
+        }
+    }
+    let arg = |index: usize| -> anyhow::Result<&str> {
+        args.get(index)
+            .map(String::as_str)
+            .with_context(|| format!("Missing argument {}", index))
+    };
+
+    let client = iota_client().await?;
+    match arg(0)? {
+        "authorize" => {
+            arg(1)?;
+            let addresses = args[1..]
+                .iter()
+                .map(|address| address.parse::<IotaAddress>())
+                .collect::<Result<Vec<_>, _>>()?;
+            let alias = match alias {
+                Some(alias) => alias,
+                None => NftRewardConfig::from_env()?.admin_alias,
+            };
+            let signer = account_signer(&alias).await?;
+            let result = authorize_addresses(&client, &signer, &addresses, None).await?;
+            for address in &result.authorized {
+                println!("✅ Authorized {}", address);
+            }
+            for address in &result.already_authorized {
+                println!("⏭️ Already authorized {}", address);
+            }
+        }
+        "list" => {
+            for entry in list_authorized(&client).await? {
+                let status = if entry.has_minted {
+                    "minted"
+                } else {
+                    "not minted"
+                };
+                println!("{} ({})", entry.account, status);
+            }
+        }
+        "nfts" => {
+            let address: IotaAddress = arg(1)?.parse()?;
+            for nft in reward_nfts(&client, address).await? {
+                match nft.minted_at {
+                    Some(minted_at) => {
+                        println!("{} {} minted at {}", nft.object_id, nft.name, minted_at)
+                    }
+                    None => println!("{} {}", nft.object_id, nft.name),
+                }
+            }
+        }
+        other => bail!("Unknown command '{}'", other),
+    }
+    Ok(())
+}
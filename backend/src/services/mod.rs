@@ -1,4 +0,0 @@
-// Copyright (c) IOTA Stiftung
-// SPDX-License-Identifier: Apache-2.0
-
-pub mod blockchain;
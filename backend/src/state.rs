@@ -0,0 +1,19 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Arc;
+
+use backend::credentials::RoleCredentials;
+use backend::did_resolution::CachedResolver;
+use backend::role_registry::RoleRegistry;
+use backend::submitter::TxSubmitter;
+use iota_sdk::IotaClient;
+
+#[derive(Clone)]
+pub struct AppState {
+    pub iota_client: IotaClient,
+    pub submitter: Arc<TxSubmitter>,
+    pub did_resolver: Arc<CachedResolver>,
+    pub roles: Arc<dyn RoleRegistry>,
+    pub credentials: Arc<dyn RoleCredentials>,
+}
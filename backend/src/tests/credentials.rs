@@ -0,0 +1,36 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use reqwest::StatusCode;
+use serde_json::json;
+
+use super::harness::TestApp;
+
+#[tokio::test]
+async fn verify_rejects_malformed_jwt() {
+    let app = TestApp::start().await;
+
+    let (status, body) = app
+        .post("/credentials/verify", json!({ "jwt": "not-a-jwt" }))
+        .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["message"].as_str().unwrap().contains("Malformed JWT"));
+    // Nothing is resolved for input that cannot be decoded
+    assert!(app.mock.requests().is_empty());
+}
+
+#[tokio::test]
+async fn verify_is_public_but_revocation_is_not() {
+    let app = TestApp::start().await;
+
+    let status = app
+        .post_unauthenticated("/credentials/verify", json!({ "jwt": "not-a-jwt" }))
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let status = app
+        .post_unauthenticated("/credentials/not-a-credential/revoke", json!({}))
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,166 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use reqwest::StatusCode;
+use serde_json::json;
+
+use super::fixtures::{identity_object, missing_object, object_id};
+use super::harness::{TestApp, IDENTITY_PKG_ID};
+use super::mock_rpc::CHAIN_IDENTIFIER;
+
+#[tokio::test]
+async fn resolve_rejects_invalid_did() {
+    let app = TestApp::start().await;
+
+    let (status, body) = app.get("/dids/not-a-did").await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["didResolutionMetadata"]["error"], "invalidDid");
+    assert!(app.mock.requests().is_empty());
+}
+
+#[tokio::test]
+async fn resolve_reports_missing_did() {
+    let app = TestApp::start().await;
+    let did_object = object_id(6);
+    app.mock
+        .respond("iota_getObject", missing_object(did_object));
+
+    let did = format!("did:iota:{}:{}", CHAIN_IDENTIFIER, did_object);
+    let (status, body) = app.get(&format!("/dids/{}", did)).await;
+
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["didResolutionMetadata"]["error"], "notFound");
+    assert!(body["didDocument"].is_null());
+}
+
+#[tokio::test]
+async fn resolve_reports_node_failure_as_internal_error() {
+    let app = TestApp::start().await;
+    // Without a canned response the mock answers iota_getObject with an RPC error
+    let did = format!("did:iota:{}:{}", CHAIN_IDENTIFIER, object_id(27));
+
+    let (status, body) = app.get(&format!("/dids/{}", did)).await;
+
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR, "{}", body);
+    assert_eq!(body["didResolutionMetadata"]["error"], "internalError");
+}
+
+#[tokio::test]
+async fn resolve_returns_published_document() {
+    let app = TestApp::start().await;
+    let did_object = object_id(23);
+    let did = format!("did:iota:{}:{}", CHAIN_IDENTIFIER, did_object);
+    app.mock.respond_object(
+        did_object,
+        identity_object(
+            did_object,
+            &format!("{}::identity::Identity", IDENTITY_PKG_ID),
+            json!({
+                "doc": {
+                    "id": "did:0:0",
+                    "service": [{
+                        "id": "did:0:0#dpp-api",
+                        "type": "DppApi",
+                        "serviceEndpoint": "https://api.example.com/",
+                    }],
+                },
+                "meta": {
+                    "created": "2024-06-10T06:13:20Z",
+                    "updated": "2024-06-10T06:13:20Z",
+                },
+            }),
+        ),
+    );
+
+    let (status, body) = app.get(&format!("/dids/{}", did)).await;
+
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["didDocument"]["id"], did);
+    assert_eq!(
+        body["didDocument"]["service"][0]["id"],
+        format!("{}#dpp-api", did)
+    );
+    assert!(body["didResolutionMetadata"]["error"].is_null());
+
+    // Resolved documents are served from the cache afterwards
+    app.mock.clear_requests();
+    let (status, body) = app.get(&format!("/dids/{}", did)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["didResolutionMetadata"]["cached"], true);
+    assert!(app.mock.requests().is_empty());
+}
+
+#[tokio::test]
+async fn domain_linkage_reports_node_failure_as_bad_gateway() {
+    let app = TestApp::start().await;
+
+    let (status, _) = app.get("/dids/not-a-did/domain-linkage").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let did = format!("did:iota:{}:{}", CHAIN_IDENTIFIER, object_id(28));
+    let (status, body) = app.get(&format!("/dids/{}/domain-linkage", did)).await;
+    assert_eq!(status, StatusCode::BAD_GATEWAY, "{}", body);
+}
+
+#[tokio::test]
+async fn domain_linkage_reads_stored_configuration() {
+    let app = TestApp::start().await;
+    let did_object = object_id(29);
+    let did = format!("did:iota:{}:{}", CHAIN_IDENTIFIER, did_object);
+    app.mock.respond_object(
+        did_object,
+        identity_object(
+            did_object,
+            &format!("{}::identity::Identity", IDENTITY_PKG_ID),
+            json!({
+                "doc": {
+                    "id": "did:0:0",
+                    "service": [{
+                        "id": "did:0:0#domain-linkage",
+                        "type": "LinkedDomains",
+                        "serviceEndpoint": "https://unlinked.example.com/",
+                    }],
+                },
+                "meta": {
+                    "created": "2024-06-10T06:13:20Z",
+                    "updated": "2024-06-10T06:13:20Z",
+                },
+            }),
+        ),
+    );
+
+    let (status, body) = app
+        .get(&format!("/dids/{}/domain-linkage?source=stored", did))
+        .await;
+
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["valid"], false);
+    let checks = &body["domains"][0]["checks"];
+    assert_eq!(checks[0]["status"], "passed");
+    assert_eq!(checks[1]["name"], "configuration");
+    assert!(checks[1]["detail"]
+        .as_str()
+        .unwrap()
+        .starts_with("No stored Domain Linkage Credential"));
+}
+
+#[tokio::test]
+async fn update_rejects_method_the_verifier_cannot_check() {
+    let app = TestApp::start().await;
+    let did = format!("did:iota:{}:{}", CHAIN_IDENTIFIER, object_id(6));
+
+    let (status, _) = app
+        .post(
+            &format!("/dids/{}/updates", did),
+            json!({
+                "type": "add_method",
+                "key_type": "secp256r1:RS256",
+                "dry_run": true,
+            }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(app.mock.requests().is_empty());
+}
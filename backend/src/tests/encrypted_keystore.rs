@@ -0,0 +1,55 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::PathBuf;
+
+use backend::encrypted_keystore::EncryptedKeystore;
+
+const PASSPHRASE: &str = "test-passphrase";
+
+fn keystore_path() -> PathBuf {
+    std::env::temp_dir()
+        .join(format!("dpp-keystore-{}", uuid::Uuid::new_v4()))
+        .join("iota.keystore.enc")
+}
+
+#[test]
+fn saved_keystore_is_private_and_reopens() {
+    let path = keystore_path();
+    let mut keystore = EncryptedKeystore::open(&path, PASSPHRASE).unwrap();
+    let address = keystore.generate_key("manu-fact").unwrap();
+    keystore.save().unwrap();
+
+    let reopened = EncryptedKeystore::open(&path, PASSPHRASE).unwrap();
+    assert_eq!(reopened.address("manu-fact").unwrap(), address);
+    // Only the renamed keystore is left
+    let files: Vec<_> = std::fs::read_dir(path.parent().unwrap())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(files, vec![path.file_name().unwrap().to_owned()]);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
+
+#[test]
+fn tampered_nonce_is_an_error() {
+    let path = keystore_path();
+    let mut keystore = EncryptedKeystore::open(&path, PASSPHRASE).unwrap();
+    keystore.generate_key("manu-fact").unwrap();
+    keystore.save().unwrap();
+
+    let mut file: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    file["nonce"] = "AAAA".into();
+    std::fs::write(&path, file.to_string()).unwrap();
+
+    let err = EncryptedKeystore::open(&path, PASSPHRASE)
+        .err()
+        .expect("A keystore with a 3 byte nonce was opened");
+    assert!(format!("{:#}", err).contains("nonce"), "{:#}", err);
+}
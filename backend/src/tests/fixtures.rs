@@ -0,0 +1,336 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Canned JSON-RPC responses, in the layout of the IOTA node.
+
+use iota_sdk::types::{
+    base_types::{IotaAddress, ObjectID},
+    digests::{ObjectDigest, TransactionDigest},
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+const GAS_COIN_TYPE: &str = "0x2::coin::Coin<0x2::iota::IOTA>";
+/// Gas charged by every executed transaction of the fixtures.
+pub const GAS_USED: u64 = 3_000_000;
+
+pub fn object_id(seed: u8) -> ObjectID {
+    ObjectID::new([seed; 32])
+}
+
+pub fn tx_digest(seed: u8) -> TransactionDigest {
+    TransactionDigest::new([seed; 32])
+}
+
+fn object_digest(seed: u8) -> ObjectDigest {
+    ObjectDigest::new([seed; 32])
+}
+
+/// `iota_getObject` result of a shared Move object.
+pub fn shared_object(
+    id: ObjectID,
+    object_type: &str,
+    previous_transaction: TransactionDigest,
+    fields: Value,
+) -> Value {
+    json!({
+        "data": {
+            "objectId": id,
+            "version": "3",
+            "digest": object_digest(7),
+            "type": object_type,
+            "owner": { "Shared": { "initial_shared_version": 3 } },
+            "previousTransaction": previous_transaction,
+            "content": {
+                "dataType": "moveObject",
+                "type": object_type,
+                "hasPublicTransfer": false,
+                "fields": fields,
+            },
+        }
+    })
+}
+
+/// `iota_getObject` result of a missing object.
+pub fn missing_object(id: ObjectID) -> Value {
+    json!({ "error": { "code": "notExists", "object_id": id } })
+}
+
+/// `iota_getTransactionBlock` result of an empty transaction of `sender`
+/// that created the shared object `created`.
+pub fn creation_transaction(
+    digest: TransactionDigest,
+    sender: IotaAddress,
+    created: ObjectID,
+    object_type: &str,
+) -> Value {
+    json!({
+        "digest": digest,
+        "transaction": {
+            "data": {
+                "messageVersion": "v1",
+                "transaction": {
+                    "kind": "ProgrammableTransaction",
+                    "inputs": [],
+                    "transactions": [],
+                },
+                "sender": sender,
+                "gasData": {
+                    "payment": [],
+                    "owner": sender,
+                    "price": "1000",
+                    "budget": "10000000",
+                },
+            },
+            "txSignatures": [],
+        },
+        "objectChanges": [{
+            "type": "created",
+            "sender": sender,
+            "owner": { "Shared": { "initial_shared_version": 3 } },
+            "objectType": object_type,
+            "objectId": created,
+            "version": "3",
+            "digest": object_digest(7),
+        }],
+    })
+}
+
+/// `iotax_getCoins` result of the gas coins `coins`, by ID and balance.
+pub fn coin_page(coins: &[(ObjectID, u64)]) -> Value {
+    let data: Vec<Value> = coins
+        .iter()
+        .map(|(id, balance)| {
+            json!({
+                "coinType": "0x2::iota::IOTA",
+                "coinObjectId": id,
+                "version": "5",
+                "digest": object_digest(5),
+                "balance": balance.to_string(),
+                "previousTransaction": tx_digest(5),
+            })
+        })
+        .collect();
+    json!({ "data": data, "nextCursor": null, "hasNextPage": false })
+}
+
+/// `iota_getObject` result of a gas coin of `owner` after one transaction.
+pub fn gas_coin(id: ObjectID, owner: IotaAddress, balance: u64) -> Value {
+    json!({
+        "data": {
+            "objectId": id,
+            "version": "6",
+            "digest": object_digest(6),
+            "type": GAS_COIN_TYPE,
+            "owner": { "AddressOwner": owner },
+            "previousTransaction": tx_digest(6),
+            "content": {
+                "dataType": "moveObject",
+                "type": GAS_COIN_TYPE,
+                "hasPublicTransfer": true,
+                "fields": { "balance": balance.to_string(), "id": { "id": id } },
+            },
+        }
+    })
+}
+
+/// `iota_getObject` result of a Move object of `object_type` owned by `owner`.
+pub fn owned_object(id: ObjectID, object_type: &str, owner: IotaAddress) -> Value {
+    json!({
+        "data": {
+            "objectId": id,
+            "version": "3",
+            "digest": object_digest(3),
+            "type": object_type,
+            "owner": { "AddressOwner": owner },
+            "previousTransaction": tx_digest(3),
+        }
+    })
+}
+
+/// Effects of a successful transaction of `sender` paid with `gas_coin`.
+fn success_effects(digest: TransactionDigest, sender: IotaAddress, gas_coin: ObjectID) -> Value {
+    json!({
+        "messageVersion": "v1",
+        "status": { "status": "success" },
+        "executedEpoch": "0",
+        "gasUsed": {
+            "computationCost": GAS_USED.to_string(),
+            "computationCostBurned": GAS_USED.to_string(),
+            "storageCost": "0",
+            "storageRebate": "0",
+            "nonRefundableStorageFee": "0",
+        },
+        "transactionDigest": digest,
+        "gasObject": {
+            "owner": { "AddressOwner": sender },
+            "reference": { "objectId": gas_coin, "version": "6", "digest": object_digest(6) },
+        },
+        "dependencies": [],
+    })
+}
+
+/// Data of an empty transaction of `sender`, as echoed by the node.
+fn transaction_data(sender: IotaAddress) -> Value {
+    json!({
+        "messageVersion": "v1",
+        "transaction": {
+            "kind": "ProgrammableTransaction",
+            "inputs": [],
+            "transactions": [],
+        },
+        "sender": sender,
+        "gasData": {
+            "payment": [],
+            "owner": sender,
+            "price": "1000",
+            "budget": "10000000",
+        },
+    })
+}
+
+/// `iota_dryRunTransactionBlock` result of a transaction that would succeed.
+pub fn dry_run(sender: IotaAddress, gas_coin: ObjectID) -> Value {
+    json!({
+        "effects": success_effects(tx_digest(20), sender, gas_coin),
+        "events": [],
+        "objectChanges": [],
+        "balanceChanges": [],
+        "input": transaction_data(sender),
+    })
+}
+
+/// `iota_executeTransactionBlock` result of a successful transaction.
+pub fn executed_transaction(
+    digest: TransactionDigest,
+    sender: IotaAddress,
+    gas_coin: ObjectID,
+) -> Value {
+    json!({
+        "digest": digest,
+        "transaction": { "data": transaction_data(sender), "txSignatures": [] },
+        "effects": success_effects(digest, sender, gas_coin),
+        "events": [],
+        "objectChanges": [],
+        "balanceChanges": [],
+        "confirmedLocalExecution": true,
+    })
+}
+
+/// `iota_devInspectTransactionBlock` result of a read-only call returning
+/// the BCS bytes `value` of type `value_type`.
+pub fn dev_inspect(value: Vec<u8>, value_type: &str) -> Value {
+    json!({
+        "effects": success_effects(tx_digest(21), IotaAddress::ZERO, object_id(0)),
+        "events": [],
+        "results": [{ "mutableReferenceOutputs": [], "returnValues": [[value, value_type]] }],
+    })
+}
+
+/// Move layout of `hierarchies::property_name::PropertyName`.
+#[derive(Serialize)]
+struct PropertyName {
+    names: Vec<String>,
+}
+
+/// Move layout of `hierarchies::property_value::PropertyValue`.
+#[derive(Serialize)]
+enum PropertyValue {
+    Text(String),
+}
+
+/// Move layout of `hierarchies::property::FederationProperty`.
+#[derive(Serialize)]
+struct FederationProperty {
+    name: PropertyName,
+    allowed_values: Vec<PropertyValue>,
+    shape: Option<u8>,
+    allow_any: bool,
+    valid_from_ms: Option<u64>,
+    valid_until_ms: Option<u64>,
+}
+
+/// Move layout of `hierarchies::accreditation::Accreditation`, the property
+/// map flattened into its entries.
+#[derive(Serialize)]
+struct Accreditation {
+    id: ObjectID,
+    accredited_by: String,
+    properties: Vec<(PropertyName, FederationProperty)>,
+}
+
+/// BCS bytes of the accreditations returned by
+/// `get_accreditations_to_attest`, one per value of the `property` property.
+pub fn accreditations(accredited_by: IotaAddress, property: &str, values: &[&str]) -> Vec<u8> {
+    let accreditations: Vec<Accreditation> = values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| Accreditation {
+            id: object_id(30 + index as u8),
+            accredited_by: accredited_by.to_string(),
+            properties: vec![(
+                PropertyName {
+                    names: vec![property.to_string()],
+                },
+                FederationProperty {
+                    name: PropertyName {
+                        names: vec![property.to_string()],
+                    },
+                    allowed_values: vec![PropertyValue::Text(value.to_string())],
+                    shape: None,
+                    allow_any: false,
+                    valid_from_ms: None,
+                    valid_until_ms: None,
+                },
+            )],
+        })
+        .collect();
+    bcs::to_bytes(&accreditations).expect("Error encoding accreditations")
+}
+
+/// `iota_getObject` result of an identity holding the packed DID document
+/// `document`, with `did:0:0` standing for the DID itself.
+pub fn identity_object(id: ObjectID, identity_type: &str, document: Value) -> Value {
+    let document = serde_json::to_vec(&document).unwrap();
+    // StateMetadataDocument V1: marker, version, JSON encoding, length, document
+    let mut packed = b"DID".to_vec();
+    packed.extend([1, 0]);
+    packed.extend((document.len() as u16).to_le_bytes());
+    packed.extend(document);
+
+    json!({
+        "data": {
+            "objectId": id,
+            "version": "4",
+            "digest": object_digest(4),
+            "type": identity_type,
+            "owner": { "Shared": { "initial_shared_version": 4 } },
+            "previousTransaction": tx_digest(4),
+            "content": {
+                "dataType": "moveObject",
+                "type": identity_type,
+                "hasPublicTransfer": false,
+                "fields": {
+                    "id": { "id": id },
+                    "did_doc": {
+                        "controlled_value": packed,
+                        "controllers": { "contents": [{ "key": object_id(40), "value": "1" }] },
+                        "controlled_by_delegates": { "contents": [] },
+                        "threshold": "1",
+                        "proposals": { "id": { "id": object_id(41) }, "size": "0" },
+                    },
+                    "created": "1718000000000",
+                    "updated": "1718000000000",
+                    "version": "1",
+                    "deleted": false,
+                    "deleted_did": false,
+                },
+            },
+        }
+    })
+}
+
+/// `iotax_getCoins` result of an address without coins.
+pub fn empty_coin_page() -> Value {
+    json!({ "data": [], "nextCursor": null, "hasNextPage": false })
+}
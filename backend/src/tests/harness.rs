@@ -0,0 +1,199 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::{Arc, Once};
+
+use backend::credentials::{ManufacturerRoleCredentials, NoRoleCredentials, RoleCredentials};
+use backend::did_resolution::{CachedResolver, DEFAULT_DID_CACHE_TTL};
+use backend::network::iota_client;
+use backend::role_registry::{HierarchiesRoleRegistry, InMemoryRoleRegistry, RoleRegistry};
+use backend::signer::account_signer;
+use backend::submitter::TxSubmitter;
+use fastcrypto::ed25519::Ed25519KeyPair;
+use iota_sdk::types::{
+    base_types::IotaAddress,
+    crypto::{get_key_pair_from_rng, IotaKeyPair},
+};
+use rand::{rngs::StdRng, SeedableRng};
+use reqwest::StatusCode;
+use serde_json::Value;
+use tokio::net::TcpListener;
+use tokio::sync::{Mutex, MutexGuard};
+
+use super::mock_rpc::MockRpc;
+use crate::routes::create_router;
+use crate::state::AppState;
+
+pub const API_KEY: &str = "test-api-key";
+pub const HIERARCHIES_PKG_ID: &str =
+    "0x00000000000000000000000000000000000000000000000000000000000000a1";
+pub const IDENTITY_PKG_ID: &str =
+    "0x00000000000000000000000000000000000000000000000000000000000000a2";
+pub const NOTARIZATION_PKG_ID: &str =
+    "0x00000000000000000000000000000000000000000000000000000000000000a3";
+
+/// The mock and the environment are shared, so tests run one at a time.
+static TEST_LOCK: Mutex<()> = Mutex::const_new(());
+static ENV: Once = Once::new();
+
+/// Deterministic secret key of a test account.
+fn secret_key(seed: u8) -> String {
+    let (_, keypair): (_, Ed25519KeyPair) =
+        get_key_pair_from_rng(&mut StdRng::from_seed([seed; 32]));
+    IotaKeyPair::Ed25519(keypair)
+        .encode()
+        .expect("Error encoding test key")
+}
+
+/// Points the backend at the mock node, raw test keys and a scratch data folder.
+fn init_env(mock: &MockRpc) {
+    ENV.call_once(|| {
+        let data_dir = std::env::temp_dir().join(format!("dpp-backend-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&data_dir).expect("Error creating test data folder");
+
+        // Set before the first dotenv() call, which never overrides a variable
+        for (name, value) in [
+            ("API_ENDPOINT", mock.url.clone()),
+            ("FAUCET_URL", format!("{}/gas", mock.url)),
+            ("API_KEY", API_KEY.to_string()),
+            ("TCP_LISTNER", "127.0.0.1:0".to_string()),
+            ("DATA_DIR", data_dir.display().to_string()),
+            ("SIGNER_BACKEND", "raw".to_string()),
+            ("ROOT_AUTH_SECRET_KEY", secret_key(1)),
+            ("MANUFACTURER_SECRET_KEY", secret_key(2)),
+            ("GAS_STATION_SECRET_KEY", secret_key(3)),
+            ("IOTA_HIERARCHIES_PKG_ID", HIERARCHIES_PKG_ID.to_string()),
+            ("IOTA_IDENTITY_PKG_ID", IDENTITY_PKG_ID.to_string()),
+            (
+                "IOTA_CUSTOM_NOTARIZATION_PKG_ID",
+                NOTARIZATION_PKG_ID.to_string(),
+            ),
+        ] {
+            std::env::set_var(name, value);
+        }
+    });
+}
+
+/// The backend router served on a local port, talking to the mock node.
+pub struct TestApp {
+    pub url: String,
+    pub mock: &'static MockRpc,
+    http: reqwest::Client,
+    _guard: MutexGuard<'static, ()>,
+}
+
+impl TestApp {
+    /// Starts the router with roles kept in the hierarchies contract.
+    pub async fn start() -> Self {
+        Self::launch(false).await
+    }
+
+    /// Starts the router with an in-memory role registry.
+    pub async fn start_in_memory() -> Self {
+        Self::launch(true).await
+    }
+
+    /// Starts the router with a fresh mock, the requests made while building
+    /// the clients are forgotten.
+    async fn launch(in_memory_roles: bool) -> Self {
+        let guard = TEST_LOCK.lock().await;
+        let mock = MockRpc::shared();
+        init_env(mock);
+        mock.reset();
+
+        let iota_client = iota_client().await.expect("Error connecting to mock node");
+        let submitter = Arc::new(TxSubmitter::new(iota_client.clone(), 1));
+        let (roles, credentials): (Arc<dyn RoleRegistry>, Arc<dyn RoleCredentials>) =
+            if in_memory_roles {
+                (
+                    Arc::new(InMemoryRoleRegistry::default()),
+                    Arc::new(NoRoleCredentials),
+                )
+            } else {
+                (
+                    Arc::new(HierarchiesRoleRegistry::new(submitter.clone())),
+                    Arc::new(ManufacturerRoleCredentials::new(submitter.clone())),
+                )
+            };
+        let state = AppState {
+            iota_client,
+            submitter,
+            did_resolver: Arc::new(
+                CachedResolver::new(DEFAULT_DID_CACHE_TTL)
+                    .await
+                    .expect("Error creating DID resolver"),
+            ),
+            roles,
+            credentials,
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Error binding test server");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            axum::serve(listener, create_router(state)).await.unwrap();
+        });
+        mock.clear_requests();
+
+        Self {
+            url,
+            mock,
+            http: reqwest::Client::new(),
+            _guard: guard,
+        }
+    }
+
+    /// Address of a keystore alias of the backend.
+    pub async fn address(&self, alias: &str) -> IotaAddress {
+        account_signer(alias)
+            .await
+            .expect("No test key for alias")
+            .address()
+    }
+
+    pub async fn get(&self, path: &str) -> (StatusCode, Value) {
+        self.send(self.http.get(format!("{}{}", self.url, path)))
+            .await
+    }
+
+    /// Gets a non-JSON resource, e.g. the metrics.
+    pub async fn get_text(&self, path: &str) -> (StatusCode, String) {
+        let response = self
+            .http
+            .get(format!("{}{}", self.url, path))
+            .send()
+            .await
+            .expect("Error calling test server");
+        let status = response.status();
+        (status, response.text().await.unwrap_or_default())
+    }
+
+    pub async fn post(&self, path: &str, body: Value) -> (StatusCode, Value) {
+        self.send(self.http.post(format!("{}{}", self.url, path)).json(&body))
+            .await
+    }
+
+    /// Sends `request` with the API key, the body is `Null` when not JSON.
+    async fn send(&self, request: reqwest::RequestBuilder) -> (StatusCode, Value) {
+        let response = request
+            .header("x-api-key", API_KEY)
+            .send()
+            .await
+            .expect("Error calling test server");
+        let status = response.status();
+        let body = response.json().await.unwrap_or(Value::Null);
+        (status, body)
+    }
+
+    /// Posts `body` without the API key.
+    pub async fn post_unauthenticated(&self, path: &str, body: Value) -> StatusCode {
+        self.http
+            .post(format!("{}{}", self.url, path))
+            .json(&body)
+            .send()
+            .await
+            .expect("Error calling test server")
+            .status()
+    }
+}
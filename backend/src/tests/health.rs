@@ -0,0 +1,48 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use reqwest::StatusCode;
+
+use super::harness::TestApp;
+use super::mock_rpc::CHAIN_IDENTIFIER;
+
+#[tokio::test]
+async fn healthz_answers_without_the_node() {
+    let app = TestApp::start_in_memory().await;
+
+    let (status, body) = app.get("/healthz").await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "ok");
+    assert!(app.mock.requests().is_empty());
+}
+
+#[tokio::test]
+async fn readyz_reports_each_check() {
+    let app = TestApp::start_in_memory().await;
+
+    // No federation in the scratch data folder, nor objects or balances on the mock
+    let (status, report) = app.get("/readyz").await;
+
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(report["ready"], false);
+    let check = |name: &str| {
+        report["checks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|check| check["name"] == name)
+            .unwrap_or_else(|| panic!("{} missing", name))
+            .clone()
+    };
+    let fullnode = check("fullnode");
+    assert_eq!(fullnode["status"], "passed");
+    assert!(fullnode["detail"]
+        .as_str()
+        .unwrap()
+        .contains(CHAIN_IDENTIFIER));
+    assert_eq!(check("federation")["status"], "failed");
+    assert_eq!(check("hierarchies_package")["status"], "failed");
+    assert_eq!(check("root_auth_key")["status"], "passed");
+    assert_eq!(check("manufacturer_gas")["status"], "failed");
+}
@@ -0,0 +1,33 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use reqwest::StatusCode;
+
+use super::fixtures::object_id;
+use super::harness::TestApp;
+
+#[tokio::test]
+async fn metrics_track_requests_and_chain_calls() {
+    let app = TestApp::start_in_memory().await;
+    app.get("/healthz").await;
+    app.get("/readyz").await;
+    app.get(&format!("/roles/{}/{}", object_id(1), object_id(2)))
+        .await;
+
+    let (status, metrics) = app.get_text("/metrics").await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert!(
+        metrics.contains(r#"dpp_http_requests_total{method="GET",route="/healthz",status="200"}"#)
+    );
+    // Routes are labelled by template, whatever the IDs in the path
+    assert!(metrics.contains(
+        r#"dpp_http_requests_total{method="GET",route="/roles/{federation_id}/{user_addr}",status="200"}"#
+    ));
+    assert!(metrics.contains(
+        r#"dpp_http_request_duration_seconds_count{method="GET",route="/readyz",status="503"}"#
+    ));
+    assert!(metrics.contains(
+        r#"dpp_chain_call_duration_seconds_count{call="get_chain_identifier",result="ok"}"#
+    ));
+}
@@ -0,0 +1,201 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use axum::{extract::State, routing::post, Json, Router};
+use serde_json::{json, Value};
+
+/// JSON-RPC error code of methods without a canned response.
+const METHOD_NOT_MOCKED: i64 = -32601;
+
+/// Version reported by `rpc.discover`.
+const NODE_VERSION: &str = "1.6.1";
+/// Chain identifier of the IOTA testnet.
+pub const CHAIN_IDENTIFIER: &str = "2304aa97";
+
+/// A JSON-RPC request received by the mock.
+#[derive(Clone, Debug)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub params: Value,
+}
+
+#[derive(Clone, Debug)]
+enum Reply {
+    Result(Value),
+    Error { code: i64, message: String },
+}
+
+#[derive(Default)]
+struct MockState {
+    requests: Vec<RecordedRequest>,
+    /// Replies used once, in order, before falling back to `sticky`.
+    queued: HashMap<String, VecDeque<Reply>>,
+    sticky: HashMap<String, Reply>,
+    /// `iota_getObject` replies by object ID, before any other reply.
+    objects: HashMap<String, Reply>,
+}
+
+impl MockState {
+    fn reply(&mut self, method: &str, params: &Value) -> Reply {
+        if method == "iota_getObject" {
+            if let Some(reply) = params[0].as_str().and_then(|id| self.objects.get(id)) {
+                return reply.clone();
+            }
+        }
+        if let Some(reply) = self.queued.get_mut(method).and_then(VecDeque::pop_front) {
+            return reply;
+        }
+        self.sticky
+            .get(method)
+            .cloned()
+            .unwrap_or_else(|| Reply::Error {
+                code: METHOD_NOT_MOCKED,
+                message: format!("No canned response for {}", method),
+            })
+    }
+}
+
+/// A fake IOTA JSON-RPC node answering with canned responses.
+///
+/// Every request is recorded. Methods without a canned response fail with
+/// [`METHOD_NOT_MOCKED`], so a flow stops at the first call a test did not
+/// expect instead of reaching the network.
+pub struct MockRpc {
+    pub url: String,
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockRpc {
+    /// The mock shared by the tests of this process.
+    ///
+    /// It runs on its own thread and runtime, so it outlives the runtime of
+    /// the test that started it.
+    pub fn shared() -> &'static MockRpc {
+        static MOCK: OnceLock<MockRpc> = OnceLock::new();
+        MOCK.get_or_init(|| {
+            let state = Arc::new(Mutex::new(MockState::default()));
+            let app = Router::new()
+                .route("/", post(handle))
+                .with_state(state.clone());
+            let (address_tx, address_rx) = std::sync::mpsc::channel();
+            std::thread::spawn(move || {
+                let runtime = tokio::runtime::Runtime::new().expect("Error starting mock runtime");
+                runtime.block_on(async move {
+                    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+                        .await
+                        .expect("Error binding mock RPC");
+                    address_tx
+                        .send(listener.local_addr().expect("Mock RPC has no address"))
+                        .expect("Error reporting mock RPC address");
+                    axum::serve(listener, app).await.expect("Mock RPC failed");
+                });
+            });
+            let address = address_rx.recv().expect("Mock RPC did not start");
+            MockRpc {
+                url: format!("http://{}", address),
+                state,
+            }
+        })
+    }
+
+    /// Forgets every request and response, keeping only what any client
+    /// needs to connect: `rpc.discover` and the chain identifier.
+    pub fn reset(&self) {
+        let mut state = self.state.lock().unwrap();
+        *state = MockState::default();
+        drop(state);
+        self.respond(
+            "rpc.discover",
+            json!({
+                "openrpc": "1.2.6",
+                "info": { "title": "IOTA JSON-RPC", "version": NODE_VERSION },
+                "methods": [],
+            }),
+        );
+        self.respond("iota_getChainIdentifier", json!(CHAIN_IDENTIFIER));
+        self.respond("iotax_getReferenceGasPrice", json!("1000"));
+    }
+
+    /// Answers every call of `method` with `result`.
+    pub fn respond(&self, method: &str, result: Value) {
+        self.state
+            .lock()
+            .unwrap()
+            .sticky
+            .insert(method.to_string(), Reply::Result(result));
+    }
+
+    /// Answers the next call of `method` with `result`, before any sticky response.
+    pub fn respond_once(&self, method: &str, result: Value) {
+        self.state
+            .lock()
+            .unwrap()
+            .queued
+            .entry(method.to_string())
+            .or_default()
+            .push_back(Reply::Result(result));
+    }
+
+    /// Answers every `iota_getObject` call for `object_id` with `result`,
+    /// whatever else was installed for the method.
+    pub fn respond_object(&self, object_id: impl ToString, result: Value) {
+        self.state
+            .lock()
+            .unwrap()
+            .objects
+            .insert(object_id.to_string(), Reply::Result(result));
+    }
+
+    /// Every request received since the last reset, oldest first.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Requests of `method` received since the last reset.
+    pub fn calls(&self, method: &str) -> Vec<RecordedRequest> {
+        self.requests()
+            .into_iter()
+            .filter(|request| request.method == method)
+            .collect()
+    }
+
+    /// Forgets the recorded requests, keeping the canned responses.
+    pub fn clear_requests(&self) {
+        self.state.lock().unwrap().requests.clear();
+    }
+}
+
+async fn handle(
+    State(state): State<Arc<Mutex<MockState>>>,
+    Json(body): Json<Value>,
+) -> Json<Value> {
+    let mut state = state.lock().unwrap();
+    match body {
+        Value::Array(batch) => Json(Value::Array(
+            batch
+                .into_iter()
+                .map(|request| answer(&mut state, request))
+                .collect(),
+        )),
+        request => Json(answer(&mut state, request)),
+    }
+}
+
+fn answer(state: &mut MockState, request: Value) -> Value {
+    let method = request["method"].as_str().unwrap_or_default().to_string();
+    state.requests.push(RecordedRequest {
+        method: method.clone(),
+        params: request["params"].clone(),
+    });
+    match state.reply(&method, &request["params"]) {
+        Reply::Result(result) => json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }),
+        Reply::Error { code, message } => json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "error": { "code": code, "message": message },
+        }),
+    }
+}
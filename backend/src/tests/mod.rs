@@ -0,0 +1,30 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! End-to-end tests of the API against a mock IOTA node.
+//!
+//! The router is served on a local port and every JSON-RPC call goes to
+//! [`mock_rpc::MockRpc`], which records it and answers with the responses a
+//! test installed. Nothing reaches the testnet or the faucet.
+
+mod credentials;
+mod dids;
+mod encrypted_keystore;
+mod fixtures;
+mod harness;
+mod health;
+mod metrics;
+mod mock_rpc;
+// The mock signer binary, served by the remote signer tests
+#[allow(dead_code)]
+#[path = "../scripts/mock_signer.rs"]
+mod mock_signer;
+mod multisig;
+mod openapi;
+mod presentations;
+mod products;
+mod reward_ledger;
+mod roles;
+mod signer;
+mod submitter;
+mod treasury;
@@ -0,0 +1,66 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use backend::data::write_data;
+use reqwest::StatusCode;
+use serde_json::json;
+
+use super::fixtures::{object_id, tx_digest};
+use super::harness::TestApp;
+
+#[tokio::test]
+async fn proposal_ids_cannot_escape_the_proposals_folder() {
+    let app = TestApp::start_in_memory().await;
+    write_data("outside.json", &json!({ "secret": true })).unwrap();
+
+    let (status, body) = app.get("/multisig/proposals/..%2Foutside").await;
+
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(body["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("Unknown proposal"));
+}
+
+#[tokio::test]
+async fn stray_files_do_not_hide_the_proposals() {
+    let app = TestApp::start_in_memory().await;
+    let id = tx_digest(30).to_string();
+    write_data(
+        &format!("multisig/{}.json", id),
+        &json!({
+            "id": id,
+            "operation": {
+                "type": "add_property",
+                "federation_id": object_id(1),
+                "property_name": "role",
+                "allowed_values": ["repairer"],
+            },
+            "tx_bytes": "",
+            "signatures": [],
+            "signed_weight": 0,
+            "threshold": 2,
+            "created_at": 0,
+            "executed_digest": null,
+        }),
+    )
+    .unwrap();
+    write_data(
+        &format!("multisig/{}.json", tx_digest(31)),
+        &json!("garbage"),
+    )
+    .unwrap();
+    write_data("multisig/notes.txt", &json!("not a proposal")).unwrap();
+
+    let (status, body) = app.get("/multisig/proposals").await;
+
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let ids: Vec<&str> = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|proposal| proposal["id"].as_str().unwrap())
+        .collect();
+    assert!(ids.contains(&id.as_str()));
+    assert!(!ids.contains(&tx_digest(31).to_string().as_str()));
+}
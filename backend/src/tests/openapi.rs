@@ -0,0 +1,41 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use reqwest::StatusCode;
+
+use super::harness::TestApp;
+
+#[tokio::test]
+async fn openapi_document_describes_routes() {
+    let app = TestApp::start_in_memory().await;
+
+    let (status, document) = app.get("/openapi.json").await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert!(document["openapi"].as_str().unwrap().starts_with("3.1"));
+    let paths = &document["paths"];
+    assert!(paths["/roles/revoke"]["post"].is_object());
+    assert!(paths["/products/{id}/trust"]["get"].is_object());
+    assert!(paths["/rewards/whitelist"]["get"].is_object());
+    assert!(paths["/rewards/whitelist"]["post"].is_object());
+    // Public routes opt out of the API key required by default
+    assert_eq!(
+        paths["/.well-known/did-configuration.json"]["get"]["security"],
+        serde_json::json!([{}])
+    );
+
+    let schemas = &document["components"]["schemas"];
+    for schema in [
+        "RoleRequest",
+        "RoleResponse",
+        "Role",
+        "ProductTrust",
+        "ErrorResponse",
+    ] {
+        assert!(schemas[schema].is_object(), "{} missing", schema);
+    }
+    assert_eq!(
+        document["components"]["securitySchemes"]["api_key"]["name"],
+        "x-api-key"
+    );
+}
@@ -0,0 +1,163 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use backend::data::{read_data, write_data};
+use backend::presentations::{consume_challenge, Challenge, CHALLENGES_FILE};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+
+use super::fixtures::{missing_object, object_id};
+use super::harness::TestApp;
+use super::mock_rpc::CHAIN_IDENTIFIER;
+
+const AUDIENCE: &str = "https://verifier.example.com";
+
+/// VP JWT of a holder DID without identity object, answering `nonce`.
+///
+/// The signature is never checked since the holder cannot be resolved, so
+/// every presentation fails its holder and role checks.
+fn presentation(app: &TestApp, nonce: &str) -> String {
+    let holder_object = object_id(60);
+    app.mock
+        .respond_object(holder_object, missing_object(holder_object));
+    let holder = format!("did:iota:{}:{}", CHAIN_IDENTIFIER, holder_object);
+
+    let encode = |value: Value| URL_SAFE_NO_PAD.encode(value.to_string());
+    format!(
+        "{}.{}.{}",
+        encode(json!({ "alg": "EdDSA", "kid": format!("{}#key-1", holder), "nonce": nonce })),
+        encode(json!({
+            "iss": holder,
+            "aud": AUDIENCE,
+            "vp": {
+                "@context": ["https://www.w3.org/2018/credentials/v1"],
+                "type": ["VerifiablePresentation"],
+                "verifiableCredential": [],
+            },
+        })),
+        URL_SAFE_NO_PAD.encode([7u8; 64]),
+    )
+}
+
+async fn challenge(app: &TestApp) -> String {
+    let (status, body) = app
+        .post("/presentations/challenges", json!({ "audience": AUDIENCE }))
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    body["nonce"].as_str().unwrap().to_string()
+}
+
+async fn verify(app: &TestApp, jwt: &str) -> Value {
+    let (status, body) = app
+        .post(
+            "/presentations/verify",
+            json!({ "jwt": jwt, "federation_id": object_id(61) }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    body
+}
+
+fn check<'a>(report: &'a Value, name: &str) -> &'a Value {
+    report["checks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|check| check["name"] == name)
+        .unwrap_or_else(|| panic!("No {} check in {}", name, report))
+}
+
+#[tokio::test]
+async fn failed_presentation_keeps_its_nonce() {
+    let app = TestApp::start().await;
+    let nonce = challenge(&app).await;
+    let jwt = presentation(&app, &nonce);
+
+    for _ in 0..2 {
+        let report = verify(&app, &jwt).await;
+        assert_eq!(report["valid"], false);
+        assert_eq!(check(&report, "challenge")["status"], "passed");
+        assert_eq!(check(&report, "audience")["status"], "passed");
+        assert_eq!(check(&report, "role_credential")["status"], "failed");
+    }
+
+    let challenges: Vec<Challenge> = read_data(CHALLENGES_FILE).unwrap().unwrap();
+    let stored = challenges
+        .iter()
+        .find(|challenge| challenge.nonce == nonce)
+        .unwrap();
+    assert!(!stored.used);
+}
+
+#[tokio::test]
+async fn replayed_nonce_is_rejected() {
+    let app = TestApp::start().await;
+    let nonce = challenge(&app).await;
+
+    // A verified presentation consumes the nonce once
+    assert!(consume_challenge(&nonce).await.unwrap().is_ok());
+    let replay = consume_challenge(&nonce).await.unwrap().unwrap_err();
+    assert!(replay.contains("already used"), "{}", replay);
+
+    let report = verify(&app, &presentation(&app, &nonce)).await;
+    assert_eq!(report["valid"], false);
+    let challenge = check(&report, "challenge");
+    assert_eq!(challenge["status"], "failed");
+    assert!(challenge["detail"]
+        .as_str()
+        .unwrap()
+        .contains("already used"));
+    assert_eq!(check(&report, "audience")["status"], "skipped");
+}
+
+#[tokio::test]
+async fn expired_challenge_is_rejected() {
+    let app = TestApp::start().await;
+    write_data(
+        CHALLENGES_FILE,
+        &[Challenge {
+            nonce: "expired".to_string(),
+            audience: AUDIENCE.to_string(),
+            issued_at: 1_718_000_000,
+            expires_at: 1_718_000_300,
+            used: false,
+        }],
+    )
+    .unwrap();
+
+    let report = verify(&app, &presentation(&app, "expired")).await;
+
+    assert_eq!(report["valid"], false);
+    let challenge = check(&report, "challenge");
+    assert_eq!(challenge["status"], "failed");
+    assert_eq!(challenge["detail"], "Challenge expired");
+    assert_eq!(check(&report, "audience")["status"], "skipped");
+    assert!(consume_challenge("expired").await.unwrap().is_err());
+}
+
+#[tokio::test]
+async fn verify_rejects_credential_jwt() {
+    let app = TestApp::start().await;
+    let encode = |value: Value| URL_SAFE_NO_PAD.encode(value.to_string());
+    let jwt = format!(
+        "{}.{}.{}",
+        encode(json!({ "alg": "EdDSA" })),
+        encode(json!({ "iss": "did:iota:0x1", "vc": {} })),
+        URL_SAFE_NO_PAD.encode([7u8; 64]),
+    );
+
+    let (status, body) = app
+        .post(
+            "/presentations/verify",
+            json!({ "jwt": jwt, "federation_id": object_id(61) }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+    assert!(body["message"]
+        .as_str()
+        .unwrap()
+        .contains("not a presentation"));
+    assert!(app.mock.requests().is_empty());
+}
@@ -0,0 +1,77 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use iota_sdk::types::base_types::IotaAddress;
+use reqwest::StatusCode;
+use serde_json::json;
+
+use super::fixtures::{creation_transaction, missing_object, object_id, shared_object, tx_digest};
+use super::harness::{TestApp, NOTARIZATION_PKG_ID};
+
+#[tokio::test]
+async fn product_trust_reads_product_and_creator() {
+    let app = TestApp::start().await;
+    let product = object_id(8);
+    let federation = object_id(9);
+    let digest = tx_digest(10);
+    let creator: IotaAddress = object_id(11).into();
+    let product_type = format!("{}::app::Product", NOTARIZATION_PKG_ID);
+
+    app.mock.respond_once(
+        "iota_getObject",
+        shared_object(
+            product,
+            &product_type,
+            digest,
+            json!({
+                "id": { "id": product },
+                "federation_addr": federation,
+                "name": "E-Bike Battery",
+                "serial_number": "EB-48V-2024-001337",
+                "manufacturer": "not-a-did",
+            }),
+        ),
+    );
+    app.mock.respond(
+        "iota_getTransactionBlock",
+        creation_transaction(digest, creator, product, &product_type),
+    );
+
+    let (status, body) = app.get(&format!("/products/{}/trust", product)).await;
+
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["product"]["name"], "E-Bike Battery");
+    assert_eq!(body["product"]["creator"], creator.to_string());
+    // The manufacturer field is not a DID, so the product cannot be trusted
+    assert_eq!(body["status"], "untrusted");
+    let did_resolution = body["checks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|check| check["name"] == "did_resolution")
+        .unwrap();
+    assert_eq!(did_resolution["status"], "failed");
+    assert_eq!(app.mock.calls("iota_getTransactionBlock").len(), 1);
+}
+
+#[tokio::test]
+async fn product_trust_reports_missing_product() {
+    let app = TestApp::start().await;
+    let product = object_id(12);
+    app.mock.respond("iota_getObject", missing_object(product));
+
+    let (status, body) = app.get(&format!("/products/{}/trust", product)).await;
+
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(body["message"].as_str().unwrap().contains("not found"));
+}
+
+#[tokio::test]
+async fn product_trust_reports_node_failure() {
+    let app = TestApp::start().await;
+    // Without a canned response the mock answers iota_getObject with an RPC error
+
+    let (status, body) = app.get(&format!("/products/{}/trust", object_id(28))).await;
+
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR, "{}", body);
+}
@@ -0,0 +1,123 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use backend::reward_ledger::{parse_time, payouts_csv, LedgerQuery, RewardKind, RewardPayout};
+use iota_sdk::types::base_types::IotaAddress;
+
+use super::fixtures::{object_id, tx_digest};
+
+fn payout(recipient: IotaAddress, product: u8, timestamp_ms: Option<u64>) -> RewardPayout {
+    RewardPayout {
+        digest: tx_digest(50).to_string(),
+        product: object_id(product),
+        entry: Some(object_id(51)),
+        recipient,
+        timestamp_ms,
+        reward_type: RewardKind::Lcc,
+        amount: Some(1_000_000_000),
+        nft_id: None,
+    }
+}
+
+#[test]
+fn parse_time_accepts_millis_dates_and_timestamps() {
+    assert_eq!(parse_time("1718000000000").unwrap(), 1_718_000_000_000);
+    assert_eq!(parse_time("2024-06-10").unwrap(), 1_717_977_600_000);
+    assert_eq!(
+        parse_time("2024-06-10T06:13:20Z").unwrap(),
+        1_718_000_000_000
+    );
+    assert!(parse_time("yesterday").is_err());
+    assert!(parse_time("1969-12-31").is_err());
+}
+
+#[test]
+fn query_matches_every_set_field() {
+    let recipient = IotaAddress::random_for_testing_only();
+    let payout = payout(recipient, 52, Some(2_000));
+
+    assert!(LedgerQuery::default().matches(&payout));
+    assert!(LedgerQuery {
+        recipient: Some(recipient),
+        product: Some(object_id(52)),
+        from: Some(2_000),
+        to: Some(2_001),
+    }
+    .matches(&payout));
+    assert!(!LedgerQuery {
+        recipient: Some(IotaAddress::random_for_testing_only()),
+        ..Default::default()
+    }
+    .matches(&payout));
+    assert!(!LedgerQuery {
+        product: Some(object_id(53)),
+        ..Default::default()
+    }
+    .matches(&payout));
+    assert!(!LedgerQuery {
+        from: Some(2_001),
+        ..Default::default()
+    }
+    .matches(&payout));
+    // `to` is exclusive
+    assert!(!LedgerQuery {
+        to: Some(2_000),
+        ..Default::default()
+    }
+    .matches(&payout));
+}
+
+#[test]
+fn payouts_without_timestamp_fall_before_any_range() {
+    let payout = payout(IotaAddress::random_for_testing_only(), 52, None);
+
+    assert!(!LedgerQuery {
+        from: Some(1),
+        ..Default::default()
+    }
+    .matches(&payout));
+    assert!(LedgerQuery {
+        to: Some(1),
+        ..Default::default()
+    }
+    .matches(&payout));
+}
+
+#[test]
+fn csv_has_a_header_and_a_row_per_payout() {
+    let recipient = IotaAddress::random_for_testing_only();
+    let mut nft = payout(recipient, 54, None);
+    nft.reward_type = RewardKind::Nft;
+    nft.amount = None;
+    nft.nft_id = Some(object_id(55));
+
+    let csv = payouts_csv(&[payout(recipient, 52, Some(1_718_000_000_000)), nft]);
+    let lines: Vec<&str> = csv.lines().collect();
+
+    assert_eq!(
+        lines[0],
+        "timestamp,timestamp_ms,recipient,product,entry,reward_type,amount,nft_id,digest"
+    );
+    assert_eq!(lines.len(), 3);
+    assert_eq!(
+        lines[1],
+        format!(
+            "2024-06-10T06:13:20Z,1718000000000,{},{},{},LCC,1000000000,,{}",
+            recipient,
+            object_id(52),
+            object_id(51),
+            tx_digest(50)
+        )
+    );
+    assert_eq!(
+        lines[2],
+        format!(
+            ",,{},{},{},NFT,,{},{}",
+            recipient,
+            object_id(54),
+            object_id(51),
+            object_id(55),
+            tx_digest(50)
+        )
+    );
+}
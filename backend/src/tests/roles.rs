@@ -0,0 +1,211 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use backend::utils::MANUFACTURER_ALIAS;
+use reqwest::StatusCode;
+use serde_json::json;
+
+use super::fixtures::{
+    accreditations, coin_page, dev_inspect, dry_run, empty_coin_page, executed_transaction,
+    gas_coin, object_id, shared_object, tx_digest, GAS_USED,
+};
+use super::harness::{TestApp, HIERARCHIES_PKG_ID};
+
+#[tokio::test]
+async fn set_role_requires_api_key() {
+    let app = TestApp::start().await;
+
+    let status = app
+        .post_unauthenticated(
+            "/roles",
+            json!({
+                "user_addr": object_id(4),
+                "user_role": "Manufacturer",
+                "federation_addr": object_id(5),
+            }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert!(app.mock.requests().is_empty());
+}
+
+#[tokio::test]
+async fn set_role_rejects_unknown_role() {
+    let app = TestApp::start().await;
+
+    let (status, _) = app
+        .post(
+            "/roles",
+            json!({
+                "user_addr": object_id(4),
+                "user_role": "Auditor",
+                "federation_addr": object_id(5),
+            }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(app.mock.requests().is_empty());
+}
+
+#[tokio::test]
+async fn set_role_fails_without_gas() {
+    let app = TestApp::start().await;
+    app.mock.respond("iotax_getCoins", empty_coin_page());
+
+    let (status, body) = app
+        .post(
+            "/roles",
+            json!({
+                "user_addr": object_id(4),
+                "user_role": "Manufacturer",
+                "federation_addr": object_id(5),
+            }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    let message = body["message"].as_str().unwrap();
+    assert!(
+        message.starts_with("Failed to set permission"),
+        "{}",
+        message
+    );
+    assert!(!app.mock.requests().is_empty());
+    // No accreditation may be submitted without a gas coin of the manufacturer
+    assert!(app.mock.calls("iota_executeTransactionBlock").is_empty());
+    let manufacturer = app.address(MANUFACTURER_ALIAS).await.to_string();
+    for call in app.mock.calls("iotax_getCoins") {
+        assert_eq!(call.params[0], manufacturer);
+    }
+}
+
+#[tokio::test]
+async fn set_role_grants_role_on_chain() {
+    let app = TestApp::start().await;
+    let manufacturer = app.address(MANUFACTURER_ALIAS).await;
+    let user = object_id(17);
+    let federation = object_id(18);
+    let gas = object_id(19);
+    let balance = 2_000_000_000;
+
+    app.mock
+        .respond_once("iotax_getCoins", coin_page(&[(gas, balance)]));
+    app.mock.respond_object(
+        federation,
+        shared_object(
+            federation,
+            &format!("{}::main::Federation", HIERARCHIES_PKG_ID),
+            tx_digest(18),
+            json!({ "id": { "id": federation } }),
+        ),
+    );
+    app.mock
+        .respond_object(gas, gas_coin(gas, manufacturer, balance - GAS_USED));
+    app.mock
+        .respond_once("iota_dryRunTransactionBlock", dry_run(manufacturer, gas));
+    app.mock.respond_once(
+        "iota_executeTransactionBlock",
+        executed_transaction(tx_digest(22), manufacturer, gas),
+    );
+    // The grant is checked by reading the accreditations back
+    app.mock.respond_once(
+        "iota_devInspectTransactionBlock",
+        dev_inspect(
+            accreditations(manufacturer, "role", &["manufacturer"]),
+            &format!("{}::accreditation::Accreditations", HIERARCHIES_PKG_ID),
+        ),
+    );
+
+    let (status, body) = app
+        .post(
+            "/roles",
+            json!({
+                "user_addr": user,
+                "user_role": "Manufacturer",
+                "federation_addr": federation,
+            }),
+        )
+        .await;
+
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert!(body["message"]
+        .as_str()
+        .unwrap()
+        .contains("granted Manufacturer permission"));
+    assert!(body.get("credential_error").is_none());
+    let executions = app.mock.calls("iota_executeTransactionBlock");
+    assert_eq!(executions.len(), 1);
+    // The accreditation is paid with the pooled coin of the manufacturer
+    let coins = app.mock.calls("iotax_getCoins");
+    assert_eq!(coins.len(), 1);
+    assert_eq!(coins[0].params[0], manufacturer.to_string());
+}
+
+#[tokio::test]
+async fn in_memory_roles_can_be_granted_and_revoked() {
+    let app = TestApp::start_in_memory().await;
+    let user = object_id(13);
+    let federation = object_id(14);
+    let request = json!({
+        "user_addr": user,
+        "user_role": "Manufacturer",
+        "federation_addr": federation,
+    });
+
+    let (status, _) = app.post("/roles", request.clone()).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = app.get(&format!("/roles/{}/{}", federation, user)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["roles"], json!(["Manufacturer"]));
+    let (_, body) = app
+        .get(&format!("/roles/{}/{}/Repairer", federation, user))
+        .await;
+    assert_eq!(body["valid"], false);
+
+    let (status, body) = app.post("/roles/revoke", request.clone()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["revoked_credentials"], json!([]));
+    let (_, body) = app
+        .get(&format!("/roles/{}/{}/Manufacturer", federation, user))
+        .await;
+    assert_eq!(body["valid"], false);
+
+    // Nothing left to revoke
+    let (status, _) = app.post("/roles/revoke", request).await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(app.mock.requests().is_empty());
+}
+
+#[tokio::test]
+async fn in_memory_repairer_role_comes_without_credential() {
+    let app = TestApp::start_in_memory().await;
+    let user = object_id(15);
+    let federation = object_id(16);
+    let request = json!({
+        "user_addr": user,
+        "user_role": "Repairer",
+        "federation_addr": federation,
+    });
+
+    let (status, body) = app.post("/roles", request.clone()).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert!(body.get("credential").is_none());
+
+    let (_, body) = app
+        .get(&format!("/roles/{}/{}/Repairer", federation, user))
+        .await;
+    assert_eq!(body["valid"], true);
+
+    let (status, body) = app.post("/roles/revoke", request).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["revoked_credentials"], json!([]));
+    let (_, body) = app
+        .get(&format!("/roles/{}/{}/Repairer", federation, user))
+        .await;
+    assert_eq!(body["valid"], false);
+    // Neither the role nor a credential touched the chain
+    assert!(app.mock.requests().is_empty());
+}
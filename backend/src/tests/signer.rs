@@ -0,0 +1,114 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Arc;
+
+use backend::signer::{account_signer, AccountSigner, RawKeyBackend, SignerConfig};
+use backend::utils::MANUFACTURER_ALIAS;
+use iota_sdk::types::{
+    base_types::SequenceNumber, digests::ObjectDigest, transaction::TransactionData,
+};
+use tokio::net::TcpListener;
+
+use super::fixtures::object_id;
+use super::harness::TestApp;
+use super::mock_signer;
+
+const TOKEN: &str = "test-signer-token";
+
+/// Serves the mock signer over the raw test keys and returns its URL.
+async fn start_mock_signer() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Error binding mock signer");
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let app = mock_signer::router(
+        Arc::new(RawKeyBackend::from_env().expect("Error loading test keys")),
+        Some(TOKEN.to_string()),
+    );
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    url
+}
+
+#[tokio::test]
+async fn remote_signer_signs_through_the_mock_signer() {
+    let app = TestApp::start().await;
+    let url = start_mock_signer().await;
+
+    // The environment is shared, so the remote backend is only selected while it is built
+    std::env::set_var("SIGNER_BACKEND", "remote");
+    std::env::set_var("REMOTE_SIGNER_URL", &url);
+    std::env::set_var("REMOTE_SIGNER_TOKEN", TOKEN);
+    let remote = SignerConfig::from_env().and_then(SignerConfig::into_backend);
+    std::env::set_var("SIGNER_BACKEND", "raw");
+    std::env::remove_var("REMOTE_SIGNER_URL");
+    std::env::remove_var("REMOTE_SIGNER_TOKEN");
+
+    let signer = AccountSigner::new(MANUFACTURER_ALIAS, remote.unwrap())
+        .await
+        .unwrap();
+    let sender = app.address(MANUFACTURER_ALIAS).await;
+    assert_eq!(signer.address(), sender);
+
+    let gas = (
+        object_id(25),
+        SequenceNumber::from_u64(1),
+        ObjectDigest::new([25; 32]),
+    );
+    let tx_data = TransactionData::new_transfer_iota(
+        object_id(26).into(),
+        sender,
+        Some(1_000),
+        gas,
+        10_000_000,
+        1_000,
+    );
+    let signature = signer.sign_transaction(&tx_data).await.unwrap();
+
+    // Ed25519 signatures are deterministic, so both must match byte for byte
+    let local = account_signer(MANUFACTURER_ALIAS)
+        .await
+        .unwrap()
+        .sign_transaction(&tx_data)
+        .await
+        .unwrap();
+    assert_eq!(signature.as_ref(), local.as_ref());
+}
+
+#[tokio::test]
+async fn remote_signer_requires_the_token() {
+    let _app = TestApp::start().await;
+    let url = start_mock_signer().await;
+
+    let backend = SignerConfig::Remote { url, token: None }
+        .into_backend()
+        .unwrap();
+    let err = AccountSigner::new(MANUFACTURER_ALIAS, backend)
+        .await
+        .err()
+        .expect("The mock signer answered without a token");
+
+    assert!(format!("{:#}", err).contains("401"), "{:#}", err);
+}
+
+#[tokio::test]
+async fn file_backend_rejects_keystore_passphrase() {
+    let _app = TestApp::start().await;
+
+    std::env::set_var("SIGNER_BACKEND", "file");
+    std::env::set_var("KEYSTORE_PASSPHRASE", "test-passphrase");
+    let config = SignerConfig::from_env();
+    std::env::set_var("SIGNER_BACKEND", "raw");
+    std::env::remove_var("KEYSTORE_PASSPHRASE");
+
+    let err = config
+        .err()
+        .expect("The file backend accepted a keystore passphrase");
+    assert!(
+        format!("{:#}", err).contains("SIGNER_BACKEND=encrypted"),
+        "{:#}",
+        err
+    );
+}
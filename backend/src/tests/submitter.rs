@@ -0,0 +1,134 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::anyhow;
+use backend::network::iota_client;
+use backend::signer::account_signer;
+use backend::submitter::TxSubmitter;
+use backend::utils::MANUFACTURER_ALIAS;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use iota_sdk::types::transaction::{
+    Argument, Command, TransactionData, TransactionDataAPI, TransactionKind,
+};
+
+use super::fixtures::{coin_page, executed_transaction, gas_coin, object_id, tx_digest};
+use super::harness::TestApp;
+
+const BALANCE: u64 = 2_000_000_000;
+
+/// A submitter whose manufacturer lane holds a single coin.
+async fn submitter(app: &TestApp) -> TxSubmitter {
+    let gas = object_id(24);
+    let manufacturer = app.address(MANUFACTURER_ALIAS).await;
+    app.mock
+        .respond("iotax_getCoins", coin_page(&[(gas, BALANCE)]));
+    app.mock
+        .respond_object(gas, gas_coin(gas, manufacturer, BALANCE));
+    let client = iota_client().await.expect("Error connecting to mock node");
+    app.mock.clear_requests();
+    TxSubmitter::new(client, 1)
+}
+
+#[tokio::test]
+async fn lane_is_prepared_once_per_signer() {
+    let app = TestApp::start().await;
+    let submitter = submitter(&app).await;
+    let signer = account_signer(MANUFACTURER_ALIAS).await.unwrap();
+
+    // Both first submissions wait for the same lane
+    let (first, second) = tokio::join!(
+        submitter.submit(&signer, |_| async { Ok(1) }),
+        submitter.submit(&signer, |_| async { Ok(2) }),
+    );
+    assert_eq!(first.unwrap(), 1);
+    assert_eq!(second.unwrap(), 2);
+    submitter
+        .submit(&signer, |_| async { Ok(()) })
+        .await
+        .unwrap();
+
+    assert_eq!(app.mock.calls("iotax_getCoins").len(), 1);
+}
+
+#[tokio::test]
+async fn conflicts_are_retried_with_the_refreshed_coin() {
+    let app = TestApp::start().await;
+    let submitter = submitter(&app).await;
+    let signer = account_signer(MANUFACTURER_ALIAS).await.unwrap();
+    let attempts = &AtomicUsize::new(0);
+
+    let result = submitter
+        .submit(&signer, move |_| async move {
+            match attempts.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(anyhow!("Object is not available for consumption")),
+                _ => Ok(()),
+            }
+        })
+        .await;
+
+    assert!(result.is_ok(), "{:?}", result);
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn other_failures_are_not_retried() {
+    let app = TestApp::start().await;
+    let submitter = submitter(&app).await;
+    let signer = account_signer(MANUFACTURER_ALIAS).await.unwrap();
+    let attempts = &AtomicUsize::new(0);
+
+    let result: anyhow::Result<()> = submitter
+        .submit(&signer, move |_| async move {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(anyhow!("MoveAbort in command 0"))
+        })
+        .await;
+
+    assert!(result.is_err());
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn missing_coins_are_split_from_the_gas_coin() {
+    let app = TestApp::start().await;
+    let (gas, split) = (object_id(24), object_id(25));
+    let manufacturer = app.address(MANUFACTURER_ALIAS).await;
+    app.mock
+        .respond_once("iotax_getCoins", coin_page(&[(gas, BALANCE)]));
+    app.mock.respond(
+        "iotax_getCoins",
+        coin_page(&[(gas, BALANCE / 2), (split, BALANCE / 2)]),
+    );
+    app.mock.respond_once(
+        "iota_executeTransactionBlock",
+        executed_transaction(tx_digest(22), manufacturer, gas),
+    );
+    let client = iota_client().await.expect("Error connecting to mock node");
+    app.mock.clear_requests();
+    let submitter = TxSubmitter::new(client, 2);
+    let signer = account_signer(MANUFACTURER_ALIAS).await.unwrap();
+
+    submitter
+        .submit(&signer, |_| async { Ok(()) })
+        .await
+        .unwrap();
+
+    let executions = app.mock.calls("iota_executeTransactionBlock");
+    assert_eq!(executions.len(), 1);
+    let tx_bytes = STANDARD
+        .decode(executions[0].params[0].as_str().unwrap())
+        .unwrap();
+    let tx_data: TransactionData = bcs::from_bytes(&tx_bytes).unwrap();
+    // The split is paid with the coin it splits, no other coin is touched
+    assert_eq!(tx_data.gas().len(), 1);
+    assert_eq!(tx_data.gas()[0].0, gas);
+    let TransactionKind::ProgrammableTransaction(ptb) = tx_data.kind() else {
+        panic!("Expected a programmable transaction");
+    };
+    assert!(matches!(
+        &ptb.commands[0],
+        Command::SplitCoins(Argument::GasCoin, amounts) if amounts.len() == 1
+    ));
+}
@@ -0,0 +1,137 @@
+// Copyright (c) IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use backend::data::append_record;
+use backend::network::iota_client;
+use backend::treasury::{
+    audit_log, minted_today, treasury_mint, AuditRecord, AuditStatus, TreasuryOperation,
+    TREASURY_AUDIT_FILE,
+};
+use backend::utils::ROOT_AUTH_ALIAS;
+
+use super::fixtures::{object_id, owned_object};
+use super::harness::{TestApp, NOTARIZATION_PKG_ID};
+
+/// Holds the treasury cap with the treasury alias and sets the mint limits.
+async fn treasury(app: &TestApp, per_operation: Option<u64>, per_day: Option<u64>) {
+    let cap = object_id(35);
+    let custodian = app.address(ROOT_AUTH_ALIAS).await;
+    app.mock.respond_object(
+        cap,
+        owned_object(
+            cap,
+            &format!("0x2::coin::TreasuryCap<{}::LCC::LCC>", NOTARIZATION_PKG_ID),
+            custodian,
+        ),
+    );
+    std::env::set_var("LCC_TREASURY_CAP_ID", cap.to_string());
+    for (name, limit) in [
+        ("LCC_MINT_LIMIT_PER_OPERATION", per_operation),
+        ("LCC_MINT_LIMIT_PER_DAY", per_day),
+    ] {
+        match limit {
+            Some(limit) => std::env::set_var(name, limit.to_string()),
+            None => std::env::remove_var(name),
+        }
+    }
+}
+
+fn operations_of(operator: &str) -> Vec<AuditRecord> {
+    audit_log()
+        .unwrap()
+        .into_iter()
+        .filter(|record| record.operator == operator)
+        .collect()
+}
+
+#[tokio::test]
+async fn mint_over_the_operation_limit_is_rejected() {
+    let app = TestApp::start().await;
+    treasury(&app, Some(1_000), None).await;
+    let client = iota_client().await.unwrap();
+
+    let err = treasury_mint(&client, "operation-limit", 1_001, None)
+        .await
+        .unwrap_err();
+
+    assert!(err.to_string().contains("per operation"), "{:#}", err);
+    assert!(app.mock.calls("iota_executeTransactionBlock").is_empty());
+    let records = operations_of("operation-limit");
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].status, AuditStatus::Rejected);
+    assert_eq!(records[0].amount, Some(1_001));
+}
+
+#[tokio::test]
+async fn pending_mints_count_toward_the_daily_limit() {
+    let app = TestApp::start().await;
+    let minted = minted_today().unwrap();
+    treasury(&app, None, Some(minted + 100)).await;
+    let client = iota_client().await.unwrap();
+    // A mint whose outcome was never recorded may have executed
+    append_record(
+        TREASURY_AUDIT_FILE,
+        &AuditRecord {
+            id: Some(uuid::Uuid::new_v4().to_string()),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            operation: TreasuryOperation::Mint,
+            operator: "interrupted".to_string(),
+            alias: ROOT_AUTH_ALIAS.to_string(),
+            signer: app.address(ROOT_AUTH_ALIAS).await,
+            amount: Some(60),
+            coin_id: None,
+            recipient: None,
+            digest: None,
+            status: AuditStatus::Pending,
+            error: None,
+        },
+    )
+    .unwrap();
+
+    let err = treasury_mint(&client, "daily-limit", 50, None)
+        .await
+        .unwrap_err();
+
+    assert!(err.to_string().contains("daily limit"), "{:#}", err);
+    assert!(app.mock.calls("iota_executeTransactionBlock").is_empty());
+    let records = operations_of("daily-limit");
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].status, AuditStatus::Rejected);
+    // Rejected mints do not count themselves
+    assert_eq!(minted_today().unwrap(), minted + 60);
+}
+
+#[tokio::test]
+async fn outcome_supersedes_the_pending_record() {
+    let app = TestApp::start().await;
+    let signer = app.address(ROOT_AUTH_ALIAS).await;
+    let mut record = AuditRecord {
+        id: Some(uuid::Uuid::new_v4().to_string()),
+        timestamp: 0,
+        operation: TreasuryOperation::Burn,
+        operator: "superseded".to_string(),
+        alias: ROOT_AUTH_ALIAS.to_string(),
+        signer,
+        amount: None,
+        coin_id: Some(object_id(36)),
+        recipient: None,
+        digest: None,
+        status: AuditStatus::Pending,
+        error: None,
+    };
+    append_record(TREASURY_AUDIT_FILE, &record).unwrap();
+    record.status = AuditStatus::Success;
+    record.digest = Some("digest".to_string());
+    append_record(TREASURY_AUDIT_FILE, &record).unwrap();
+
+    let records = operations_of("superseded");
+
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].status, AuditStatus::Success);
+    assert_eq!(records[0].digest.as_deref(), Some("digest"));
+}